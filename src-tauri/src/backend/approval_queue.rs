@@ -0,0 +1,148 @@
+use std::path::PathBuf;
+
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use crate::backend::error_handling::{error_loading_approval_queue, error_saving_approval_queue};
+use crate::backend::mail_list_utils::Person;
+use crate::backend::mail_sender::{MailSender, MessageTemplate};
+use crate::backend::schema::{decode, unchanged, Migration, Versioned};
+use crate::backend::storage::{approval_queue_path, write_atomically};
+
+//---------------------------
+
+static REQUESTED_AT_FORMAT: &str = "%d.%m.%Y %H:%M";
+
+//selection as it was when the mechanic sent it, files are read only after approval
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ApprovalRequest {
+    pub id: usize,
+    pub requested_at: String,
+    pub recipients: Vec<Person>,
+    pub other_people: Vec<Person>, //typed into "ostatní", not from contacts
+    pub files: Vec<PathBuf>,
+    pub note: String, //reason of rejection, written by the admin
+    #[serde(default)]
+    pub sender: Option<Person>,
+    #[serde(default)]
+    pub profile: Option<usize>, //None - main profile
+    #[serde(default)]
+    pub template: Option<MessageTemplate>,
+    #[serde(default)]
+    pub resent_from: Option<usize>,
+}
+
+impl ApprovalRequest {
+    pub fn mails(&self) -> Vec<String> {
+        self.recipients
+            .iter()
+            .chain(self.other_people.iter())
+            .map(|person| person.mail.clone())
+            .collect()
+    }
+
+    pub fn file_names(&self) -> Vec<String> {
+        self.files
+            .iter()
+            .map(|path| {
+                path.file_name()
+                    .and_then(|name| name.to_str())
+                    .unwrap_or("soubor")
+                    .to_string()
+            })
+            .collect()
+    }
+}
+
+//sends waiting for the admin in settings
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(default)]
+pub struct ApprovalQueue {
+    version: u32,
+    next_id: usize,
+    requests: Vec<ApprovalRequest>,
+}
+
+impl ApprovalQueue {
+    //missing file is an empty queue
+    pub fn load(app: AppHandle) -> ApprovalQueue {
+        let empty = ApprovalQueue {
+            version: ApprovalQueue::CURRENT_VERSION,
+            ..Default::default()
+        };
+
+        let Ok(ron_string) = std::fs::read_to_string(approval_queue_path(&app)) else {
+            return empty;
+        };
+
+        decode::<ApprovalQueue>(&ron_string).unwrap_or_else(|_| {
+            error_loading_approval_queue(app);
+            empty
+        })
+    }
+
+    pub fn save(&self, app: AppHandle) {
+        let Ok(ron_string) = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+        else {
+            return error_saving_approval_queue(app);
+        };
+
+        write_atomically(&approval_queue_path(&app), &ron_string)
+            .unwrap_or_else(|_| error_saving_approval_queue(app));
+    }
+
+    pub fn requests(&self) -> &[ApprovalRequest] {
+        &self.requests
+    }
+
+    //files, sender and template are taken from the selection
+    pub fn add(
+        &mut self,
+        recipients: Vec<Person>,
+        other_people: Vec<Person>,
+        mail: &MailSender,
+        profile: Option<usize>,
+    ) {
+        self.requests.push(ApprovalRequest {
+            id: self.next_id,
+            requested_at: Local::now().format(REQUESTED_AT_FORMAT).to_string(),
+            recipients,
+            other_people,
+            files: mail.files(),
+            note: String::new(),
+            sender: mail.sender().cloned(),
+            profile,
+            template: mail.template().cloned(),
+            resent_from: mail.resent_from(),
+        });
+
+        self.next_id += 1;
+    }
+
+    pub fn save_note(&mut self, id: usize, note: String) {
+        if let Some(request) = self.requests.iter_mut().find(|request| request.id == id) {
+            request.note = note;
+        }
+    }
+
+    //request leaves the queue, it is put back if sending fails
+    pub fn take(&mut self, id: usize) -> Option<ApprovalRequest> {
+        let index = self.requests.iter().position(|request| request.id == id)?;
+
+        Some(self.requests.remove(index))
+    }
+
+    pub fn put_back(&mut self, request: ApprovalRequest) {
+        self.requests.push(request);
+        self.requests.sort_by_key(|request| request.id);
+    }
+}
+
+impl Versioned for ApprovalQueue {
+    const CURRENT_VERSION: u32 = 1;
+
+    fn migrations() -> &'static [Migration] {
+        &[unchanged]
+    }
+}
@@ -1,13 +1,19 @@
+use chrono::Local;
 use lettre::transport::smtp::authentication::Credentials;
 use serde::{Deserialize, Serialize};
 use tauri::AppHandle;
 
 use crate::backend::error_handling::*;
+use crate::backend::schema::{Migration, SchemaError, Versioned};
+use crate::backend::storage::{config_path, migrate_file, write_atomically, write_with_backup};
 
 //---------------------------
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+//missing fields are filled with defaults, so adding a field doesn't break older files
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(default)]
 pub struct Config {
+    version: u32,
     sender_name: String,
     sender_mail: String,
     sender_password: String,
@@ -17,6 +23,85 @@ pub struct Config {
     feedback_recepient: String,
     feedback_subject: String,
     settings_password: String,
+    internal_domains: Vec<String>,
+    allowed_domains: Vec<String>, //empty - every external domain that isn't blocked
+    blocked_domains: Vec<String>,
+    send_confirmation: SendConfirmation,
+    undo_delay: u32, //seconds the message is held before sending, 0 - sent right away
+    approval: ApprovalRule,
+    approval_roles: Vec<String>, //sends of these roles always wait for approval
+    signature: String,           //under the mechanic's name, empty - sender name
+    profiles: Vec<SenderProfile>, //other mailboxes, fields above are the main profile
+    group_profiles: Vec<GroupProfile>,
+    archive_retention_days: u32, //sent messages older than this are purged, 0 - kept forever
+}
+
+//mailbox of a department, e.g. warranty or parts
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct SenderProfile {
+    pub id: usize,
+    pub name: String,
+    pub sender_name: String,
+    pub sender_mail: String,
+    pub sender_password: String,
+    pub smtp_transport: String,
+    pub title: String,
+    pub signature: String,
+}
+
+//profile picked by the mechanic on the main screen, or stored in a preset
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+pub enum ProfileChoice {
+    #[default]
+    Auto, //by categories of recipients, main profile when none has a default
+    Main,
+    Profile(usize),
+}
+
+impl ProfileChoice {
+    pub fn parse(text: &str) -> ProfileChoice {
+        match text {
+            "main" => ProfileChoice::Main,
+            _ => text
+                .parse()
+                .map(ProfileChoice::Profile)
+                .unwrap_or(ProfileChoice::Auto),
+        }
+    }
+}
+
+//profile picked automatically when someone from the category is a recipient
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct GroupProfile {
+    pub category_id: usize,
+    pub profile_id: usize,
+}
+
+//which sends wait in settings until the admin approves them
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+pub enum ApprovalRule {
+    #[default]
+    Never,
+    External,
+    Always,
+}
+
+//when is the summary shown before sending
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+pub enum SendConfirmation {
+    #[default]
+    Never,
+    External,
+    Always,
+}
+
+//where is a recipient according to domain policy in config
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RecipientDomain {
+    Internal,
+    External,
+    Blocked,
 }
 
 impl Config {
@@ -24,11 +109,13 @@ impl Config {
         let ron_string = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
             .unwrap_or_else(|_| error_parsing_config_to_string(app.clone()));
 
-        std::fs::write("config.ron", ron_string).unwrap_or_else(|_| error_saving_config(app));
+        write_with_backup(&config_path(&app), &ron_string)
+            .unwrap_or_else(|_| error_saving_config(app));
     }
     pub fn load_config(app: AppHandle) -> Config {
-        let ron_string: String = std::fs::read_to_string("config.ron")
+        let ron_string: String = std::fs::read_to_string(config_path(&app))
             .unwrap_or_else(|_| error_loading_config(app.clone()));
+        let ron_string = migrate_file::<Config>(app.clone(), &config_path(&app), ron_string);
         let result: Config = ron::de::from_str(&ron_string)
             .unwrap_or_else(|_| error_decoding_config_from_string(app.clone(), &ron_string));
         result
@@ -90,13 +177,261 @@ impl Config {
     pub fn save_settings_password(&mut self, text: String) {
         self.settings_password = text;
     }
+    pub fn signature(&self) -> &str {
+        &self.signature
+    }
+    pub fn save_signature(&mut self, text: String) {
+        self.signature = text;
+    }
+    pub fn profiles(&self) -> &[SenderProfile] {
+        &self.profiles
+    }
+    pub fn profile(&self, id: usize) -> Option<&SenderProfile> {
+        self.profiles.iter().find(|profile| profile.id == id)
+    }
+    //archived message is sent again through the mailbox it left from
+    pub fn profile_by_mail(&self, mail: &str) -> Option<usize> {
+        self.profiles
+            .iter()
+            .find(|profile| profile.sender_mail.eq_ignore_ascii_case(mail))
+            .map(|profile| profile.id)
+    }
+    pub fn add_profile(&mut self) -> usize {
+        let id = self
+            .profiles
+            .iter()
+            .map(|profile| profile.id + 1)
+            .max()
+            .unwrap_or_default();
+
+        self.profiles.push(SenderProfile {
+            id,
+            name: "nový profil".to_string(),
+            smtp_transport: self.smtp_transport.clone(),
+            ..Default::default()
+        });
+
+        id
+    }
+    pub fn remove_profile(&mut self, id: usize) {
+        self.profiles.retain(|profile| profile.id != id);
+        self.group_profiles
+            .retain(|group_profile| group_profile.profile_id != id);
+    }
+    pub fn edit_profile(&mut self, id: usize, field: &str, text: String) {
+        let Some(profile) = self.profiles.iter_mut().find(|profile| profile.id == id) else {
+            return;
+        };
+
+        match field {
+            "name" => profile.name = text,
+            "sender_name" => profile.sender_name = text,
+            "sender_mail" => profile.sender_mail = text,
+            "sender_password" => profile.sender_password = text,
+            "smtp_transport" => profile.smtp_transport = text,
+            "title" => profile.title = text,
+            "signature" => profile.signature = text,
+            _ => {}
+        }
+    }
+    pub fn group_profile(&self, category_id: usize) -> Option<usize> {
+        self.group_profiles
+            .iter()
+            .find(|group_profile| group_profile.category_id == category_id)
+            .map(|group_profile| group_profile.profile_id)
+    }
+    //anything else than a profile id means the main profile
+    pub fn save_group_profile(&mut self, category_id: usize, text: String) {
+        self.group_profiles
+            .retain(|group_profile| group_profile.category_id != category_id);
+
+        if let Ok(profile_id) = text.parse::<usize>() {
+            if self.profile(profile_id).is_some() {
+                self.group_profiles.push(GroupProfile {
+                    category_id,
+                    profile_id,
+                });
+            }
+        }
+    }
+    //config the message is sent with - profile replaces the main sender,
+    //{mechanik} and {datum} in the subject are filled in
+    pub fn for_send(&self, profile: Option<usize>, mechanic: Option<&str>) -> Config {
+        let mut config = self.clone();
+
+        if let Some(profile) = profile.and_then(|id| self.profile(id)) {
+            config.sender_name = profile.sender_name.clone();
+            config.sender_mail = profile.sender_mail.clone();
+            config.sender_password = profile.sender_password.clone();
+            config.smtp_transport = profile.smtp_transport.clone();
+            config.title = profile.title.clone();
+            config.signature = profile.signature.clone();
+        }
+
+        config.title = fill_placeholders(&config.title, mechanic);
+
+        config
+    }
+    //subject of a preset replaces the one of the profile, empty keeps it
+    pub fn override_title(&mut self, title: &str, mechanic: Option<&str>) {
+        if !title.trim().is_empty() {
+            self.title = fill_placeholders(title, mechanic);
+        }
+    }
     pub fn settings_password_check(&self, password: &str) -> bool {
         self.settings_password == password
     }
+    pub fn internal_domains(&self) -> &[String] {
+        &self.internal_domains
+    }
+    pub fn save_internal_domains(&mut self, text: String) {
+        self.internal_domains = parse_domains(&text);
+    }
+    pub fn allowed_domains(&self) -> &[String] {
+        &self.allowed_domains
+    }
+    pub fn save_allowed_domains(&mut self, text: String) {
+        self.allowed_domains = parse_domains(&text);
+    }
+    pub fn blocked_domains(&self) -> &[String] {
+        &self.blocked_domains
+    }
+    pub fn save_blocked_domains(&mut self, text: String) {
+        self.blocked_domains = parse_domains(&text);
+    }
+    pub fn send_confirmation(&self) -> SendConfirmation {
+        self.send_confirmation
+    }
+    pub fn save_send_confirmation(&mut self, text: String) {
+        self.send_confirmation = match text.as_str() {
+            "external" => SendConfirmation::External,
+            "always" => SendConfirmation::Always,
+            _ => SendConfirmation::Never,
+        };
+    }
+    pub fn undo_delay(&self) -> u32 {
+        self.undo_delay
+    }
+    pub fn save_undo_delay(&mut self, text: String) {
+        self.undo_delay = text.trim().parse().unwrap_or_default();
+    }
+    pub fn archive_retention_days(&self) -> u32 {
+        self.archive_retention_days
+    }
+    pub fn save_archive_retention_days(&mut self, text: String) {
+        self.archive_retention_days = text.trim().parse().unwrap_or_default();
+    }
+    pub fn approval(&self) -> ApprovalRule {
+        self.approval
+    }
+    pub fn save_approval(&mut self, text: String) {
+        self.approval = match text.as_str() {
+            "external" => ApprovalRule::External,
+            "always" => ApprovalRule::Always,
+            _ => ApprovalRule::Never,
+        };
+    }
+    pub fn approval_roles(&self) -> &[String] {
+        &self.approval_roles
+    }
+    pub fn save_approval_roles(&mut self, text: String) {
+        self.approval_roles = text
+            .split([',', ';'])
+            .map(|role| role.trim().to_string())
+            .filter(|role| !role.is_empty())
+            .collect();
+    }
+    //roles are compared without case, "Učeň" is "učeň"
+    pub fn role_needs_approval(&self, role: &str) -> bool {
+        let role = role.trim().to_lowercase();
+
+        !role.is_empty()
+            && self
+                .approval_roles
+                .iter()
+                .any(|listed| listed.to_lowercase() == role)
+    }
+    //without internal domains every recipient is external, until the admin fills them in
+    pub fn recipient_domain(&self, mail: &str) -> RecipientDomain {
+        let domain = mail
+            .trim()
+            .rsplit_once('@')
+            .map(|(_, domain)| domain.to_lowercase())
+            .unwrap_or_default();
+
+        if domain_matches(&domain, &self.blocked_domains) {
+            RecipientDomain::Blocked
+        } else if domain_matches(&domain, &self.internal_domains) {
+            RecipientDomain::Internal
+        } else if !self.allowed_domains.is_empty()
+            && !domain_matches(&domain, &self.allowed_domains)
+        {
+            RecipientDomain::Blocked
+        } else {
+            RecipientDomain::External
+        }
+    }
+}
+
+//"servis.cz, @firma.cz" -> ["servis.cz", "firma.cz"]
+fn parse_domains(text: &str) -> Vec<String> {
+    text.split(|c: char| c == ',' || c == ';' || c.is_whitespace())
+        .map(|domain| domain.trim().trim_start_matches('@').to_lowercase())
+        .filter(|domain| !domain.is_empty())
+        .collect()
+}
+
+//subdomains belong to their domain, mail.firma.cz is firma.cz
+fn domain_matches(domain: &str, domains: &[String]) -> bool {
+    domains
+        .iter()
+        .any(|listed| domain == listed || domain.ends_with(&format!(".{listed}")))
+}
+
+impl Versioned for Config {
+    const CURRENT_VERSION: u32 = 1;
+
+    fn migrations() -> &'static [Migration] {
+        &[config_v0_to_v1]
+    }
+}
+
+//layout of versions 0 and 1 as it was before the version field, later fields are serde defaults
+#[derive(Serialize, Deserialize)]
+struct LegacyConfig {
+    #[serde(default)]
+    version: u32,
+    sender_name: String,
+    sender_mail: String,
+    sender_password: String,
+    title: String,
+    smtp_transport: String,
+    feedback_mail: String,
+    feedback_recepient: String,
+    feedback_subject: String,
+    settings_password: String,
+}
+
+//version 0 had no version field, the layout is otherwise the same
+fn config_v0_to_v1(ron_string: &str) -> Result<String, SchemaError> {
+    let mut config: LegacyConfig = ron::de::from_str(ron_string)?;
+    config.version = 1;
+
+    Ok(ron::ser::to_string_pretty(
+        &config,
+        ron::ser::PrettyConfig::default(),
+    )?)
+}
+
+//{mechanik} and {datum} in subjects and body templates
+pub fn fill_placeholders(text: &str, mechanic: Option<&str>) -> String {
+    text.replace("{mechanik}", mechanic.unwrap_or_default())
+        .replace("{datum}", &Local::now().format("%d.%m.%Y").to_string())
 }
 
 pub fn create_empty_config(app: AppHandle) -> String {
     static EMPTY_CONFIG: &str = "(
+    version: 1,
     sender_name: \"\",
     sender_mail: \"\",
     sender_password: \"\",
@@ -106,15 +441,28 @@ pub fn create_empty_config(app: AppHandle) -> String {
     feedback_recepient: \"\",
     feedback_subject: \"\",
     settings_password: \"\",
+    internal_domains: [],
+    allowed_domains: [],
+    blocked_domains: [],
+    send_confirmation: Never,
+    undo_delay: 0,
+    approval: Never,
+    approval_roles: [],
+    signature: \"\",
+    profiles: [],
+    group_profiles: [],
+    archive_retention_days: 0,
     )";
 
-    std::fs::write("config.ron", EMPTY_CONFIG).unwrap_or_else(|_| error_of_fail_back_system(app));
+    write_atomically(&config_path(&app), EMPTY_CONFIG)
+        .unwrap_or_else(|_| error_of_fail_back_system(app));
 
     EMPTY_CONFIG.to_string()
 }
 
 pub fn empty_config() -> Config {
     Config {
+        version: Config::CURRENT_VERSION,
         sender_name: "".to_string(),
         sender_mail: "".to_string(),
         sender_password: "".to_string(),
@@ -124,5 +472,16 @@ pub fn empty_config() -> Config {
         feedback_recepient: "".to_string(),
         feedback_subject: "".to_string(),
         settings_password: "".to_string(),
+        internal_domains: vec![],
+        allowed_domains: vec![],
+        blocked_domains: vec![],
+        send_confirmation: SendConfirmation::Never,
+        undo_delay: 0,
+        approval: ApprovalRule::Never,
+        approval_roles: vec![],
+        signature: "".to_string(),
+        profiles: vec![],
+        group_profiles: vec![],
+        archive_retention_days: 0,
     }
 }
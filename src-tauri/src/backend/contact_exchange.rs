@@ -0,0 +1,550 @@
+use lettre::Address;
+use std::path::Path;
+use thiserror::Error;
+
+use crate::backend::mail_list_utils::{Contact, MailList};
+
+//---------------------------
+
+#[derive(Error, Debug)]
+pub enum ContactExchangeError {
+    #[error("unsupported file type")]
+    UnsupportedFormat,
+
+    #[error("couldn't read file: {0}")]
+    Read(#[from] std::io::Error),
+
+    #[error("file contains no contacts")]
+    Empty,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ContactFormat {
+    Csv,
+    VCard,
+}
+
+impl ContactFormat {
+    pub fn from_path(path: &Path) -> Result<ContactFormat, ContactExchangeError> {
+        let extension = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .map(|extension| extension.to_ascii_lowercase());
+
+        match extension.as_deref() {
+            Some("csv") | Some("txt") => Ok(ContactFormat::Csv),
+            Some("vcf") | Some("vcard") => Ok(ContactFormat::VCard),
+            _ => Err(ContactExchangeError::UnsupportedFormat),
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ContactFormat::Csv => "csv",
+            ContactFormat::VCard => "vcf",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CsvField {
+    Name,
+    Mail,
+    Phone,
+    Role,
+    Category,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct CsvMapping {
+    pub name: Option<usize>,
+    pub mail: Option<usize>,
+    pub phone: Option<usize>,
+    pub role: Option<usize>,
+    pub category: Option<usize>,
+}
+
+impl CsvMapping {
+    //first header containing one of the known names is used
+    fn guess(headers: &[String]) -> CsvMapping {
+        let find = |names: &[&str]| {
+            headers.iter().position(|header| {
+                let header = header.trim().to_lowercase();
+                names.iter().any(|name| header.contains(name))
+            })
+        };
+
+        CsvMapping {
+            name: find(&["jméno", "jmeno", "name", "fn"]),
+            mail: find(&["mail"]),
+            phone: find(&["telefon", "phone", "tel"]),
+            role: find(&["funkce", "pozice", "role", "title"]),
+            category: find(&["kategorie", "category", "skupina", "group"]),
+        }
+    }
+
+    pub fn column(&self, field: CsvField) -> Option<usize> {
+        match field {
+            CsvField::Name => self.name,
+            CsvField::Mail => self.mail,
+            CsvField::Phone => self.phone,
+            CsvField::Role => self.role,
+            CsvField::Category => self.category,
+        }
+    }
+
+    pub fn set_column(&mut self, field: CsvField, column: Option<usize>) {
+        match field {
+            CsvField::Name => self.name = column,
+            CsvField::Mail => self.mail = column,
+            CsvField::Phone => self.phone = column,
+            CsvField::Role => self.role = column,
+            CsvField::Category => self.category = column,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportedContact {
+    pub name: String,
+    pub mail: String,
+    pub phone: String,
+    pub role: String,
+    pub category: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ImportChange {
+    Add(ImportedContact),
+    Update {
+        id: usize,
+        old_name: String,
+        old_mail: String,
+        contact: ImportedContact,
+    },
+    Unchanged(ImportedContact),
+    Conflict {
+        contact: ImportedContact,
+        reason: String,
+    },
+}
+
+//file loaded in settings, waiting for confirmation
+#[derive(Debug, Clone)]
+pub struct ContactImport {
+    pub format: ContactFormat,
+    pub headers: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+    pub mapping: CsvMapping,
+    vcard_contacts: Vec<ImportedContact>,
+}
+
+impl ContactImport {
+    pub fn load(path: &Path) -> Result<ContactImport, ContactExchangeError> {
+        let format = ContactFormat::from_path(path)?;
+        let text = std::fs::read_to_string(path)?;
+        //excel likes to save BOM at the start of the file
+        let text = text.trim_start_matches('\u{feff}');
+
+        let import = match format {
+            ContactFormat::Csv => {
+                let mut rows = parse_csv(text);
+                if rows.is_empty() {
+                    return Err(ContactExchangeError::Empty);
+                }
+                let headers = rows.remove(0);
+
+                ContactImport {
+                    format,
+                    mapping: CsvMapping::guess(&headers),
+                    headers,
+                    rows,
+                    vcard_contacts: vec![],
+                }
+            }
+            ContactFormat::VCard => ContactImport {
+                format,
+                headers: vec![],
+                rows: vec![],
+                mapping: CsvMapping::default(),
+                vcard_contacts: parse_vcard(text),
+            },
+        };
+
+        if import.contacts().is_empty() {
+            return Err(ContactExchangeError::Empty);
+        }
+
+        Ok(import)
+    }
+
+    //rows without name and mail are skipped
+    pub fn contacts(&self) -> Vec<ImportedContact> {
+        match self.format {
+            ContactFormat::VCard => self.vcard_contacts.clone(),
+            ContactFormat::Csv => {
+                let cell = |row: &Vec<String>, column: Option<usize>| {
+                    column
+                        .and_then(|column| row.get(column))
+                        .map(|value| value.trim().to_string())
+                        .unwrap_or_default()
+                };
+
+                self.rows
+                    .iter()
+                    .map(|row| ImportedContact {
+                        name: cell(row, self.mapping.name),
+                        mail: cell(row, self.mapping.mail),
+                        phone: cell(row, self.mapping.phone),
+                        role: cell(row, self.mapping.role),
+                        category: Some(cell(row, self.mapping.category))
+                            .filter(|category| !category.is_empty()),
+                    })
+                    .filter(|contact| !contact.name.is_empty() || !contact.mail.is_empty())
+                    .collect()
+            }
+        }
+    }
+
+    //contacts are matched by mail first, then by name
+    pub fn plan(&self, mail_list: &MailList) -> Vec<ImportChange> {
+        let imported = self.contacts();
+
+        imported
+            .iter()
+            .enumerate()
+            .map(|(index, contact)| {
+                let conflict = |reason: &str| ImportChange::Conflict {
+                    contact: contact.clone(),
+                    reason: reason.to_string(),
+                };
+
+                if contact.name.is_empty() {
+                    return conflict("chybí jméno");
+                }
+                if contact.mail.parse::<Address>().is_err() {
+                    return conflict("neplatný E-mail");
+                }
+
+                let duplicate = imported[..index]
+                    .iter()
+                    .any(|other| other.mail.eq_ignore_ascii_case(&contact.mail));
+                if duplicate {
+                    return conflict("E-mail je v souboru vícekrát");
+                }
+
+                let by_mail = mail_list
+                    .contacts()
+                    .find(|existing| existing.has_mail(&contact.mail));
+                let by_name: Vec<_> = mail_list
+                    .contacts()
+                    .filter(|existing| existing.name.trim() == contact.name)
+                    .collect();
+
+                let update = |existing: &Contact| ImportChange::Update {
+                    id: existing.id,
+                    old_name: existing.name.clone(),
+                    old_mail: existing.mail().to_string(),
+                    contact: contact.clone(),
+                };
+
+                match (by_mail, by_name.as_slice()) {
+                    (Some(existing), _) if existing.name.trim() == contact.name => {
+                        ImportChange::Unchanged(contact.clone())
+                    }
+                    (Some(_), [_, ..]) => conflict("E-mail patří jiné osobě, jméno má jiná osoba"),
+                    (Some(existing), []) => update(existing),
+                    (None, [existing]) => update(existing),
+                    (None, []) => ImportChange::Add(contact.clone()),
+                    (None, _) => conflict("jméno má více osob"),
+                }
+            })
+            .collect()
+    }
+}
+
+//quoted fields may contain separators, quotes ("") and new lines
+fn parse_csv(text: &str) -> Vec<Vec<String>> {
+    let first_line = text.lines().next().unwrap_or_default();
+    //czech excel separates columns with semicolon
+    let separator = if first_line.matches(';').count() >= first_line.matches(',').count() {
+        ';'
+    } else {
+        ','
+    };
+
+    let mut rows: Vec<Vec<String>> = vec![];
+    let mut row: Vec<String> = vec![];
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            c if c == separator && !quoted => row.push(std::mem::take(&mut field)),
+            '\r' if !quoted => {}
+            '\n' if !quoted => {
+                row.push(std::mem::take(&mut field));
+                rows.push(std::mem::take(&mut row));
+            }
+            c => field.push(c),
+        }
+    }
+
+    if !field.is_empty() || !row.is_empty() {
+        row.push(field);
+        rows.push(row);
+    }
+
+    rows.retain(|row| row.iter().any(|field| !field.trim().is_empty()));
+    rows
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([';', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+//handles vCard 3 and 4, only the first (or preferred) E-mail and phone are used
+fn parse_vcard(text: &str) -> Vec<ImportedContact> {
+    //folded lines continue with space or tab
+    let mut lines: Vec<String> = vec![];
+    for line in text.lines() {
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(continuation), Some(last)) => last.push_str(continuation),
+            _ => lines.push(line.trim_end().to_string()),
+        }
+    }
+
+    let mut contacts: Vec<ImportedContact> = vec![];
+    let mut current: Option<(ImportedContact, bool)> = None;
+
+    for line in lines {
+        let Some((property, value)) = line.split_once(':') else {
+            continue;
+        };
+        let mut parameters = property.split(';');
+        let name = parameters.next().unwrap_or_default().to_ascii_uppercase();
+        //grouped properties look like item1.EMAIL
+        let name = name.rsplit('.').next().unwrap_or_default();
+        let preferred = parameters.any(|parameter| {
+            let parameter = parameter.to_ascii_uppercase();
+            parameter.contains("PREF")
+        });
+
+        match (name, current.as_mut()) {
+            ("BEGIN", _) if value.eq_ignore_ascii_case("VCARD") => {
+                current = Some((
+                    ImportedContact {
+                        name: String::new(),
+                        mail: String::new(),
+                        phone: String::new(),
+                        role: String::new(),
+                        category: None,
+                    },
+                    false,
+                ));
+            }
+            ("END", Some(_)) if value.eq_ignore_ascii_case("VCARD") => {
+                if let Some((contact, _)) = current.take() {
+                    if !contact.name.is_empty() || !contact.mail.is_empty() {
+                        contacts.push(contact);
+                    }
+                }
+            }
+            ("FN", Some((contact, _))) => contact.name = unescape_vcard(value),
+            //N is used only when FN is missing - family;given;...
+            ("N", Some((contact, _))) if contact.name.is_empty() => {
+                let parts: Vec<String> = value.split(';').map(unescape_vcard).collect();
+                contact.name = [parts.get(1), parts.first()]
+                    .into_iter()
+                    .flatten()
+                    .filter(|part| !part.is_empty())
+                    .cloned()
+                    .collect::<Vec<String>>()
+                    .join(" ");
+            }
+            ("EMAIL", Some((contact, has_preferred)))
+                if contact.mail.is_empty() || (preferred && !*has_preferred) =>
+            {
+                contact.mail = unescape_vcard(value).trim().to_string();
+                *has_preferred = preferred;
+            }
+            ("TEL", Some((contact, _))) if contact.phone.is_empty() => {
+                contact.phone = unescape_vcard(value);
+            }
+            ("TITLE", Some((contact, _))) | ("ROLE", Some((contact, _)))
+                if contact.role.is_empty() =>
+            {
+                contact.role = unescape_vcard(value);
+            }
+            ("CATEGORIES", Some((contact, _))) => {
+                contact.category = value
+                    .split(',')
+                    .map(unescape_vcard)
+                    .find(|category| !category.trim().is_empty());
+            }
+            _ => {}
+        }
+    }
+
+    contacts
+}
+
+fn unescape_vcard(value: &str) -> String {
+    value
+        .replace("\\n", " ")
+        .replace("\\N", " ")
+        .replace("\\,", ",")
+        .replace("\\;", ";")
+        .replace("\\\\", "\\")
+        .trim()
+        .to_string()
+}
+
+fn escape_vcard(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace(',', "\\,")
+        .replace(';', "\\;")
+        .replace('\n', "\\n")
+}
+
+//N is family;given;additional;prefix;suffix, the last word of the name is taken as family name
+//import joins it back as "given family"
+fn vcard_name(name: &str) -> String {
+    let name = name.trim();
+
+    let (given, family) = name.rsplit_once(char::is_whitespace).unwrap_or(("", name));
+
+    format!("{};{};;;", escape_vcard(family), escape_vcard(given.trim()))
+}
+
+pub fn export_contacts(mail_list: &MailList, format: ContactFormat) -> String {
+    let mut export = String::new();
+
+    match format {
+        ContactFormat::Csv => {
+            export.push_str("kategorie;jméno;E-mail;telefon;funkce\r\n");
+            for category in mail_list.categories() {
+                for contact in category
+                    .contacts
+                    .iter()
+                    .filter(|c| !c.name.trim().is_empty())
+                {
+                    export.push_str(&format!(
+                        "{};{};{};{};{}\r\n",
+                        csv_field(&category.name),
+                        csv_field(&contact.name),
+                        csv_field(contact.mail()),
+                        csv_field(&contact.phone),
+                        csv_field(&contact.role)
+                    ));
+                }
+            }
+        }
+        ContactFormat::VCard => {
+            for category in mail_list.categories() {
+                for contact in category
+                    .contacts
+                    .iter()
+                    .filter(|c| !c.name.trim().is_empty())
+                {
+                    export.push_str("BEGIN:VCARD\r\nVERSION:3.0\r\n");
+                    export.push_str(&format!("FN:{}\r\n", escape_vcard(&contact.name)));
+                    export.push_str(&format!("N:{}\r\n", vcard_name(&contact.name)));
+                    for (index, address) in contact.addresses.iter().enumerate() {
+                        let preferred = if index == contact.primary {
+                            ",PREF"
+                        } else {
+                            ""
+                        };
+                        export.push_str(&format!(
+                            "EMAIL;TYPE=INTERNET{preferred};X-LABEL={}:{}\r\n",
+                            escape_vcard(&address.label),
+                            escape_vcard(&address.mail)
+                        ));
+                    }
+                    if !contact.phone.is_empty() {
+                        export.push_str(&format!("TEL:{}\r\n", escape_vcard(&contact.phone)));
+                    }
+                    if !contact.role.is_empty() {
+                        export.push_str(&format!("TITLE:{}\r\n", escape_vcard(&contact.role)));
+                    }
+                    if !contact.notes.is_empty() {
+                        export.push_str(&format!("NOTE:{}\r\n", escape_vcard(&contact.notes)));
+                    }
+                    export.push_str(&format!("CATEGORIES:{}\r\n", escape_vcard(&category.name)));
+                    export.push_str("END:VCARD\r\n");
+                }
+            }
+        }
+    }
+
+    export
+}
+
+//conflicts and unchanged contacts are skipped, updated contacts stay in their category
+pub fn apply_import(mail_list: &mut MailList, changes: &[ImportChange]) -> usize {
+    static DEFAULT_CATEGORY: &str = "importované";
+
+    let mut applied = 0;
+
+    for change in changes {
+        match change {
+            ImportChange::Add(contact) => {
+                let category_name = contact.category.as_deref().unwrap_or(DEFAULT_CATEGORY);
+
+                let category_id = match mail_list
+                    .categories()
+                    .iter()
+                    .find(|category| category.name.trim() == category_name)
+                {
+                    Some(category) => category.id,
+                    None => mail_list.add_category(category_name.to_string()),
+                };
+
+                if let Some(id) = mail_list.add_contact(category_id) {
+                    mail_list.save_person_name(id, contact.name.clone());
+                    mail_list.save_person_mail(id, contact.mail.clone());
+                    mail_list.update_contact(id, |existing| {
+                        existing.phone = contact.phone.clone();
+                        existing.role = contact.role.clone();
+                    });
+                    applied += 1;
+                }
+            }
+            //mail matching secondary address doesn't replace the primary one
+            ImportChange::Update { id, contact, .. } => {
+                mail_list.update_contact(*id, |existing| {
+                    existing.name = contact.name.clone();
+
+                    if !existing.has_mail(&contact.mail) {
+                        if existing.addresses.is_empty() {
+                            existing.add_address();
+                        }
+                        existing.addresses[existing.primary].mail = contact.mail.clone();
+                    }
+                    if !contact.phone.is_empty() {
+                        existing.phone = contact.phone.clone();
+                    }
+                    if !contact.role.is_empty() {
+                        existing.role = contact.role.clone();
+                    }
+                });
+                applied += 1;
+            }
+            ImportChange::Unchanged(_) | ImportChange::Conflict { .. } => {}
+        }
+    }
+
+    applied
+}
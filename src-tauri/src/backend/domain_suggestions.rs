@@ -0,0 +1,275 @@
+use std::collections::BTreeMap;
+
+use crate::backend::mail_list_utils::{ListMember, MailList};
+use crate::backend::send_stats::SendStats;
+
+//---------------------------
+
+//domains the mechanics send to the most, on equal distance they win over learned ones
+static COMMON_DOMAINS: [&str; 16] = [
+    "seznam.cz",
+    "email.cz",
+    "post.cz",
+    "centrum.cz",
+    "atlas.cz",
+    "volny.cz",
+    "tiscali.cz",
+    "quick.cz",
+    "iol.cz",
+    "outlook.cz",
+    "gmail.com",
+    "outlook.com",
+    "hotmail.com",
+    "live.com",
+    "yahoo.com",
+    "icloud.com",
+];
+
+//single mistyped address shouldn't teach its own typo
+static LEARNED_MIN_COUNT: u32 = 2;
+
+//"did you mean ...?" for mistyped domains, like seznam.cs or gmial.com
+pub struct DomainSuggestions {
+    known: Vec<String>,
+}
+
+impl DomainSuggestions {
+    //domains are learned from contacts, distribution lists and send history
+    pub fn new(mail_list: &MailList, send_stats: &SendStats) -> DomainSuggestions {
+        let mut counts: BTreeMap<String, u32> = BTreeMap::new();
+
+        let mut learn = |mail: &str, count: u32| {
+            if let Some(domain) = domain(mail) {
+                *counts.entry(domain).or_default() += count;
+            }
+        };
+
+        for contact in mail_list.contacts() {
+            for address in &contact.addresses {
+                learn(&address.mail, 1);
+            }
+        }
+
+        for list in mail_list.distribution_lists() {
+            for member in &list.members {
+                if let ListMember::Address(mail) = member {
+                    learn(mail, 1);
+                }
+            }
+        }
+
+        for (mail, count) in send_stats.counts() {
+            learn(mail, count);
+        }
+
+        let mut known: Vec<String> = COMMON_DOMAINS
+            .iter()
+            .map(|domain| domain.to_string())
+            .collect();
+
+        //typo of a common domain sent again stays a typo
+        for (domain, count) in counts {
+            if count >= LEARNED_MIN_COUNT
+                && !known.contains(&domain)
+                && !COMMON_DOMAINS
+                    .iter()
+                    .any(|common| edit_distance(&domain, common) <= 1)
+            {
+                known.push(domain);
+            }
+        }
+
+        DomainSuggestions { known }
+    }
+
+    //whole corrected mail, None when the domain is known or nothing is close enough
+    pub fn suggest(&self, mail: &str) -> Option<String> {
+        let (local, domain) = mail.trim().rsplit_once('@')?;
+
+        if local.is_empty() || domain.is_empty() {
+            return None;
+        }
+
+        let domain = domain.to_lowercase();
+
+        if self.known.contains(&domain) {
+            return None;
+        }
+
+        //short domains would match too many others with two changes
+        let max_distance = if domain.chars().count() < 8 { 1 } else { 2 };
+
+        self.known
+            .iter()
+            .map(|known| (edit_distance(&domain, known), known))
+            .filter(|(distance, _)| *distance <= max_distance)
+            .min_by_key(|(distance, _)| *distance)
+            .map(|(_, known)| format!("{local}@{known}"))
+    }
+}
+
+fn domain(mail: &str) -> Option<String> {
+    let (local, domain) = mail.trim().rsplit_once('@')?;
+
+    (!local.is_empty() && domain.contains('.')).then(|| domain.to_lowercase())
+}
+
+//Damerau-Levenshtein (optimal string alignment), swapped letters count as one typo
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+
+    let mut distances = vec![vec![0; b.len() + 1]; a.len() + 1];
+
+    for (i, row) in distances.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, distance) in distances[0].iter_mut().enumerate() {
+        *distance = j;
+    }
+
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+
+            let mut distance = (distances[i - 1][j] + 1)
+                .min(distances[i][j - 1] + 1)
+                .min(distances[i - 1][j - 1] + cost);
+
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                distance = distance.min(distances[i - 2][j - 2] + 1);
+            }
+
+            distances[i][j] = distance;
+        }
+    }
+
+    distances[a.len()][b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::mail_list_utils::empty_mail_list;
+
+    fn common() -> DomainSuggestions {
+        DomainSuggestions::new(&empty_mail_list(), &SendStats::default())
+    }
+
+    #[test]
+    fn same_text_has_no_distance() {
+        assert_eq!(edit_distance("", ""), 0);
+        assert_eq!(edit_distance("seznam.cz", "seznam.cz"), 0);
+    }
+
+    #[test]
+    fn empty_text_is_as_far_as_its_length() {
+        assert_eq!(edit_distance("abc", ""), 3);
+        assert_eq!(edit_distance("", "abc"), 3);
+    }
+
+    #[test]
+    fn every_single_typo_is_one() {
+        assert_eq!(edit_distance("seznam.cs", "seznam.cz"), 1);
+        assert_eq!(edit_distance("gmaill.com", "gmail.com"), 1);
+        assert_eq!(edit_distance("gmal.com", "gmail.com"), 1);
+        assert_eq!(edit_distance("gmial.com", "gmail.com"), 1);
+    }
+
+    #[test]
+    fn letters_are_compared_not_bytes() {
+        assert_eq!(edit_distance("pošta.cz", "posta.cz"), 1);
+    }
+
+    #[test]
+    fn more_typos_add_up() {
+        assert_eq!(edit_distance("sezanm.cs", "seznam.cz"), 2);
+        assert_eq!(edit_distance("outlok.cm", "outlook.com"), 2);
+    }
+
+    #[test]
+    fn mistyped_domain_is_corrected() {
+        let suggestions = common();
+
+        assert_eq!(
+            suggestions.suggest("jan@seznam.cs"),
+            Some("jan@seznam.cz".to_string())
+        );
+        assert_eq!(
+            suggestions.suggest("jan@gmial.com"),
+            Some("jan@gmail.com".to_string())
+        );
+    }
+
+    #[test]
+    fn local_part_is_kept() {
+        assert_eq!(
+            common().suggest(" Jan.Novak@Seznam.cs "),
+            Some("Jan.Novak@seznam.cz".to_string())
+        );
+    }
+
+    #[test]
+    fn known_domain_is_not_corrected() {
+        let suggestions = common();
+
+        assert_eq!(suggestions.suggest("jan@seznam.cz"), None);
+        assert_eq!(suggestions.suggest("jan@SEZNAM.CZ"), None);
+    }
+
+    #[test]
+    fn unrelated_domain_is_not_corrected() {
+        assert_eq!(common().suggest("jan@autoservis-novak.cz"), None);
+    }
+
+    #[test]
+    fn short_domain_allows_one_typo() {
+        let suggestions = common();
+
+        assert_eq!(
+            suggestions.suggest("jan@iol.cy"),
+            Some("jan@iol.cz".to_string())
+        );
+        assert_eq!(suggestions.suggest("jan@ol.cy"), None);
+    }
+
+    #[test]
+    fn incomplete_mail_has_no_suggestion() {
+        let suggestions = common();
+
+        assert_eq!(suggestions.suggest("seznam.cs"), None);
+        assert_eq!(suggestions.suggest("@seznam.cs"), None);
+        assert_eq!(suggestions.suggest("jan@"), None);
+    }
+
+    #[test]
+    fn domains_are_learned_from_repeated_sends() {
+        let mut send_stats = SendStats::default();
+        send_stats.record([
+            "jan@autoservis-novak.cz",
+            "petr@autoservis-novak.cz",
+            "jan@autoservis-novk.cz",
+        ]);
+
+        let suggestions = DomainSuggestions::new(&empty_mail_list(), &send_stats);
+
+        assert_eq!(suggestions.suggest("jan@autoservis-novak.cz"), None);
+        assert_eq!(
+            suggestions.suggest("jan@autoservis-novk.cz"),
+            Some("jan@autoservis-novak.cz".to_string())
+        );
+    }
+
+    #[test]
+    fn repeated_typo_of_common_domain_is_not_learned() {
+        let mut send_stats = SendStats::default();
+        send_stats.record(["jan@seznam.cs", "petr@seznam.cs"]);
+
+        let suggestions = DomainSuggestions::new(&empty_mail_list(), &send_stats);
+
+        assert_eq!(
+            suggestions.suggest("jan@seznam.cs"),
+            Some("jan@seznam.cz".to_string())
+        );
+    }
+}
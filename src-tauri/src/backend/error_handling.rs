@@ -4,13 +4,20 @@ use lettre::transport::smtp::SmtpTransport;
 use lettre::transport::Transport;
 use lettre::{Address, Message};
 use std::env;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use tauri::Manager;
 use tauri_plugin_dialog::{DialogExt, MessageDialogButtons, MessageDialogKind};
 
 use crate::backend::config::{create_empty_config, empty_config, Config};
+use crate::backend::contact_exchange::ContactExchangeError;
+use crate::backend::integrity::refresh_integrity_issues;
 use crate::backend::mail_list_utils::{create_empty_mail_list, empty_mail_list, MailList};
 use crate::backend::mail_sender::MailSenderError;
+use crate::backend::send_history::SendHistory;
+use crate::backend::storage::{
+    backup_name, config_path, hold_saving, keep_broken_file, mail_list_path, newest_valid_backup,
+    release_saving, restore_backup, send_history_path,
+};
 use crate::AppState;
 
 //---------------------------
@@ -74,7 +81,7 @@ pub fn error_parsing_mail_address(app: tauri::AppHandle, original_mail: String)
 
 pub fn error_saving_config(app: tauri::AppHandle) {
     //Depending on the platform, this function may fail if the full directory path does not exist.
-    //this should't happen as file path is just name of the file.
+    //this should't happen as data directories are created on startup.
     let app_state = app.state::<AppState>();
 
     let error_message: String = format!(
@@ -89,7 +96,7 @@ pub fn error_saving_config(app: tauri::AppHandle) {
 
 pub fn error_saving_mail_list(app: tauri::AppHandle) {
     //Depending on the platform, this function may fail if the full directory path does not exist.
-    //this should't happen as file path is just name of the file.
+    //this should't happen as data directories are created on startup.
     let app_state = app.state::<AppState>();
 
     let error_message: String = format!(
@@ -102,6 +109,13 @@ pub fn error_saving_mail_list(app: tauri::AppHandle) {
     show_error_saving_mail_list_and_continue(app);
 }
 
+//statistics only rank search results, so the user isn't bothered
+pub fn error_saving_send_stats(app: tauri::AppHandle) {
+    let error_message: String = "Nepodařilo se uložit statistiku odeslaných E-mailů".to_string();
+
+    let _ = send_error_mail(error_message, app);
+}
+
 pub fn error_loading_config(app: tauri::AppHandle) -> String {
     let error_message: String = "Nepodařilo se načíst config.".to_string();
 
@@ -157,6 +171,16 @@ pub fn error_decoding_config_from_string(app: tauri::AppHandle, raw_config: &str
 
     let _ = send_error_mail(error_message, app.clone());
 
+    let path = config_path(&app);
+    let _ = keep_broken_file(&path);
+
+    //empty config is used meanwhile, but it isn't saved over the file until the user decides
+    if let Some((backup, backup_config)) = newest_valid_backup::<Config>(&path) {
+        hold_saving(&path);
+        show_restore_config_backup(app, backup, backup_config);
+        return empty_config();
+    }
+
     show_error_loading_config_and_continue(app.clone());
 
     create_empty_config(app);
@@ -172,30 +196,71 @@ pub fn error_decoding_mail_list_from_string(
 
     let _ = send_error_mail(error_message, app.clone());
 
+    let path = mail_list_path(&app);
+    let _ = keep_broken_file(&path);
+
+    //empty list is used meanwhile, but it isn't saved over the file until the user decides
+    if let Some((backup, backup_mail_list)) = newest_valid_backup::<MailList>(&path) {
+        hold_saving(&path);
+        show_restore_mail_list_backup(app, backup, backup_mail_list);
+        return empty_mail_list();
+    }
+
     show_error_loading_mail_list_and_continue(app.clone());
 
     create_empty_mail_list(app);
     empty_mail_list()
 }
 
-pub fn error_mail_list_id_overflow(app: tauri::AppHandle) -> MailList {
-    let error_message: String = "ID > mail_list.len()".to_string();
+pub fn error_of_fail_back_system(app: tauri::AppHandle) {
+    let error_message: String =
+        "Nepodařilo se uložit prázdný config/mail_list v rámci fail_back systému.".to_string();
 
     let _ = send_error_mail(error_message, app.clone());
 
-    show_error_loading_mail_list_and_continue(app.clone());
+    show_unexpected_user_error_and_quit(app)
+}
 
-    create_empty_mail_list(app.clone());
-    empty_mail_list()
+pub fn error_resolving_data_dir(app: tauri::AppHandle) -> PathBuf {
+    let error_message: String =
+        "Nepodařilo se zjistit složku pro data aplikace. Použije se pracovní složka.".to_string();
+
+    let _ = send_error_mail(error_message, app.clone());
+
+    show_error_data_dir_and_continue(app);
+
+    PathBuf::from(".")
 }
 
-pub fn error_of_fail_back_system(app: tauri::AppHandle) {
+pub fn error_creating_data_dir(app: tauri::AppHandle, dir: &Path) {
+    let error_message: String = format!(
+        "Nepodařilo se vytvořit složku pro data aplikace.\nSložka: {}",
+        dir.display()
+    );
+
+    let _ = send_error_mail(error_message, app.clone());
+
+    show_error_data_dir_and_continue(app);
+}
+
+pub fn error_migrating_data_file(app: tauri::AppHandle, file_name: &str) {
     let error_message: String =
-        "Nepodařilo se uložit prázdný config/mail_list v rámci fail_back systému.".to_string();
+        format!("Nepodařilo se přesunout {file_name} z pracovní složky do složky aplikace.");
 
     let _ = send_error_mail(error_message, app.clone());
 
-    show_unexpected_user_error_and_quit(app)
+    show_error_data_dir_and_continue(app);
+}
+
+pub fn error_migrating_file(app: tauri::AppHandle, path: &Path, reason: String) {
+    let error_message: String = format!(
+        "Nepodařilo se převést soubor na novou verzi.\nSoubor: {}\nDůvod: {reason}",
+        path.display()
+    );
+
+    let _ = send_error_mail(error_message, app.clone());
+
+    show_error_migrating_file_and_continue(app);
 }
 
 pub fn error_showing_file_name(app: tauri::AppHandle) {
@@ -207,6 +272,181 @@ pub fn error_showing_file_name(app: tauri::AppHandle) {
     show_error_showing_file_name_and_continue(app);
 }
 
+//wrong file is user's mistake, so author isn't informed
+pub fn error_importing_contacts(app: tauri::AppHandle, error: ContactExchangeError) {
+    let reason = match error {
+        ContactExchangeError::UnsupportedFormat => {
+            "Podporované jsou pouze soubory CSV a vCard (.vcf).".to_string()
+        }
+        ContactExchangeError::Read(_) => "Soubor nelze přečíst.".to_string(),
+        ContactExchangeError::Empty => "Soubor neobsahuje žádné kontakty.".to_string(),
+    };
+
+    show_error_importing_contacts_and_continue(app, reason);
+}
+
+pub fn error_exporting_contacts(app: tauri::AppHandle) {
+    let error_message: String = "Nepodařilo se uložit export kontaktů".to_string();
+
+    let _ = send_error_mail(error_message, app.clone());
+
+    show_error_exporting_contacts_and_continue(app);
+}
+
+pub fn error_loading_scheduled_sends(app: tauri::AppHandle) {
+    let error_message: String = "Nepodařilo se načíst naplánované E-maily".to_string();
+
+    let _ = send_error_mail(error_message, app.clone());
+
+    show_error_scheduled_sends_and_continue(app);
+}
+
+pub fn error_saving_scheduled_sends(app: tauri::AppHandle) {
+    let error_message: String = "Nepodařilo se uložit naplánované E-maily".to_string();
+
+    let _ = send_error_mail(error_message, app.clone());
+
+    show_error_scheduled_sends_and_continue(app);
+}
+
+pub fn error_scheduling_send(app: tauri::AppHandle) {
+    let error_message: String = "Nepodařilo se naplánovat E-mail".to_string();
+
+    let _ = send_error_mail(error_message, app.clone());
+
+    show_error_scheduled_sends_and_continue(app);
+}
+
+pub fn error_sending_scheduled_mail(app: tauri::AppHandle, error: MailSenderError) {
+    let error_message: String =
+        format!("Došlo k chybě při odesílání naplánovaného mailu. \n\n {error}");
+
+    let _ = send_error_mail(error_message, app.clone());
+
+    show_error_sending_scheduled_mail_and_continue(app);
+}
+
+//nothing went wrong in the app, the user only has to decide what to do with them
+pub fn error_missed_scheduled_sends(app: tauri::AppHandle, count: usize) {
+    show_missed_scheduled_sends_and_continue(app, count);
+}
+
+//broken history is never overwritten - it is kept next to the backups in any case
+pub fn error_loading_send_history(app: tauri::AppHandle) {
+    let error_message: String = "Nepodařilo se načíst historii odeslaných E-mailů".to_string();
+
+    let _ = send_error_mail(error_message, app.clone());
+
+    let path = send_history_path(&app);
+    let _ = keep_broken_file(&path);
+
+    if let Some((backup, backup_history)) = newest_valid_backup::<SendHistory>(&path) {
+        hold_saving(&path);
+        show_restore_send_history_backup(app, backup, backup_history);
+        return;
+    }
+
+    show_error_loading_send_history_and_continue(app);
+}
+
+pub fn error_saving_send_history(app: tauri::AppHandle) {
+    let error_message: String = "Nepodařilo se uložit historii odeslaných E-mailů".to_string();
+
+    let _ = send_error_mail(error_message, app.clone());
+
+    show_error_send_history_and_continue(app);
+}
+
+pub fn error_loading_approval_queue(app: tauri::AppHandle) {
+    let error_message: String = "Nepodařilo se načíst E-maily ke schválení".to_string();
+
+    let _ = send_error_mail(error_message, app.clone());
+
+    show_error_approval_queue_and_continue(app);
+}
+
+pub fn error_saving_approval_queue(app: tauri::AppHandle) {
+    let error_message: String = "Nepodařilo se uložit E-maily ke schválení".to_string();
+
+    let _ = send_error_mail(error_message, app.clone());
+
+    show_error_approval_queue_and_continue(app);
+}
+
+pub fn error_sending_approved_mail(app: tauri::AppHandle, error: MailSenderError) {
+    let error_message: String =
+        format!("Došlo k chybě při odesílání schváleného mailu. \n\n {error}");
+
+    let _ = send_error_mail(error_message, app.clone());
+
+    show_error_sending_approved_mail_and_continue(app);
+}
+
+pub fn error_loading_send_presets(app: tauri::AppHandle) {
+    let error_message: String = "Nepodařilo se načíst předvolby odesílání".to_string();
+
+    let _ = send_error_mail(error_message, app.clone());
+
+    show_error_send_presets_and_continue(app);
+}
+
+pub fn error_saving_send_presets(app: tauri::AppHandle) {
+    let error_message: String = "Nepodařilo se uložit předvolby odesílání".to_string();
+
+    let _ = send_error_mail(error_message, app.clone());
+
+    show_error_send_presets_and_continue(app);
+}
+
+pub fn error_loading_sent_archive(app: tauri::AppHandle) {
+    let error_message: String = "Nepodařilo se načíst archiv odeslaných E-mailů".to_string();
+
+    let _ = send_error_mail(error_message, app.clone());
+
+    show_error_sent_archive_and_continue(app);
+}
+
+pub fn error_saving_sent_archive(app: tauri::AppHandle) {
+    let error_message: String = "Nepodařilo se uložit archiv odeslaných E-mailů".to_string();
+
+    let _ = send_error_mail(error_message, app.clone());
+
+    show_error_sent_archive_and_continue(app);
+}
+
+pub fn error_archiving_message(app: tauri::AppHandle) {
+    let error_message: String = "Odeslaný E-mail se nepodařilo uložit do archivu".to_string();
+
+    let _ = send_error_mail(error_message, app.clone());
+
+    show_error_archiving_message_and_continue(app);
+}
+
+pub fn error_exporting_archived_message(app: tauri::AppHandle) {
+    let error_message: String = "Nepodařilo se uložit export E-mailu z archivu".to_string();
+
+    let _ = send_error_mail(error_message, app.clone());
+
+    show_error_exporting_archived_message_and_continue(app);
+}
+
+pub fn error_exporting_history_proof(app: tauri::AppHandle) {
+    let error_message: String = "Nepodařilo se uložit doklad o odeslání z historie".to_string();
+
+    let _ = send_error_mail(error_message, app.clone());
+
+    show_error_exporting_history_proof_and_continue(app);
+}
+
+pub fn error_resending_archived_mail(app: tauri::AppHandle, error: MailSenderError) {
+    let error_message: String =
+        format!("Došlo k chybě při opětovném odesílání E-mailu z archivu. \n\n {error}");
+
+    let _ = send_error_mail(error_message, app.clone());
+
+    show_sending_user_error_and_continue(app);
+}
+
 pub fn error_failback_config(app: tauri::AppHandle) -> String {
     show_error_mail_error_and_continue(app);
     "".to_string()
@@ -260,6 +500,315 @@ fn show_error_loading_mail_list_and_continue(app: tauri::AppHandle) {
         .show(|result| if result {});
 }
 
+fn show_error_data_dir_and_continue(app: tauri::AppHandle) {
+    static ERROR_MESSAGE_TITLE: &str = "Došlo k chybě při přípravě složky s daty";
+    static ERROR_MESSAGE_TEXT: &str = "Nebylo možné připravit složku pro config a seznam osob.\n\nAutor aplikace byl informován.\n\nInformujte prosím vedoucího.";
+
+    app.dialog()
+        .message(ERROR_MESSAGE_TEXT.to_string())
+        .kind(MessageDialogKind::Info)
+        .title(ERROR_MESSAGE_TITLE.to_string())
+        .buttons(MessageDialogButtons::OkCustom("OK".to_string()))
+        .show(|result| if result {});
+}
+
+fn show_restore_config_backup(app: tauri::AppHandle, backup: PathBuf, backup_config: Config) {
+    static ERROR_MESSAGE_TITLE: &str = "Došlo k chybě při načítání configuračního souboru";
+    let error_message_text: String = format!(
+        "Config je poškozený.\n\nPoslední platná záloha: {}\n\nPřejete si ji obnovit?",
+        backup_name(&backup)
+    );
+
+    app.dialog()
+        .message(error_message_text)
+        .kind(MessageDialogKind::Warning)
+        .title(ERROR_MESSAGE_TITLE.to_string())
+        .buttons(MessageDialogButtons::OkCancelCustom(
+            "obnovit zálohu".to_string(),
+            "vytvořit prázdný".to_string(),
+        ))
+        .show(move |result| {
+            let saved_meanwhile = release_saving(&config_path(&app)).unwrap_or_else(|_| {
+                error_saving_config(app.clone());
+                true
+            });
+
+            //changes saved meanwhile are already written over the broken file
+            if !result {
+                if !saved_meanwhile {
+                    create_empty_config(app.clone());
+                }
+                return;
+            }
+
+            restore_backup(&backup, &config_path(&app))
+                .unwrap_or_else(|_| error_saving_config(app.clone()));
+
+            *app.state::<AppState>().config.lock() = backup_config;
+            refresh_integrity_issues(&app);
+        });
+}
+
+fn show_restore_mail_list_backup(
+    app: tauri::AppHandle,
+    backup: PathBuf,
+    backup_mail_list: MailList,
+) {
+    static ERROR_MESSAGE_TITLE: &str = "Došlo k chybě při načítání seznamu osob";
+    let error_message_text: String = format!(
+        "Seznam osob je poškozený.\n\nPoslední platná záloha: {}\n\nPřejete si ji obnovit?",
+        backup_name(&backup)
+    );
+
+    app.dialog()
+        .message(error_message_text)
+        .kind(MessageDialogKind::Warning)
+        .title(ERROR_MESSAGE_TITLE.to_string())
+        .buttons(MessageDialogButtons::OkCancelCustom(
+            "obnovit zálohu".to_string(),
+            "vytvořit prázdný".to_string(),
+        ))
+        .show(move |result| {
+            let saved_meanwhile = release_saving(&mail_list_path(&app)).unwrap_or_else(|_| {
+                error_saving_mail_list(app.clone());
+                true
+            });
+
+            //changes saved meanwhile are already written over the broken file
+            if !result {
+                if !saved_meanwhile {
+                    create_empty_mail_list(app.clone());
+                }
+                return;
+            }
+
+            restore_backup(&backup, &mail_list_path(&app))
+                .unwrap_or_else(|_| error_saving_mail_list(app.clone()));
+
+            *app.state::<AppState>().mail_list.lock() = backup_mail_list;
+            refresh_integrity_issues(&app);
+
+            //buttons were already rendered from empty list
+            reload_main_window(app);
+        });
+}
+
+fn show_error_loading_send_history_and_continue(app: tauri::AppHandle) {
+    static ERROR_MESSAGE_TITLE: &str = "Došlo k chybě při načítání historie odeslaných E-mailů";
+    static ERROR_MESSAGE_TEXT: &str = "Historie odeslaných je poškozená a nemá platnou zálohu.\n\nPoškozený soubor byl uschován mezi zálohy, začíná nová historie.\n\nAutor aplikace byl informován.";
+
+    app.dialog()
+        .message(ERROR_MESSAGE_TEXT.to_string())
+        .kind(MessageDialogKind::Warning)
+        .title(ERROR_MESSAGE_TITLE.to_string())
+        .buttons(MessageDialogButtons::OkCustom("OK".to_string()))
+        .show(|result| if result {});
+}
+
+fn show_restore_send_history_backup(
+    app: tauri::AppHandle,
+    backup: PathBuf,
+    backup_history: SendHistory,
+) {
+    static ERROR_MESSAGE_TITLE: &str = "Došlo k chybě při načítání historie odeslaných E-mailů";
+    let error_message_text: String = format!(
+        "Historie odeslaných je poškozená, poškozený soubor byl uschován mezi zálohy.\n\nPoslední platná záloha: {}\n\nPřejete si ji obnovit?",
+        backup_name(&backup)
+    );
+
+    app.dialog()
+        .message(error_message_text)
+        .kind(MessageDialogKind::Warning)
+        .title(ERROR_MESSAGE_TITLE.to_string())
+        .buttons(MessageDialogButtons::OkCancelCustom(
+            "obnovit zálohu".to_string(),
+            "začít novou".to_string(),
+        ))
+        .show(move |result| {
+            if release_saving(&send_history_path(&app)).is_err() {
+                error_saving_send_history(app.clone());
+            }
+
+            let app_state = app.state::<AppState>();
+            let mut send_history = app_state.send_history.lock();
+
+            if result {
+                let recorded = std::mem::replace(&mut *send_history, backup_history);
+                send_history.take_over(recorded);
+            }
+
+            send_history.save(app.clone());
+        });
+}
+
+fn show_error_migrating_file_and_continue(app: tauri::AppHandle) {
+    static ERROR_MESSAGE_TITLE: &str = "Došlo k chybě při převodu dat na novou verzi";
+    static ERROR_MESSAGE_TEXT: &str = "Nebylo možné převést config nebo seznam osob na novou verzi.\n\nAutor aplikace byl informován.\n\nInformujte prosím vedoucího.";
+
+    app.dialog()
+        .message(ERROR_MESSAGE_TEXT.to_string())
+        .kind(MessageDialogKind::Info)
+        .title(ERROR_MESSAGE_TITLE.to_string())
+        .buttons(MessageDialogButtons::OkCustom("OK".to_string()))
+        .show(|result| if result {});
+}
+
+fn show_error_importing_contacts_and_continue(app: tauri::AppHandle, reason: String) {
+    static ERROR_MESSAGE_TITLE: &str = "Import kontaktů se nezdařil";
+
+    app.dialog()
+        .message(reason)
+        .kind(MessageDialogKind::Info)
+        .title(ERROR_MESSAGE_TITLE.to_string())
+        .buttons(MessageDialogButtons::OkCustom("OK".to_string()))
+        .show(|result| if result {});
+}
+
+fn show_error_exporting_contacts_and_continue(app: tauri::AppHandle) {
+    static ERROR_MESSAGE_TITLE: &str = "Export kontaktů se nezdařil";
+    static ERROR_MESSAGE_TEXT: &str = "Soubor s kontakty nebylo možné uložit.\n\nZkuste prosím jiné umístění.\n\nAutor aplikace byl informován.";
+
+    app.dialog()
+        .message(ERROR_MESSAGE_TEXT.to_string())
+        .kind(MessageDialogKind::Info)
+        .title(ERROR_MESSAGE_TITLE.to_string())
+        .buttons(MessageDialogButtons::OkCustom("OK".to_string()))
+        .show(|result| if result {});
+}
+
+fn show_error_scheduled_sends_and_continue(app: tauri::AppHandle) {
+    static ERROR_MESSAGE_TITLE: &str = "Došlo k chybě u naplánovaných E-mailů";
+    static ERROR_MESSAGE_TEXT: &str = "Naplánované E-maily nebylo možné načíst nebo uložit.\n\nAutor aplikace byl informován.\n\nInformujte prosím vedoucího.";
+
+    app.dialog()
+        .message(ERROR_MESSAGE_TEXT.to_string())
+        .kind(MessageDialogKind::Info)
+        .title(ERROR_MESSAGE_TITLE.to_string())
+        .buttons(MessageDialogButtons::OkCustom("OK".to_string()))
+        .show(|result| if result {});
+}
+
+fn show_error_sending_scheduled_mail_and_continue(app: tauri::AppHandle) {
+    static ERROR_MESSAGE_TITLE: &str = "Naplánovaný E-mail se nepodařilo odeslat";
+    static ERROR_MESSAGE_TEXT: &str =
+        "E-mail zůstal v seznamu naplánovaných.\n\nOdešlete ho prosím znovu, nebo ho zrušte.";
+
+    app.dialog()
+        .message(ERROR_MESSAGE_TEXT.to_string())
+        .kind(MessageDialogKind::Info)
+        .title(ERROR_MESSAGE_TITLE.to_string())
+        .buttons(MessageDialogButtons::OkCustom("OK".to_string()))
+        .show(|result| if result {});
+}
+
+fn show_missed_scheduled_sends_and_continue(app: tauri::AppHandle, count: usize) {
+    static ERROR_MESSAGE_TITLE: &str = "Naplánované E-maily nebyly odeslány";
+
+    app.dialog()
+        .message(format!("{count} naplánovaných E-mailů se neodeslalo, protože aplikace nebyla spuštěná.\n\nV seznamu naplánovaných je můžete odeslat, přeplánovat, nebo zrušit."))
+        .kind(MessageDialogKind::Info)
+        .title(ERROR_MESSAGE_TITLE.to_string())
+        .buttons(MessageDialogButtons::OkCustom("OK".to_string()))
+        .show(|result| if result {});
+}
+
+fn show_error_send_history_and_continue(app: tauri::AppHandle) {
+    static ERROR_MESSAGE_TITLE: &str = "Došlo k chybě u historie odeslaných E-mailů";
+    static ERROR_MESSAGE_TEXT: &str = "Záznam o odeslání nebylo možné uložit.\n\nAutor aplikace byl informován.\n\nInformujte prosím vedoucího.";
+
+    app.dialog()
+        .message(ERROR_MESSAGE_TEXT.to_string())
+        .kind(MessageDialogKind::Info)
+        .title(ERROR_MESSAGE_TITLE.to_string())
+        .buttons(MessageDialogButtons::OkCustom("OK".to_string()))
+        .show(|result| if result {});
+}
+
+fn show_error_approval_queue_and_continue(app: tauri::AppHandle) {
+    static ERROR_MESSAGE_TITLE: &str = "Došlo k chybě u E-mailů ke schválení";
+    static ERROR_MESSAGE_TEXT: &str = "E-maily ke schválení nebylo možné načíst nebo uložit.\n\nAutor aplikace byl informován.\n\nInformujte prosím vedoucího.";
+
+    app.dialog()
+        .message(ERROR_MESSAGE_TEXT.to_string())
+        .kind(MessageDialogKind::Info)
+        .title(ERROR_MESSAGE_TITLE.to_string())
+        .buttons(MessageDialogButtons::OkCustom("OK".to_string()))
+        .show(|result| if result {});
+}
+
+fn show_error_sending_approved_mail_and_continue(app: tauri::AppHandle) {
+    static ERROR_MESSAGE_TITLE: &str = "Schválený E-mail se nepodařilo odeslat";
+    static ERROR_MESSAGE_TEXT: &str =
+        "E-mail zůstal mezi E-maily ke schválení.\n\nZkuste ho prosím schválit znovu, nebo ho zamítněte.";
+
+    app.dialog()
+        .message(ERROR_MESSAGE_TEXT.to_string())
+        .kind(MessageDialogKind::Info)
+        .title(ERROR_MESSAGE_TITLE.to_string())
+        .buttons(MessageDialogButtons::OkCustom("OK".to_string()))
+        .show(|result| if result {});
+}
+
+fn show_error_send_presets_and_continue(app: tauri::AppHandle) {
+    static ERROR_MESSAGE_TITLE: &str = "Došlo k chybě u předvoleb odesílání";
+    static ERROR_MESSAGE_TEXT: &str = "Předvolby nebylo možné načíst nebo uložit.\n\nAutor aplikace byl informován.\n\nInformujte prosím vedoucího.";
+
+    app.dialog()
+        .message(ERROR_MESSAGE_TEXT.to_string())
+        .kind(MessageDialogKind::Info)
+        .title(ERROR_MESSAGE_TITLE.to_string())
+        .buttons(MessageDialogButtons::OkCustom("OK".to_string()))
+        .show(|result| if result {});
+}
+
+fn show_error_sent_archive_and_continue(app: tauri::AppHandle) {
+    static ERROR_MESSAGE_TITLE: &str = "Došlo k chybě u archivu odeslaných E-mailů";
+    static ERROR_MESSAGE_TEXT: &str = "Seznam archivovaných E-mailů nebylo možné načíst nebo uložit.\n\nAutor aplikace byl informován.\n\nInformujte prosím vedoucího.";
+
+    app.dialog()
+        .message(ERROR_MESSAGE_TEXT.to_string())
+        .kind(MessageDialogKind::Info)
+        .title(ERROR_MESSAGE_TITLE.to_string())
+        .buttons(MessageDialogButtons::OkCustom("OK".to_string()))
+        .show(|result| if result {});
+}
+
+fn show_error_archiving_message_and_continue(app: tauri::AppHandle) {
+    static ERROR_MESSAGE_TITLE: &str = "E-mail byl odeslán, ale není v archivu";
+    static ERROR_MESSAGE_TEXT: &str = "Odeslaný E-mail se nepodařilo uložit do archivu.\n\nAutor aplikace byl informován.\n\nInformujte prosím vedoucího.";
+
+    app.dialog()
+        .message(ERROR_MESSAGE_TEXT.to_string())
+        .kind(MessageDialogKind::Info)
+        .title(ERROR_MESSAGE_TITLE.to_string())
+        .buttons(MessageDialogButtons::OkCustom("OK".to_string()))
+        .show(|result| if result {});
+}
+
+fn show_error_exporting_archived_message_and_continue(app: tauri::AppHandle) {
+    static ERROR_MESSAGE_TITLE: &str = "Export E-mailu se nezdařil";
+    static ERROR_MESSAGE_TEXT: &str = "E-mail z archivu nebylo možné uložit.\n\nZkuste prosím jiné umístění.\n\nAutor aplikace byl informován.";
+
+    app.dialog()
+        .message(ERROR_MESSAGE_TEXT.to_string())
+        .kind(MessageDialogKind::Info)
+        .title(ERROR_MESSAGE_TITLE.to_string())
+        .buttons(MessageDialogButtons::OkCustom("OK".to_string()))
+        .show(|result| if result {});
+}
+
+fn show_error_exporting_history_proof_and_continue(app: tauri::AppHandle) {
+    static ERROR_MESSAGE_TITLE: &str = "Export dokladu se nezdařil";
+    static ERROR_MESSAGE_TEXT: &str = "Doklad o odeslání nebylo možné uložit.\n\nZkuste prosím jiné umístění.\n\nAutor aplikace byl informován.";
+
+    app.dialog()
+        .message(ERROR_MESSAGE_TEXT.to_string())
+        .kind(MessageDialogKind::Info)
+        .title(ERROR_MESSAGE_TITLE.to_string())
+        .buttons(MessageDialogButtons::OkCustom("OK".to_string()))
+        .show(|result| if result {});
+}
+
 fn show_file_pick_user_error_and_continue(app: tauri::AppHandle) {
     static ERROR_MESSAGE_TITLE: &str = "Došlo k chybě při výběru souboru";
     static ERROR_MESSAGE_TEXT: &str = "Nebylo možné vybrat soubor.\n\nAutor aplikace byl informován.\n\nInformujte prosím vedoucího.";
@@ -343,6 +892,12 @@ fn end_app(app: tauri::AppHandle) {
     app.exit(0);
 }
 
+fn reload_main_window(app: tauri::AppHandle) {
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.eval("window.location.reload()");
+    }
+}
+
 fn send_error_mail(text: String, app: tauri::AppHandle) -> Result<(), MailSenderError> {
     //must be error proof, so config will be hard wired
     let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("hard_coded_config.env");
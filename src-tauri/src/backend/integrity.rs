@@ -0,0 +1,186 @@
+use lettre::Address;
+use tauri::Manager;
+
+use crate::backend::config::Config;
+use crate::backend::mail_list_utils::{ListMember, MailList};
+use crate::AppState;
+
+//---------------------------
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum IntegrityIssue {
+    InvalidMail { id: usize, name: String },
+    DuplicateMail { id: usize, name: String },
+    DuplicateName { id: usize, name: String },
+    DuplicatePin { id: usize, name: String },
+    InvalidListMember { list: String, mail: String },
+    InvalidConfig(String),
+}
+
+impl IntegrityIssue {
+    pub fn person_id(&self) -> Option<usize> {
+        match self {
+            IntegrityIssue::InvalidMail { id, .. }
+            | IntegrityIssue::DuplicateMail { id, .. }
+            | IntegrityIssue::DuplicateName { id, .. }
+            | IntegrityIssue::DuplicatePin { id, .. } => Some(*id),
+            IntegrityIssue::InvalidListMember { .. } | IntegrityIssue::InvalidConfig(_) => None,
+        }
+    }
+
+    //sending to invalid mail would end the app, duplicates are only highlighted
+    pub fn blocks_sending(&self) -> bool {
+        matches!(self, IntegrityIssue::InvalidMail { .. })
+    }
+
+    pub fn description(&self) -> String {
+        match self {
+            IntegrityIssue::InvalidMail { name, .. } => format!("{name} - neplatný E-mail"),
+            IntegrityIssue::DuplicateMail { name, .. } => {
+                format!("{name} - E-mail je uveden u více osob")
+            }
+            IntegrityIssue::DuplicateName { name, .. } => {
+                format!("{name} - jméno je uvedeno vícekrát")
+            }
+            IntegrityIssue::DuplicatePin { name, .. } => {
+                format!("{name} - PIN má i jiná osoba")
+            }
+            IntegrityIssue::InvalidListMember { list, mail } => {
+                format!("seznam {list} - neplatný E-mail {mail}, při výběru seznamu se vynechá")
+            }
+            IntegrityIssue::InvalidConfig(description) => format!("config - {description}"),
+        }
+    }
+}
+
+pub fn check_mail_list(mail_list: &MailList) -> Vec<IntegrityIssue> {
+    let contacts: Vec<_> = mail_list.contacts().collect();
+
+    let mut issues: Vec<IntegrityIssue> = vec![];
+
+    for contact in &contacts {
+        //same rule as save_list
+        if !contact.has_valid_mail() {
+            issues.push(IntegrityIssue::InvalidMail {
+                id: contact.id,
+                name: contact.name.clone(),
+            });
+        }
+
+        let duplicate_mail = contacts.iter().any(|other| {
+            other.id != contact.id
+                && other.addresses.iter().any(|address| {
+                    !address.mail.trim().is_empty() && contact.has_mail(&address.mail)
+                })
+        });
+        if duplicate_mail {
+            issues.push(IntegrityIssue::DuplicateMail {
+                id: contact.id,
+                name: contact.name.clone(),
+            });
+        }
+
+        let duplicate_name = contacts
+            .iter()
+            .any(|other| other.id != contact.id && other.name.trim() == contact.name.trim());
+        if duplicate_name {
+            issues.push(IntegrityIssue::DuplicateName {
+                id: contact.id,
+                name: contact.name.clone(),
+            });
+        }
+
+        //PIN picks the first match, the other person couldn't identify
+        let duplicate_pin = !contact.pin.is_empty()
+            && contacts
+                .iter()
+                .any(|other| other.id != contact.id && other.pin == contact.pin);
+        if duplicate_pin {
+            issues.push(IntegrityIssue::DuplicatePin {
+                id: contact.id,
+                name: contact.name.clone(),
+            });
+        }
+    }
+
+    //invalid contacts in lists are already reported above
+    for list in mail_list.distribution_lists() {
+        for member in &list.members {
+            if let ListMember::Address(mail) = member {
+                if mail.parse::<Address>().is_err() {
+                    issues.push(IntegrityIssue::InvalidListMember {
+                        list: list.name.clone(),
+                        mail: mail.clone(),
+                    });
+                }
+            }
+        }
+    }
+
+    issues
+}
+
+pub fn check_config(config: &Config) -> Vec<IntegrityIssue> {
+    let mut issues: Vec<IntegrityIssue> = vec![];
+
+    if config.sender_mail().parse::<Address>().is_err() {
+        issues.push(IntegrityIssue::InvalidConfig(
+            "neplatný E-mail odesilatele".to_string(),
+        ));
+    }
+    if config.sender_password().is_empty() {
+        issues.push(IntegrityIssue::InvalidConfig(
+            "chybí heslo odesilatele".to_string(),
+        ));
+    }
+    if config.smtp_transport().trim().is_empty() {
+        issues.push(IntegrityIssue::InvalidConfig(
+            "chybí smtp transport".to_string(),
+        ));
+    }
+    if config.title().trim().is_empty() {
+        issues.push(IntegrityIssue::InvalidConfig(
+            "chybí předmět E-mailu".to_string(),
+        ));
+    }
+    if config.feedback_mail().parse::<Address>().is_err() {
+        issues.push(IntegrityIssue::InvalidConfig(
+            "neplatný feedback E-mail".to_string(),
+        ));
+    }
+    if config.settings_password().is_empty() {
+        issues.push(IntegrityIssue::InvalidConfig(
+            "chybí heslo nastavení".to_string(),
+        ));
+    }
+    for profile in config.profiles() {
+        if profile.sender_mail.parse::<Address>().is_err() {
+            issues.push(IntegrityIssue::InvalidConfig(format!(
+                "profil {} - neplatný E-mail odesilatele",
+                profile.name
+            )));
+        }
+        if profile.smtp_transport.trim().is_empty() {
+            issues.push(IntegrityIssue::InvalidConfig(format!(
+                "profil {} - chybí smtp transport",
+                profile.name
+            )));
+        }
+    }
+
+    issues
+}
+
+pub fn check_all(mail_list: &MailList, config: &Config) -> Vec<IntegrityIssue> {
+    let mut issues = check_mail_list(mail_list);
+    issues.extend(check_config(config));
+    issues
+}
+
+pub fn refresh_integrity_issues(app: &tauri::AppHandle) {
+    let app_state = app.state::<AppState>();
+
+    let issues = check_all(&app_state.mail_list.lock(), &app_state.config.lock());
+
+    *app_state.integrity_issues.lock() = issues;
+}
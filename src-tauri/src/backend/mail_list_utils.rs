@@ -3,6 +3,8 @@ use serde::{Deserialize, Serialize};
 use tauri::AppHandle;
 
 use crate::backend::error_handling::*;
+use crate::backend::schema::{Migration, SchemaError, Versioned};
+use crate::backend::storage::{mail_list_path, migrate_file, write_atomically, write_with_backup};
 
 //---------------------------
 
@@ -12,34 +14,199 @@ pub struct Person {
     pub mail: String,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ContactAddress {
+    pub label: String,
+    pub mail: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Contact {
+    pub id: usize,
+    pub name: String,
+    pub addresses: Vec<ContactAddress>,
+    pub primary: usize, //index into addresses
+    pub phone: String,
+    pub role: String,
+    pub notes: String,
+    pub active: bool, //inactive contacts are hidden from the main screen
+    #[serde(default)]
+    pub pin: String, //quick identification of the mechanic before sending, empty - no PIN
+}
+
+impl Contact {
+    fn new(id: usize) -> Contact {
+        Contact {
+            id,
+            name: "".to_string(),
+            addresses: vec![],
+            primary: 0,
+            phone: "".to_string(),
+            role: "".to_string(),
+            notes: "".to_string(),
+            active: true,
+            pin: "".to_string(),
+        }
+    }
+
+    pub fn mail(&self) -> &str {
+        self.addresses
+            .get(self.primary)
+            .map(|address| address.mail.as_str())
+            .unwrap_or_default()
+    }
+
+    pub fn has_valid_mail(&self) -> bool {
+        !self.addresses.is_empty()
+            && self
+                .addresses
+                .iter()
+                .all(|address| address.mail.parse::<Address>().is_ok())
+    }
+
+    pub fn has_mail(&self, mail: &str) -> bool {
+        self.addresses
+            .iter()
+            .any(|address| address.mail.trim().eq_ignore_ascii_case(mail.trim()))
+    }
+
+    //primary address
+    pub fn person(&self) -> Person {
+        Person {
+            name: self.name.clone(),
+            mail: self.mail().to_string(),
+        }
+    }
+
+    //every address, primary first
+    pub fn people(&self) -> Vec<Person> {
+        let mut people = vec![self.person()];
+
+        people.extend(
+            self.addresses
+                .iter()
+                .enumerate()
+                .filter(|(index, _)| *index != self.primary)
+                .map(|(_, address)| Person {
+                    name: self.name.clone(),
+                    mail: address.mail.clone(),
+                }),
+        );
+
+        people
+    }
+
+    pub fn add_address(&mut self) {
+        let label = if self.addresses.is_empty() {
+            "pracovní"
+        } else {
+            "soukromý"
+        };
+
+        self.addresses.push(ContactAddress {
+            label: label.to_string(),
+            mail: "".to_string(),
+        });
+    }
+
+    pub fn remove_address(&mut self, index: usize) {
+        if index >= self.addresses.len() {
+            return;
+        }
+
+        self.addresses.remove(index);
+
+        if self.primary > index || self.primary >= self.addresses.len() {
+            self.primary = self.primary.saturating_sub(1);
+        }
+    }
+
+    pub fn set_primary(&mut self, index: usize) {
+        if index < self.addresses.len() {
+            self.primary = index;
+        }
+    }
+
+    //empty rows left in the editor are dropped on save
+    fn drop_empty_addresses(&mut self) {
+        while let Some(index) = self
+            .addresses
+            .iter()
+            .position(|address| address.mail.trim().is_empty())
+        {
+            self.remove_address(index);
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Category {
+    pub id: usize,
+    pub name: String,
+    pub contacts: Vec<Contact>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum ListMember {
+    Contact(usize),
+    Address(String),
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct DistributionList {
+    pub id: usize,
+    pub name: String,
+    pub members: Vec<ListMember>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(default)]
 pub struct MailList {
-    list: Vec<Option<Person>>, //0-23 - mechanic | 24-28 - technique
+    version: u32,
+    next_id: usize, //shared by categories, contacts and distribution lists
+    categories: Vec<Category>,
+    distribution_lists: Vec<DistributionList>,
 }
 
 impl MailList {
     pub fn save_list(&mut self, app: AppHandle) -> Result<(), Vec<String>> {
-        //will change every empty names into None
-        self.list
-            .iter_mut()
-            .filter(|person| person.as_ref().is_some_and(|person| person.name.is_empty()))
-            .for_each(|person| *person = None);
+        //will remove every contact with empty name
+        self.categories.iter_mut().for_each(|category| {
+            category
+                .contacts
+                .retain(|contact| !contact.name.trim().is_empty());
+            category
+                .contacts
+                .iter_mut()
+                .for_each(|contact| contact.drop_empty_addresses());
+        });
 
-        //will create list of invalid mails
-        let wrong_mail_list: Vec<String> = self
-            .list
-            .iter()
-            .filter_map(|person| {
-                person.as_ref().and_then(|p| {
-                    if p.mail.parse::<Address>().is_err() {
-                        Some(p.name.clone())
-                    } else {
-                        None
-                    }
-                })
+        //removed contacts are removed from distribution lists too
+        let contact_ids: Vec<usize> = self.contacts().map(|contact| contact.id).collect();
+        self.distribution_lists.iter_mut().for_each(|list| {
+            list.members.retain(|member| match member {
+                ListMember::Contact(id) => contact_ids.contains(id),
+                ListMember::Address(mail) => !mail.trim().is_empty(),
             })
+        });
+
+        //will create list of invalid mails
+        let mut wrong_mail_list: Vec<String> = self
+            .contacts()
+            .filter(|contact| !contact.has_valid_mail())
+            .map(|contact| contact.name.clone())
             .collect();
 
+        for list in &self.distribution_lists {
+            for member in &list.members {
+                if let ListMember::Address(mail) = member {
+                    if mail.parse::<Address>().is_err() {
+                        wrong_mail_list.push(format!("{} - {mail}", list.name));
+                    }
+                }
+            }
+        }
+
         if !wrong_mail_list.is_empty() {
             return Err(wrong_mail_list);
         }
@@ -47,97 +214,433 @@ impl MailList {
         let ron_string = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
             .unwrap_or_else(|_| error_parsing_mail_list_to_string(app.clone()));
 
-        std::fs::write("mail_list.ron", ron_string).unwrap_or_else(|_| error_saving_mail_list(app));
+        write_with_backup(&mail_list_path(&app), &ron_string)
+            .unwrap_or_else(|_| error_saving_mail_list(app));
 
         Ok(())
     }
 
     pub fn load_list(app: AppHandle) -> MailList {
-        let ron_string = std::fs::read_to_string("mail_list.ron")
+        let ron_string = std::fs::read_to_string(mail_list_path(&app))
             .unwrap_or_else(|_| error_loading_mail_list(app.clone()));
+        let ron_string = migrate_file::<MailList>(app.clone(), &mail_list_path(&app), ron_string);
+
+        ron::de::from_str(&ron_string)
+            .unwrap_or_else(|_| error_decoding_mail_list_from_string(app.clone(), &ron_string))
+    }
 
-        let mut new_mail_list = ron::de::from_str(&ron_string)
-            .unwrap_or_else(|_| error_decoding_mail_list_from_string(app.clone(), &ron_string));
+    pub fn categories(&self) -> &[Category] {
+        &self.categories
+    }
 
-        if new_mail_list.list.len() < 29 {
-            new_mail_list = error_mail_list_id_overflow(app);
-        }
+    //empty names are dropped by save_list, so they are skipped here too
+    pub fn contacts(&self) -> impl Iterator<Item = &Contact> {
+        self.categories
+            .iter()
+            .flat_map(|category| category.contacts.iter())
+            .filter(|contact| !contact.name.trim().is_empty())
+    }
 
-        new_mail_list
+    pub fn load_contact(&self, id: usize) -> Option<Contact> {
+        self.categories
+            .iter()
+            .flat_map(|category| category.contacts.iter())
+            .find(|contact| contact.id == id)
+            .cloned()
     }
 
-    pub fn load_person(&self, id: usize) -> Option<Person> {
-        self.list[id].clone()
+    pub fn load_category(&self, id: usize) -> Option<&Category> {
+        self.categories.iter().find(|category| category.id == id)
     }
 
-    pub fn save_person_name(&mut self, id: usize, name: String) {
-        let mut person = match self.load_person(id) {
-            Some(person) => person,
-            None => Person {
-                name: "".to_string(),
-                mail: "".to_string(),
-            },
+    pub fn add_category(&mut self, name: String) -> usize {
+        let id = self.new_id();
+
+        self.categories.push(Category {
+            id,
+            name,
+            contacts: vec![],
+        });
+
+        id
+    }
+
+    pub fn save_category_name(&mut self, id: usize, name: String) {
+        if let Some(category) = self.categories.iter_mut().find(|c| c.id == id) {
+            category.name = name;
+        }
+    }
+
+    //negative offset moves category up
+    pub fn move_category(&mut self, id: usize, offset: isize) {
+        let Some(index) = self.categories.iter().position(|c| c.id == id) else {
+            return;
         };
 
-        person.name = name;
+        let new_index = index
+            .saturating_add_signed(offset)
+            .min(self.categories.len() - 1);
+
+        let category = self.categories.remove(index);
+        self.categories.insert(new_index, category);
+    }
+
+    pub fn remove_category(&mut self, id: usize) {
+        self.categories.retain(|category| category.id != id);
+    }
+
+    pub fn add_contact(&mut self, category_id: usize) -> Option<usize> {
+        let id = self.new_id();
+
+        let category = self.categories.iter_mut().find(|c| c.id == category_id)?;
+        category.contacts.push(Contact::new(id));
+
+        Some(id)
+    }
 
-        self.list[id] = Some(person);
+    pub fn save_person_name(&mut self, id: usize, name: String) {
+        if let Some(contact) = self.contact_mut(id) {
+            contact.name = name;
+        }
     }
 
+    //changes primary address, the first address is created when missing
     pub fn save_person_mail(&mut self, id: usize, mail: String) {
-        let mut person = match self.load_person(id) {
-            Some(person) => person,
-            None => Person {
-                name: "".to_string(),
-                mail: "".to_string(),
-            },
+        if let Some(contact) = self.contact_mut(id) {
+            if contact.addresses.is_empty() {
+                contact.add_address();
+            }
+
+            let primary = contact.primary;
+            contact.addresses[primary].mail = mail;
+        }
+    }
+
+    //only active contacts with a valid mail can send, same as in the sender picker
+    //empty PIN never matches
+    pub fn contact_by_pin(&self, pin: &str) -> Option<&Contact> {
+        let pin = pin.trim();
+
+        if pin.is_empty() {
+            return None;
+        }
+
+        self.contacts()
+            .find(|contact| contact.active && contact.has_valid_mail() && contact.pin == pin)
+    }
+
+    pub fn update_contact(&mut self, id: usize, update: impl FnOnce(&mut Contact)) {
+        if let Some(contact) = self.contact_mut(id) {
+            update(contact);
+        }
+    }
+
+    pub fn distribution_lists(&self) -> &[DistributionList] {
+        &self.distribution_lists
+    }
+
+    pub fn load_distribution_list(&self, id: usize) -> Option<&DistributionList> {
+        self.distribution_lists.iter().find(|list| list.id == id)
+    }
+
+    pub fn add_distribution_list(&mut self, name: String) -> usize {
+        let id = self.new_id();
+
+        self.distribution_lists.push(DistributionList {
+            id,
+            name,
+            members: vec![],
+        });
+
+        id
+    }
+
+    pub fn remove_distribution_list(&mut self, id: usize) {
+        self.distribution_lists.retain(|list| list.id != id);
+    }
+
+    pub fn save_distribution_list_name(&mut self, id: usize, name: String) {
+        if let Some(list) = self.distribution_list_mut(id) {
+            list.name = name;
+        }
+    }
+
+    pub fn toggle_distribution_list_contact(&mut self, id: usize, contact_id: usize) {
+        if let Some(list) = self.distribution_list_mut(id) {
+            let member = ListMember::Contact(contact_id);
+
+            if list.members.contains(&member) {
+                list.members.retain(|m| *m != member);
+            } else {
+                list.members.push(member);
+            }
+        }
+    }
+
+    //free addresses are replaced as a whole, contacts stay
+    pub fn save_distribution_list_addresses(&mut self, id: usize, addresses: Vec<String>) {
+        if let Some(list) = self.distribution_list_mut(id) {
+            list.members
+                .retain(|member| matches!(member, ListMember::Contact(_)));
+            list.members
+                .extend(addresses.into_iter().map(ListMember::Address));
+        }
+    }
+
+    //members resolved into recipients, missing and inactive contacts are skipped,
+    //invalid addresses too - they are shown by the integrity check, sending to them would end the app
+    pub fn distribution_list_people(&self, id: usize) -> Vec<Person> {
+        let Some(list) = self.load_distribution_list(id) else {
+            return vec![];
         };
 
-        person.mail = mail;
+        list.members
+            .iter()
+            .filter_map(|member| match member {
+                ListMember::Contact(contact_id) => self
+                    .load_contact(*contact_id)
+                    .filter(|contact| contact.active && contact.has_valid_mail())
+                    .map(|contact| contact.person()),
+                ListMember::Address(mail) => mail.parse::<Address>().is_ok().then(|| Person {
+                    name: mail.clone(),
+                    mail: mail.clone(),
+                }),
+            })
+            .collect()
+    }
+
+    fn distribution_list_mut(&mut self, id: usize) -> Option<&mut DistributionList> {
+        self.distribution_lists
+            .iter_mut()
+            .find(|list| list.id == id)
+    }
+
+    fn contact_mut(&mut self, id: usize) -> Option<&mut Contact> {
+        self.categories
+            .iter_mut()
+            .flat_map(|category| category.contacts.iter_mut())
+            .find(|contact| contact.id == id)
+    }
+
+    fn new_id(&mut self) -> usize {
+        let id = self.next_id;
+        self.next_id += 1;
+        id
+    }
+}
+
+impl Versioned for MailList {
+    const CURRENT_VERSION: u32 = 4;
+
+    fn migrations() -> &'static [Migration] {
+        &[
+            mail_list_v0_to_v1,
+            mail_list_v1_to_v2,
+            mail_list_v2_to_v3,
+            mail_list_v3_to_v4,
+        ]
+    }
+}
+
+//layout of versions 0 and 1 - fixed slots, 0-23 - mechanic | 24-28 - technique
+#[derive(Serialize, Deserialize)]
+struct SlotMailList {
+    #[serde(default)]
+    version: u32,
+    list: Vec<Option<Person>>,
+}
+
+//version 0 had no version field, the layout is otherwise the same
+fn mail_list_v0_to_v1(ron_string: &str) -> Result<String, SchemaError> {
+    let mut mail_list: SlotMailList = ron::de::from_str(ron_string)?;
+    mail_list.version = 1;
+
+    Ok(ron::ser::to_string_pretty(
+        &mail_list,
+        ron::ser::PrettyConfig::default(),
+    )?)
+}
+
+//layout of versions 2 and 3 - contacts with single mail, version 2 had no distribution lists
+#[derive(Serialize, Deserialize, Default)]
+struct SingleMailList {
+    version: u32,
+    next_id: usize,
+    categories: Vec<SingleMailCategory>,
+    #[serde(default)]
+    distribution_lists: Vec<LegacyDistributionList>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SingleMailCategory {
+    id: usize,
+    name: String,
+    contacts: Vec<SingleMailContact>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SingleMailContact {
+    id: usize,
+    name: String,
+    mail: String,
+}
+
+//distribution lists of versions 3 and 4
+#[derive(Serialize, Deserialize)]
+struct LegacyDistributionList {
+    id: usize,
+    name: String,
+    members: Vec<LegacyListMember>,
+}
+
+#[derive(Serialize, Deserialize)]
+enum LegacyListMember {
+    Contact(usize),
+    Address(String),
+}
+
+//layout of version 4 - contacts with labelled addresses, phone, role, notes and active flag
+#[derive(Serialize, Deserialize)]
+struct LabelledMailList {
+    version: u32,
+    next_id: usize,
+    categories: Vec<LabelledMailCategory>,
+    distribution_lists: Vec<LegacyDistributionList>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct LabelledMailCategory {
+    id: usize,
+    name: String,
+    contacts: Vec<LabelledMailContact>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct LabelledMailContact {
+    id: usize,
+    name: String,
+    addresses: Vec<LabelledMailAddress>,
+    primary: usize,
+    phone: String,
+    role: String,
+    notes: String,
+    active: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+struct LabelledMailAddress {
+    label: String,
+    mail: String,
+}
+
+//fixed slots are turned into two categories, ids follow the old slot order
+fn mail_list_v1_to_v2(ron_string: &str) -> Result<String, SchemaError> {
+    let slot_mail_list: SlotMailList = ron::de::from_str(ron_string)?;
+
+    let mut mail_list = SingleMailList {
+        version: 2,
+        ..Default::default()
+    };
+
+    for (name, slots) in [("mechanici", 0..24), ("technici", 24..29)] {
+        let category_id = mail_list.next_id;
+        mail_list.next_id += 1;
 
-        self.list[id] = Some(person);
+        let mut contacts: Vec<SingleMailContact> = vec![];
+
+        for person in slot_mail_list
+            .list
+            .iter()
+            .skip(slots.start)
+            .take(slots.len())
+            .flatten()
+            .filter(|person| !person.name.is_empty())
+        {
+            contacts.push(SingleMailContact {
+                id: mail_list.next_id,
+                name: person.name.clone(),
+                mail: person.mail.clone(),
+            });
+            mail_list.next_id += 1;
+        }
+
+        mail_list.categories.push(SingleMailCategory {
+            id: category_id,
+            name: name.to_string(),
+            contacts,
+        });
     }
+
+    Ok(ron::ser::to_string_pretty(
+        &mail_list,
+        ron::ser::PrettyConfig::default(),
+    )?)
+}
+
+//version 3 added distribution lists, they are filled with serde defaults
+//the version was raised before the rule in schema.rs, files of version 3 exist, so the step stays
+fn mail_list_v2_to_v3(ron_string: &str) -> Result<String, SchemaError> {
+    let mut mail_list: SingleMailList = ron::de::from_str(ron_string)?;
+    mail_list.version = 3;
+
+    Ok(ron::ser::to_string_pretty(
+        &mail_list,
+        ron::ser::PrettyConfig::default(),
+    )?)
+}
+
+//single mail becomes primary work address, every contact stays active
+fn mail_list_v3_to_v4(ron_string: &str) -> Result<String, SchemaError> {
+    let single_mail_list: SingleMailList = ron::de::from_str(ron_string)?;
+
+    let mail_list = LabelledMailList {
+        version: 4,
+        next_id: single_mail_list.next_id,
+        categories: single_mail_list
+            .categories
+            .into_iter()
+            .map(|category| LabelledMailCategory {
+                id: category.id,
+                name: category.name,
+                contacts: category
+                    .contacts
+                    .into_iter()
+                    .map(|contact| LabelledMailContact {
+                        id: contact.id,
+                        name: contact.name,
+                        addresses: vec![LabelledMailAddress {
+                            label: "pracovní".to_string(),
+                            mail: contact.mail,
+                        }],
+                        primary: 0,
+                        phone: String::new(),
+                        role: String::new(),
+                        notes: String::new(),
+                        active: true,
+                    })
+                    .collect(),
+            })
+            .collect(),
+        distribution_lists: single_mail_list.distribution_lists,
+    };
+
+    Ok(ron::ser::to_string_pretty(
+        &mail_list,
+        ron::ser::PrettyConfig::default(),
+    )?)
 }
 
 pub fn create_empty_mail_list(app: AppHandle) -> String {
     static EMPTY_MAIL_LIST: &str = "
 (
-    list: [
-        None,
-        None,
-        None,
-        None,
-        None,
-        None,
-        None,
-        None,
-        None,
-        None,
-        None,
-        None,
-        None,
-        None,
-        None,
-        None,
-        None,
-        None,
-        None,
-        None,
-        None,
-        None,
-        None,
-        None,
-        None,
-        None,
-        None,
-        None,
-        None,
-        None,
-    ],
+    version: 4,
+    next_id: 0,
+    categories: [],
+    distribution_lists: [],
 )
 ";
 
-    std::fs::write("mail_list.ron", EMPTY_MAIL_LIST)
+    write_atomically(&mail_list_path(&app), EMPTY_MAIL_LIST)
         .unwrap_or_else(|_| error_of_fail_back_system(app));
 
     EMPTY_MAIL_LIST.to_string()
@@ -145,6 +648,70 @@ pub fn create_empty_mail_list(app: AppHandle) -> String {
 
 pub fn empty_mail_list() -> MailList {
     MailList {
-        list: vec![None; 30],
+        version: MailList::CURRENT_VERSION,
+        next_id: 0,
+        categories: vec![],
+        distribution_lists: vec![],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::schema::decode;
+
+    #[test]
+    fn slot_list_is_migrated_to_categories() {
+        let mail_list: MailList = decode(
+            r#"(
+    list: [
+        Some((name: "Jan", mail: "jan@firma.cz")),
+        None,
+    ],
+)"#,
+        )
+        .expect("version 0 is migrated");
+
+        let names: Vec<&str> = mail_list
+            .categories()
+            .iter()
+            .map(|category| category.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["mechanici", "technici"]);
+
+        let contacts: Vec<&Contact> = mail_list.contacts().collect();
+        assert_eq!(contacts.len(), 1);
+        assert_eq!(contacts[0].name, "Jan");
+        assert_eq!(contacts[0].mail(), "jan@firma.cz");
+        assert!(contacts[0].active);
+    }
+
+    #[test]
+    fn single_mail_list_keeps_distribution_lists() {
+        let mail_list: MailList = decode(
+            r#"(
+    version: 3,
+    next_id: 3,
+    categories: [
+        (id: 0, name: "mechanici", contacts: [(id: 1, name: "Jan", mail: "jan@firma.cz")]),
+    ],
+    distribution_lists: [
+        (id: 2, name: "dílna", members: [Contact(1), Address("sklad@firma.cz")]),
+    ],
+)"#,
+        )
+        .expect("version 3 is migrated");
+
+        let contact = mail_list.contacts().next().expect("contact is kept");
+        assert_eq!(contact.addresses[0].label, "pracovní");
+        assert_eq!(contact.mail(), "jan@firma.cz");
+
+        assert_eq!(
+            mail_list.distribution_lists()[0].members,
+            vec![
+                ListMember::Contact(1),
+                ListMember::Address("sklad@firma.cz".to_string()),
+            ]
+        );
     }
 }
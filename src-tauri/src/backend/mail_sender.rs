@@ -1,24 +1,102 @@
 use anyhow::Result;
 
+use lettre::address::Envelope;
 use lettre::message::Mailbox;
-use lettre::message::{header::ContentType, Attachment, Body, MultiPart};
+use lettre::message::{header::ContentType, Attachment, Body, MultiPart, SinglePart};
 use lettre::{Address, Message, SmtpTransport, Transport};
 
 use std::ffi::OsStr;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
 
 use tauri_plugin_dialog::FilePath;
 
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
-use crate::backend::config::Config;
+use crate::backend::config::{fill_placeholders, Config, RecipientDomain};
 use crate::backend::error_handling::error_parsing_mail_address;
 use crate::backend::mail_list_utils;
 use crate::backend::mail_list_utils::Person;
+use crate::backend::other_mail_utils::OtherMailList;
+use crate::backend::sent_archive::{record_archive, ArchivedMessage};
 
 //---------------------------
 
+//message held for the undo delay, selection is kept to be restored on undo
+pub struct PendingSend {
+    pub mail: MailSender,
+    pub other_mail_list: OtherMailList,
+    pub send_at: Instant,
+    pub config: Config, //profile and subject as they were when send was clicked
+    pub sender_id: Option<usize>, //mechanic picked again when the send is undone
+}
+
+//subject and body of a preset, kept with the selection until it is sent or cleared
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct MessageTemplate {
+    pub subject: String,
+    pub body: String,
+}
+
+//shown in the confirmation before sending
+pub struct AttachmentInfo {
+    pub name: String,
+    pub size: u64,
+    pub pages: Option<usize>, //only for PDF, None when it couldn't be counted
+}
+
+impl AttachmentInfo {
+    pub fn load(path: &Path) -> AttachmentInfo {
+        let name = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or("soubor")
+            .to_string();
+
+        let bytes = fs::read(path).unwrap_or_default();
+
+        let is_pdf = path
+            .extension()
+            .is_some_and(|extension| extension.eq_ignore_ascii_case("pdf"));
+
+        AttachmentInfo {
+            name,
+            size: bytes.len() as u64,
+            pages: if is_pdf { pdf_page_count(&bytes) } else { None },
+        }
+    }
+}
+
+//without PDF parser - the page tree root has the highest /Count,
+//files with compressed object streams fall back to counting /Type /Page objects
+fn pdf_page_count(bytes: &[u8]) -> Option<usize> {
+    let text = String::from_utf8_lossy(bytes);
+
+    let count = text
+        .match_indices("/Count")
+        .filter_map(|(index, _)| {
+            text[index + "/Count".len()..]
+                .trim_start()
+                .split(|c: char| !c.is_ascii_digit())
+                .next()
+                .and_then(|number| number.parse::<usize>().ok())
+        })
+        .max();
+
+    let pages = ["/Type /Page", "/Type/Page"]
+        .iter()
+        .map(|pattern| {
+            text.match_indices(pattern)
+                .filter(|(index, _)| !text[index + pattern.len()..].starts_with('s'))
+                .count()
+        })
+        .sum::<usize>();
+
+    count.or((pages > 0).then_some(pages))
+}
+
 #[derive(Error, Debug)]
 pub enum MailSenderError {
     #[error("invalid file path")]
@@ -30,6 +108,9 @@ pub enum MailSenderError {
     #[error("no file")]
     NoFile,
 
+    #[error("recipient domain is blocked: {0}")]
+    BlockedDomain(String),
+
     #[error("couldn't send email: {0}")]
     CouldntSendEmail(#[from] lettre::error::Error),
 
@@ -58,41 +139,148 @@ pub struct Recipient {
     pub mail: Address,
 }
 
+impl Recipient {
+    fn parse(person: Person, app: tauri::AppHandle) -> Recipient {
+        Recipient {
+            name: person.name,
+            mail: person
+                .mail
+                .parse()
+                .unwrap_or_else(|_| error_parsing_mail_address(app, person.mail)),
+        }
+    }
+
+    fn is_person(&self, person: &Person) -> bool {
+        self.mail
+            .to_string()
+            .eq_ignore_ascii_case(person.mail.trim())
+    }
+}
+
 #[derive(Default, Debug)]
 pub struct MailSender {
     people: Vec<Recipient>,
+    //people picked through distribution lists are kept apart, so deselecting a list
+    //doesn't remove people picked one by one
+    distribution_lists: Vec<(usize, Vec<Recipient>)>,
     files: Option<Vec<PathBuf>>,
+    sender: Option<Person>, //mechanic who sends, replies go to them
+    template: Option<MessageTemplate>,
+    resent_from: Option<usize>, //history entry whose archived files are sent again
 }
 
 impl MailSender {
+    //selection restored from an approval request, recipients are passed to send
+    pub fn with_files(files: Vec<PathBuf>) -> MailSender {
+        MailSender {
+            files: Some(files),
+            ..Default::default()
+        }
+    }
+    pub fn set_sender(&mut self, sender: Option<Person>) -> &mut Self {
+        self.sender = sender;
+        self
+    }
+    pub fn set_template(&mut self, template: Option<MessageTemplate>) -> &mut Self {
+        self.template = template;
+        self
+    }
+    pub fn template(&self) -> Option<&MessageTemplate> {
+        self.template.as_ref()
+    }
+    pub fn set_resent_from(&mut self, resent_from: Option<usize>) -> &mut Self {
+        self.resent_from = resent_from;
+        self
+    }
+    pub fn resent_from(&self) -> Option<usize> {
+        self.resent_from
+    }
+    //archived copies from the history, they were checked when archived
+    pub fn set_files(&mut self, files: Vec<PathBuf>) -> &mut Self {
+        self.files = Some(files);
+        self
+    }
+    pub fn sender(&self) -> Option<&Person> {
+        self.sender.as_ref()
+    }
     pub fn add_person(&mut self, person: Person, app: tauri::AppHandle) -> &mut Self {
-        let person_parsed = Recipient {
-            name: person.name,
-            mail: person
-                .mail
-                .parse()
-                .unwrap_or_else(|_| error_parsing_mail_address(app, person.mail)),
-        };
+        let person_parsed = Recipient::parse(person, app);
 
         self.people.push(person_parsed);
 
         self
     }
 
-    pub fn remove_person(&mut self, person: Person, app: tauri::AppHandle) -> &mut Self {
-        let person_parsed = Recipient {
-            name: person.name,
-            mail: person
-                .mail
-                .parse()
-                .unwrap_or_else(|_| error_parsing_mail_address(app, person.mail)),
-        };
+    //compared by mail, so removing address that isn't picked does nothing
+    pub fn remove_person(&mut self, person: Person) -> &mut Self {
+        self.people.retain(|x| !x.is_person(&person));
+
+        self
+    }
 
-        self.people.retain(|x| *x != person_parsed);
+    pub fn select_distribution_list(
+        &mut self,
+        id: usize,
+        people: Vec<Person>,
+        app: tauri::AppHandle,
+    ) -> &mut Self {
+        let recipients: Vec<Recipient> = people
+            .into_iter()
+            .map(|person| Recipient::parse(person, app.clone()))
+            .collect();
+
+        self.deselect_distribution_list(id);
+        self.distribution_lists.push((id, recipients));
+
+        self
+    }
+
+    pub fn deselect_distribution_list(&mut self, id: usize) -> &mut Self {
+        self.distribution_lists
+            .retain(|(list_id, _)| *list_id != id);
 
         self
     }
 
+    pub fn is_distribution_list_selected(&self, id: usize) -> bool {
+        self.distribution_lists
+            .iter()
+            .any(|(list_id, _)| *list_id == id)
+    }
+
+    pub fn is_picked(&self, person: &Person) -> bool {
+        self.people
+            .iter()
+            .any(|recipient| recipient.is_person(person))
+    }
+
+    pub fn is_in_selected_list(&self, person: &Person) -> bool {
+        self.distribution_lists
+            .iter()
+            .flat_map(|(_, recipients)| recipients.iter())
+            .any(|recipient| recipient.is_person(person))
+    }
+
+    //everybody is there only once, even if picked more ways
+    pub fn recipients(&self) -> Vec<Recipient> {
+        let mut recipients: Vec<Recipient> = vec![];
+
+        self.people
+            .iter()
+            .chain(
+                self.distribution_lists
+                    .iter()
+                    .flat_map(|(_, recipients)| recipients.iter()),
+            )
+            .for_each(|recipient| {
+                if !recipients.iter().any(|r| r.mail == recipient.mail) {
+                    recipients.push(recipient.clone());
+                }
+            });
+
+        recipients
+    }
+
     pub fn add_file(&mut self, vec_path: Vec<FilePath>) -> Result<(), MailSenderError> {
         let mut file_paths: Vec<PathBuf> = vec![];
 
@@ -108,20 +296,67 @@ impl MailSender {
             file_paths.push(path);
         }
 
+        //newly picked files are not a resend anymore
         self.files = Some(file_paths);
+        self.resent_from = None;
 
         Ok(())
     }
 
+    //returns the message as it left, its hash goes into the history
     pub fn send(
         &mut self,
         other_mail_list: Vec<mail_list_utils::Person>,
         config: Config,
         app: tauri::AppHandle,
-    ) -> Result<(), MailSenderError> {
+    ) -> Result<Vec<u8>, MailSenderError> {
+        let message = self.build_message(other_mail_list, &config, app.clone())?;
+
+        //get credentials
+        let creds = config.credentials();
+
+        // open a remote connection to gmail
+        let mailer = SmtpTransport::relay(config.smtp_transport())
+            .map_err(|_| MailSenderError::NoRemoteConnection)?
+            .credentials(creds)
+            .build();
+
+        //send the email
+        mailer
+            .send(&message)
+            .map_err(MailSenderError::ErrorOpeningSMTP)?;
+
+        let formatted = message.formatted();
+
+        record_archive(
+            &app,
+            ArchivedMessage::new(
+                message.envelope(),
+                config.title().to_string(),
+                self.file_names(),
+                self.sender.as_ref().map(|sender| sender.name.clone()),
+            ),
+            &formatted,
+            &self.files(),
+        );
+
+        Ok(formatted)
+    }
+
+    //message is built separately, so its size can be shown before sending
+    pub fn build_message(
+        &self,
+        other_mail_list: Vec<mail_list_utils::Person>,
+        config: &Config,
+        app: tauri::AppHandle,
+    ) -> Result<Message, MailSenderError> {
         let mut mail = MailSender {
-            people: self.people.clone(),
+            people: self.recipients(),
+            distribution_lists: vec![],
             files: self.files.clone(),
+            sender: self.sender.clone(),
+            template: self.template.clone(),
+            resent_from: self.resent_from,
         };
 
         other_mail_list.iter().for_each(|person| {
@@ -134,6 +369,12 @@ impl MailSender {
         if mail.files.is_none() {
             return Err(MailSenderError::NoFile);
         }
+        //the user is told about blocked recipients before, this only guards the policy
+        if let Some(recipient) = mail.people.iter().find(|recipient| {
+            config.recipient_domain(recipient.mail.as_ref()) == RecipientDomain::Blocked
+        }) {
+            return Err(MailSenderError::BlockedDomain(recipient.mail.to_string()));
+        }
 
         let mut message_builder = Message::builder();
 
@@ -146,6 +387,17 @@ impl MailSender {
                 .map_err(|_| MailSenderError::InvalidSenderMail)?,
         ));
 
+        //replies go to the mechanic, not to the shared mailbox
+        if let Some(sender) = &mail.sender {
+            message_builder = message_builder.reply_to(Mailbox::new(
+                Some(sender.name.clone()),
+                sender
+                    .mail
+                    .parse()
+                    .map_err(|_| MailSenderError::InvalidSenderMail)?,
+            ));
+        }
+
         //recipient
         message_builder = mail
             .people
@@ -160,8 +412,11 @@ impl MailSender {
         //subject
         message_builder = message_builder.subject(config.title());
 
-        //attachments
-        let mut attachment_multipart = MultiPart::mixed().build();
+        //body, attachments
+        let mut attachment_multipart = match self.body_text(config) {
+            Some(body_text) => MultiPart::mixed().singlepart(SinglePart::plain(body_text)),
+            None => MultiPart::mixed().build(),
+        };
 
         if let Some(mail_files) = mail.files {
             for file_path in mail_files {
@@ -190,31 +445,87 @@ impl MailSender {
             return Err(MailSenderError::InvalidFilePath);
         }
 
-        let message = message_builder.multipart(attachment_multipart);
-
-        //get credentials
-        let creds = config.credentials();
+        message_builder
+            .multipart(attachment_multipart)
+            .map_err(MailSenderError::CouldntSendEmail)
+    }
 
-        // open a remote connection to gmail
+    //already built message, used for scheduled sends
+    pub fn send_raw(
+        config: &Config,
+        envelope: &Envelope,
+        message: &[u8],
+    ) -> Result<(), MailSenderError> {
         let mailer = SmtpTransport::relay(config.smtp_transport())
             .map_err(|_| MailSenderError::NoRemoteConnection)?
-            .credentials(creds)
+            .credentials(config.credentials())
             .build();
 
-        //send the email
         mailer
-            .send(&message.map_err(MailSenderError::CouldntSendEmail)?)
+            .send_raw(envelope, message)
             .map_err(MailSenderError::ErrorOpeningSMTP)?;
 
         Ok(())
     }
 
+    //without the mechanic the message has attachments only
+    pub fn body_text(&self, config: &Config) -> Option<String> {
+        let signature = if config.signature().trim().is_empty() {
+            config.sender_name()
+        } else {
+            config.signature()
+        };
+
+        let template_body = self
+            .template
+            .as_ref()
+            .map(|template| template.body.trim())
+            .filter(|body| !body.is_empty());
+
+        self.sender.as_ref().map(|sender| {
+            let body = match template_body {
+                Some(body) => fill_placeholders(body, Some(&sender.name)),
+                None => format!(
+                    "Dobrý den,\n\nv příloze Vám {} zasílá dokumenty.",
+                    sender.name
+                ),
+            };
+
+            format!("{body}\n\n-- \n{}\n{}", sender.name, signature)
+        })
+    }
+
     pub fn file_is_valid(&self) -> bool {
         self.files.is_some()
     }
 
+    pub fn file_names(&self) -> Vec<String> {
+        self.files
+            .iter()
+            .flatten()
+            .map(|path| {
+                path.file_name()
+                    .and_then(|name| name.to_str())
+                    .unwrap_or("soubor")
+                    .to_string()
+            })
+            .collect()
+    }
+
+    pub fn files(&self) -> Vec<PathBuf> {
+        self.files.clone().unwrap_or_default()
+    }
+
+    pub fn attachments(&self) -> Vec<AttachmentInfo> {
+        self.files
+            .iter()
+            .flatten()
+            .map(|path| AttachmentInfo::load(path))
+            .collect()
+    }
+
     pub fn person_list_is_valid(&self) -> bool {
-        !self.people.is_empty()
+        !self.recipients().is_empty()
     }
 
     pub fn send_feedback(text: String, config: Config) -> Result<()> {
@@ -255,8 +566,19 @@ impl MailSender {
         Ok(())
     }
 
+    //preset replaces the selection, picked files stay
+    pub fn clear_recipients(&mut self) {
+        self.template = None;
+        self.people.clear();
+        self.distribution_lists.clear();
+    }
+
     pub fn clear(&mut self) {
         self.files = None;
+        self.sender = None;
+        self.template = None;
+        self.resent_from = None;
         self.people.clear();
+        self.distribution_lists.clear();
     }
 }
@@ -1,6 +1,8 @@
+use lettre::message::Mailbox;
 use lettre::Address;
 use maud::{html, Markup};
 
+use crate::backend::domain_suggestions::DomainSuggestions;
 use crate::backend::mail_list_utils::Person;
 
 //---------------------------
@@ -9,6 +11,7 @@ use crate::backend::mail_list_utils::Person;
 pub struct OtherMailList {
     list: Vec<Option<Person>>,
     size: usize,
+    kept_mails: Vec<String>, //lowercase mails sent as typed despite suggestion
 }
 
 impl OtherMailList {
@@ -16,19 +19,23 @@ impl OtherMailList {
         self.size
     }
 
-    pub fn render_input_fields(&self) -> Markup {
+    pub fn render_input_fields(&self, domains: &DomainSuggestions) -> Markup {
         let markup: Markup = html! {
             @for (index, person) in (self.list.iter().enumerate()) {
                 @if let Some(person) = person{
                        div.other-mail-button-row{
-                            input.other-mail-input-field
+                            input.other-mail-input-field.invalid-input[has_invalid_mail(person)]
                             type="text"
                             hx-post="command:edit_mail"
                             name="text"
                             hx-trigger="change"
+                            hx-target="#other-mail-buttons"
+                            hx-swap="innerHTML"
                             hx-vals={(format!(r#""index": {index}"#))}
                             placeholder="Zadejte prosím E-mail"
-                            value=(person.mail)
+                            list="address-history"
+                            autocomplete="off"
+                            value=(display_value(person))
                             {}
                             button.remove-button
                             hx-post="command:remove_other_row"
@@ -38,6 +45,28 @@ impl OtherMailList {
                             hx-vals={(format!(r#""index": {index}"#))}
                             {("odstranit")}
                        }
+                       @if let Some(problem) = mail_problem(&person.mail) {
+                           p.other-mail-error{(format!("\"{}\" - {problem}", person.mail))}
+                       }
+                       @if let Some(suggestion) = self.suggestion(person, domains) {
+                           div.other-mail-suggestion{
+                               p.other-mail-suggestion-text{(format!("Nemysleli jste \"{suggestion}\"?"))}
+                               button.add-button
+                               hx-post="command:apply_mail_suggestion"
+                               hx-trigger="click"
+                               hx-target="#other-mail-buttons"
+                               hx-swap="innerHTML"
+                               hx-vals={(format!(r#""index": {index}"#))}
+                               {("opravit")}
+                               button.remove-button
+                               hx-post="command:keep_mail"
+                               hx-trigger="click"
+                               hx-target="#other-mail-buttons"
+                               hx-swap="innerHTML"
+                               hx-vals={(format!(r#""index": {index}"#))}
+                               {("ponechat")}
+                           }
+                       }
                 }
             }
             div #other-mail-list-placeholder {}
@@ -52,11 +81,69 @@ impl OtherMailList {
         }));
     }
 
-    pub fn edit_person(&mut self, mail: &str, index: usize) {
-        self.list[index] = Some(Person {
-            name: mail.to_string(),
-            mail: mail.to_string(),
+    //filled row, used by presets
+    pub fn add_mail(&mut self, mail: String) {
+        self.list.push(Some(Person {
+            name: mail.clone(),
+            mail,
+        }));
+        self.increment_size();
+    }
+
+    //pasted list is split into more rows, the first one replaces the edited row
+    //fragments that can't be parsed keep their text, so they are shown as invalid
+    pub fn edit_person(&mut self, text: &str, index: usize) {
+        let mut people = parse_address_list(text).into_iter().map(|fragment| {
+            fragment.unwrap_or_else(|raw| Person {
+                name: raw.clone(),
+                mail: raw,
+            })
         });
+
+        self.list[index] = Some(people.next().unwrap_or(Person {
+            name: "".to_string(),
+            mail: "".to_string(),
+        }));
+
+        for person in people {
+            self.list.push(Some(person));
+            self.increment_size();
+        }
+    }
+
+    //None once the user decided to keep the mail as typed
+    fn suggestion(&self, person: &Person, domains: &DomainSuggestions) -> Option<String> {
+        if self.kept_mails.contains(&person.mail.trim().to_lowercase()) {
+            return None;
+        }
+
+        domains.suggest(&person.mail)
+    }
+
+    pub fn has_suggestions(&self, domains: &DomainSuggestions) -> bool {
+        self.list
+            .iter()
+            .flatten()
+            .any(|person| self.suggestion(person, domains).is_some())
+    }
+
+    pub fn apply_suggestion(&mut self, index: usize, domains: &DomainSuggestions) {
+        let Some(Some(person)) = self.list.get_mut(index) else {
+            return;
+        };
+
+        if let Some(suggestion) = domains.suggest(&person.mail) {
+            if person.name == person.mail {
+                person.name = suggestion.clone();
+            }
+            person.mail = suggestion;
+        }
+    }
+
+    pub fn keep_mail(&mut self, index: usize) {
+        if let Some(Some(person)) = self.list.get(index) {
+            self.kept_mails.push(person.mail.trim().to_lowercase());
+        }
     }
 
     pub fn remove_person(&mut self, index: usize) {
@@ -102,11 +189,252 @@ impl OtherMailList {
         self.list
             .iter()
             .filter_map(|person| person.as_ref())
-            .all(|person| person.mail.parse::<Address>().is_ok())
+            .all(|person| !has_invalid_mail(person))
+    }
+
+    pub fn remove_invalid_persons(&mut self) {
+        self.list.iter_mut().for_each(|person| {
+            if person.as_ref().is_some_and(has_invalid_mail) {
+                *person = None;
+            }
+        });
     }
 
     pub fn clear(&mut self) {
         self.list.clear();
         self.size = 0;
+        self.kept_mails.clear();
+    }
+}
+
+fn has_invalid_mail(person: &Person) -> bool {
+    mail_problem(&person.mail).is_some()
+}
+
+//reason shown under the row, None for valid or empty mail
+pub fn mail_problem(mail: &str) -> Option<String> {
+    //mistyped endings of common domains
+    static TLD_TYPOS: [&str; 7] = ["con", "cmo", "ocm", "cpm", "vom", "xom", "zc"];
+
+    if mail.is_empty() || (mail.parse::<Address>().is_ok() && !mail.contains(char::is_whitespace)) {
+        let tld = mail.rsplit('.').next().unwrap_or_default();
+
+        return TLD_TYPOS
+            .contains(&tld.to_lowercase().as_str())
+            .then(|| format!("pravděpodobně překlep v koncovce \".{tld}\""));
+    }
+
+    let problem = if mail.contains(char::is_whitespace) {
+        "E-mail obsahuje mezeru"
+    } else if !mail.contains('@') {
+        "chybí znak @"
+    } else if mail.matches('@').count() > 1 {
+        "E-mail obsahuje více znaků @"
+    } else if mail.starts_with('@') {
+        "chybí část před @"
+    } else if mail.ends_with('@') {
+        "chybí doména za @"
+    } else if mail.contains("..") || mail.ends_with('.') || mail.contains("@.") {
+        "chybná tečka v E-mailu"
+    } else if !mail.rsplit('@').next().unwrap_or_default().contains('.') {
+        "doméně chybí koncovka, např. .cz"
+    } else {
+        "neplatný E-mail"
+    };
+
+    Some(problem.to_string())
+}
+
+//"Name <mail>" is shown the same way it was typed
+fn display_value(person: &Person) -> String {
+    if person.name.is_empty() || person.name == person.mail {
+        person.mail.clone()
+    } else {
+        format!("{} <{}>", person.name, person.mail)
+    }
+}
+
+//RFC 5322 address list, separated by comma, semicolon or new line
+//Err holds the fragment that isn't valid address
+pub fn parse_address_list(text: &str) -> Vec<Result<Person, String>> {
+    split_address_list(text)
+        .into_iter()
+        .flat_map(|fragment| match fragment.parse::<Mailbox>() {
+            Ok(mailbox) => vec![Ok(mailbox_person(mailbox))],
+            //addresses separated only by spaces are accepted too
+            Err(_) => {
+                let words: Vec<Result<Address, _>> = fragment
+                    .split_whitespace()
+                    .map(|word| word.parse::<Address>())
+                    .collect();
+
+                if words.len() > 1 && words.iter().all(|word| word.is_ok()) {
+                    words
+                        .into_iter()
+                        .flatten()
+                        .map(|address| {
+                            Ok(Person {
+                                name: address.to_string(),
+                                mail: address.to_string(),
+                            })
+                        })
+                        .collect()
+                } else {
+                    vec![Err(fragment)]
+                }
+            }
+        })
+        .collect()
+}
+
+fn mailbox_person(mailbox: Mailbox) -> Person {
+    let mail = mailbox.email.to_string();
+
+    Person {
+        name: mailbox.name.unwrap_or_else(|| mail.clone()),
+        mail,
+    }
+}
+
+//separators inside quoted names or angle brackets are kept
+fn split_address_list(text: &str) -> Vec<String> {
+    let mut fragments: Vec<String> = vec![];
+    let mut fragment = String::new();
+    let mut quoted = false;
+    let mut in_angle = false;
+
+    for c in text.chars() {
+        match c {
+            '"' => {
+                quoted = !quoted;
+                fragment.push(c);
+            }
+            '<' if !quoted => {
+                in_angle = true;
+                fragment.push(c);
+            }
+            '>' if !quoted => {
+                in_angle = false;
+                fragment.push(c);
+            }
+            ',' | ';' | '\n' | '\r' if !quoted && !in_angle => {
+                fragments.push(std::mem::take(&mut fragment));
+            }
+            c => fragment.push(c),
+        }
+    }
+    fragments.push(fragment);
+
+    fragments
+        .into_iter()
+        .map(|fragment| fragment.trim().to_string())
+        .filter(|fragment| !fragment.is_empty())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn named(text: &str) -> Vec<Result<(String, String), String>> {
+        parse_address_list(text)
+            .into_iter()
+            .map(|result| result.map(|person| (person.name, person.mail)))
+            .collect()
+    }
+
+    fn parsed(text: &str) -> Vec<Result<String, String>> {
+        parse_address_list(text)
+            .into_iter()
+            .map(|result| result.map(|person| person.mail))
+            .collect()
+    }
+
+    #[test]
+    fn list_is_split_by_every_separator() {
+        assert_eq!(
+            split_address_list("a@firma.cz, b@firma.cz; c@firma.cz\nd@firma.cz\r\ne@firma.cz"),
+            vec![
+                "a@firma.cz",
+                "b@firma.cz",
+                "c@firma.cz",
+                "d@firma.cz",
+                "e@firma.cz",
+            ]
+        );
+    }
+
+    #[test]
+    fn empty_fragments_are_dropped() {
+        assert_eq!(
+            split_address_list(" , a@firma.cz,,;\n\n b@firma.cz ; "),
+            vec!["a@firma.cz", "b@firma.cz"]
+        );
+        assert!(split_address_list("").is_empty());
+    }
+
+    #[test]
+    fn separators_in_quoted_names_are_kept() {
+        assert_eq!(
+            split_address_list(r#""Novak, Jan" <jan@firma.cz>, petr@firma.cz"#),
+            vec![r#""Novak, Jan" <jan@firma.cz>"#, "petr@firma.cz"]
+        );
+    }
+
+    #[test]
+    fn separators_in_angle_brackets_are_kept() {
+        assert_eq!(
+            split_address_list("Jan <jan,novak@firma.cz>; petr@firma.cz"),
+            vec!["Jan <jan,novak@firma.cz>", "petr@firma.cz"]
+        );
+    }
+
+    #[test]
+    fn bare_address_is_its_own_name() {
+        assert_eq!(
+            named("jan@firma.cz"),
+            vec![Ok(("jan@firma.cz".to_string(), "jan@firma.cz".to_string()))]
+        );
+    }
+
+    #[test]
+    fn display_name_is_kept() {
+        assert_eq!(
+            named("Jan Novak <jan@firma.cz>"),
+            vec![Ok(("Jan Novak".to_string(), "jan@firma.cz".to_string()))]
+        );
+    }
+
+    #[test]
+    fn quoted_name_with_comma_is_one_address() {
+        assert_eq!(
+            parsed(r#""Novak, Jan" <jan@firma.cz>, petr@firma.cz"#),
+            vec![
+                Ok("jan@firma.cz".to_string()),
+                Ok("petr@firma.cz".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn addresses_separated_by_spaces_are_accepted() {
+        assert_eq!(
+            parsed("jan@firma.cz   petr@firma.cz"),
+            vec![
+                Ok("jan@firma.cz".to_string()),
+                Ok("petr@firma.cz".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn invalid_fragment_is_returned_whole() {
+        assert_eq!(
+            parsed("jan@firma.cz neplatny; petr@firma.cz"),
+            vec![
+                Err("jan@firma.cz neplatny".to_string()),
+                Ok("petr@firma.cz".to_string()),
+            ]
+        );
     }
 }
@@ -0,0 +1,435 @@
+use std::fs;
+use std::path::PathBuf;
+use std::thread;
+use std::time::Duration;
+
+use chrono::{Local, NaiveDateTime};
+use lettre::address::Envelope;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::backend::error_handling::{
+    error_loading_scheduled_sends, error_missed_scheduled_sends, error_saving_scheduled_sends,
+    error_sending_scheduled_mail,
+};
+use crate::backend::mail_sender::{MailSender, MailSenderError};
+use crate::backend::schema::{decode, unchanged, Migration, Versioned};
+use crate::backend::send_history::{record_history, sha256_hex, HistoryEntry, HistoryOutcome};
+use crate::backend::sent_archive::{record_archive, ArchivedMessage};
+use crate::backend::storage::{
+    scheduled_files_path, scheduled_message_path, scheduled_sends_path, write_atomically,
+};
+use crate::AppState;
+
+//---------------------------
+
+//same format as <input type="datetime-local">
+pub static SCHEDULE_FORMAT: &str = "%Y-%m-%dT%H:%M";
+
+static CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum ScheduleState {
+    Waiting,
+    Sending,
+    Missed, //time passed while the app was closed, it is sent only on request
+    Failed,
+}
+
+//message itself is stored next to the queue as <id>.eml
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ScheduledSend {
+    pub id: usize,
+    pub send_at: String,
+    pub state: ScheduleState,
+    pub from: String,
+    pub recipients: Vec<String>,
+    pub free_addresses: Vec<String>,
+    pub subject: String,
+    pub files: Vec<String>,
+    #[serde(default)]
+    pub sent_by: Option<String>,
+    #[serde(default)]
+    pub profile: Option<usize>, //SMTP of the profile is used, None - main profile
+    #[serde(default)]
+    pub file_paths: Vec<PathBuf>, //copies taken when scheduled, archived into the history once sent
+    #[serde(default)]
+    pub resent_from: Option<usize>,
+}
+
+impl ScheduledSend {
+    pub fn send_at(&self) -> Option<NaiveDateTime> {
+        NaiveDateTime::parse_from_str(&self.send_at, SCHEDULE_FORMAT).ok()
+    }
+
+    fn is_due(&self, now: NaiveDateTime) -> bool {
+        self.send_at().is_none_or(|send_at| send_at <= now)
+    }
+
+    fn envelope(&self) -> Result<Envelope, MailSenderError> {
+        let from = self
+            .from
+            .parse()
+            .map_err(|_| MailSenderError::InvalidSenderMail)?;
+        let recipients = self
+            .recipients
+            .iter()
+            .map(|mail| mail.parse())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| MailSenderError::InvalidMessage)?;
+
+        Envelope::new(Some(from), recipients).map_err(|_| MailSenderError::InvalidMessage)
+    }
+}
+
+//messages waiting for their time, kept over restarts
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(default)]
+pub struct ScheduledSends {
+    version: u32,
+    next_id: usize,
+    sends: Vec<ScheduledSend>,
+}
+
+impl ScheduledSends {
+    //missing file is an empty queue
+    pub fn load(app: AppHandle) -> ScheduledSends {
+        let empty = ScheduledSends {
+            version: ScheduledSends::CURRENT_VERSION,
+            ..Default::default()
+        };
+
+        let Ok(ron_string) = fs::read_to_string(scheduled_sends_path(&app)) else {
+            return empty;
+        };
+
+        decode::<ScheduledSends>(&ron_string).unwrap_or_else(|_| {
+            error_loading_scheduled_sends(app);
+            empty
+        })
+    }
+
+    pub fn save(&self, app: AppHandle) {
+        let Ok(ron_string) = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+        else {
+            return error_saving_scheduled_sends(app);
+        };
+
+        write_atomically(&scheduled_sends_path(&app), &ron_string)
+            .unwrap_or_else(|_| error_saving_scheduled_sends(app));
+    }
+
+    //the soonest first
+    pub fn sends(&self) -> Vec<&ScheduledSend> {
+        let mut sends: Vec<&ScheduledSend> = self.sends.iter().collect();
+
+        sends.sort_by_key(|send| send.send_at());
+
+        sends
+    }
+
+    //id of the send is assigned here
+    //attachments are copied along with the message, the history has to hash what was built,
+    //not what is on the disk when the time comes
+    pub fn add(
+        &mut self,
+        app: &AppHandle,
+        mut send: ScheduledSend,
+        message: &[u8],
+    ) -> std::io::Result<()> {
+        send.id = self.next_id;
+
+        let path = scheduled_message_path(app, send.id);
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        let files_dir = scheduled_files_path(app, send.id);
+        fs::create_dir_all(&files_dir)?;
+        let copies = send
+            .file_paths
+            .iter()
+            .enumerate()
+            .map(|(index, file_path)| {
+                //every file has its own directory, same named files don't overwrite each other
+                let file_dir = files_dir.join(index.to_string());
+                fs::create_dir_all(&file_dir)?;
+
+                let copy = file_dir.join(file_path.file_name().unwrap_or_default());
+                fs::copy(file_path, &copy)?;
+                Ok(copy)
+            })
+            .collect::<std::io::Result<Vec<PathBuf>>>();
+        send.file_paths = match copies {
+            Ok(copies) => copies,
+            Err(error) => {
+                let _ = fs::remove_dir_all(&files_dir);
+                return Err(error);
+            }
+        };
+
+        fs::write(path, message)?;
+
+        self.next_id += 1;
+        self.sends.push(send);
+
+        Ok(())
+    }
+
+    //only the time can be changed, recipients and subject are already in the built message
+    pub fn reschedule(&mut self, id: usize, send_at: NaiveDateTime) {
+        if let Some(send) = self.sends.iter_mut().find(|send| send.id == id) {
+            if send.state != ScheduleState::Sending {
+                send.send_at = send_at.format(SCHEDULE_FORMAT).to_string();
+                send.state = ScheduleState::Waiting;
+            }
+        }
+    }
+
+    pub fn cancel(&mut self, app: &AppHandle, id: usize) {
+        self.sends
+            .retain(|send| send.id != id || send.state == ScheduleState::Sending);
+
+        if !self.sends.iter().any(|send| send.id == id) {
+            let _ = fs::remove_file(scheduled_message_path(app, id));
+            let _ = fs::remove_dir_all(scheduled_files_path(app, id));
+        }
+    }
+
+    //sends that were due while the app was closed, or interrupted while sending
+    fn mark_missed(&mut self) -> usize {
+        let now = Local::now().naive_local();
+        let mut missed = 0;
+
+        for send in &mut self.sends {
+            if (send.state == ScheduleState::Waiting && send.is_due(now))
+                || send.state == ScheduleState::Sending
+            {
+                send.state = ScheduleState::Missed;
+                missed += 1;
+            }
+        }
+
+        missed
+    }
+
+    fn due_ids(&self) -> Vec<usize> {
+        let now = Local::now().naive_local();
+
+        self.sends
+            .iter()
+            .filter(|send| send.state == ScheduleState::Waiting && send.is_due(now))
+            .map(|send| send.id)
+            .collect()
+    }
+
+    //only one thread gets the send, the other one finds it already sending
+    fn start_sending(&mut self, id: usize) -> Option<ScheduledSend> {
+        let send = self
+            .sends
+            .iter_mut()
+            .find(|send| send.id == id && send.state != ScheduleState::Sending)?;
+
+        send.state = ScheduleState::Sending;
+
+        Some(send.clone())
+    }
+
+    fn set_state(&mut self, id: usize, state: ScheduleState) {
+        if let Some(send) = self.sends.iter_mut().find(|send| send.id == id) {
+            send.state = state;
+        }
+    }
+
+    //copies of the attachments are removed only after the history took them over
+    fn remove(&mut self, app: &AppHandle, id: usize) {
+        self.sends.retain(|send| send.id != id);
+
+        let _ = fs::remove_file(scheduled_message_path(app, id));
+    }
+}
+
+//missed sends are reported once, the rest is checked periodically
+pub fn start_scheduler(app: AppHandle) {
+    let app_state = app.state::<AppState>();
+
+    let missed = {
+        let mut scheduled_sends = app_state.scheduled_sends.lock();
+        let missed = scheduled_sends.mark_missed();
+
+        if missed > 0 {
+            scheduled_sends.save(app.clone());
+        }
+
+        missed
+    };
+
+    if missed > 0 {
+        error_missed_scheduled_sends(app.clone(), missed);
+    }
+
+    thread::spawn(move || loop {
+        thread::sleep(CHECK_INTERVAL);
+
+        let due_ids = app.state::<AppState>().scheduled_sends.lock().due_ids();
+
+        for id in due_ids {
+            dispatch(&app, id);
+        }
+    });
+}
+
+fn dispatch(app: &AppHandle, id: usize) {
+    if let Some(send) = begin_dispatch(app, id) {
+        deliver(app, send);
+    }
+}
+
+//None when the send is gone or somebody else is already sending it
+pub fn begin_dispatch(app: &AppHandle, id: usize) -> Option<ScheduledSend> {
+    let app_state = app.state::<AppState>();
+
+    let mut scheduled_sends = app_state.scheduled_sends.lock();
+
+    let send = scheduled_sends.start_sending(id)?;
+    scheduled_sends.save(app.clone());
+
+    Some(send)
+}
+
+//queue isn't locked while sending, so the list stays usable meanwhile
+pub fn deliver(app: &AppHandle, send: ScheduledSend) {
+    let app_state = app.state::<AppState>();
+    let id = send.id;
+
+    let config = app_state.config.lock().for_send(send.profile, None);
+
+    let result = send.envelope().and_then(|envelope| {
+        let message = fs::read(scheduled_message_path(app, id))
+            .map_err(|_| MailSenderError::InvalidFilePath)?;
+        let message = with_date(&message, &Local::now().to_rfc2822());
+
+        MailSender::send_raw(&config, &envelope, &message)?;
+
+        Ok((envelope, message))
+    });
+
+    let mut scheduled_sends = app_state.scheduled_sends.lock();
+
+    match result {
+        Ok((envelope, message)) => {
+            scheduled_sends.remove(app, id);
+            scheduled_sends.save(app.clone());
+            drop(scheduled_sends);
+
+            let mut send_stats = app_state.send_stats.lock();
+            send_stats.record(send.recipients.iter().map(|mail| mail.as_str()));
+            send_stats.record_free_addresses(send.free_addresses.iter().map(|mail| mail.as_str()));
+            send_stats.save(app.clone());
+            drop(send_stats);
+
+            record_archive(
+                app,
+                ArchivedMessage::new(
+                    &envelope,
+                    send.subject.clone(),
+                    send.files.clone(),
+                    send.sent_by.clone(),
+                ),
+                &message,
+                &send.file_paths,
+            );
+
+            record_history(
+                app,
+                HistoryEntry {
+                    sent_by: send.sent_by,
+                    resent_from: send.resent_from,
+                    message_hash: Some(sha256_hex(&message)),
+                    ..HistoryEntry::new(
+                        HistoryOutcome::Sent,
+                        send.recipients,
+                        send.subject,
+                        send.files,
+                    )
+                },
+                &send.file_paths,
+            );
+
+            let _ = fs::remove_dir_all(scheduled_files_path(app, id));
+        }
+        Err(error) => {
+            scheduled_sends.set_state(id, ScheduleState::Failed);
+            scheduled_sends.save(app.clone());
+            drop(scheduled_sends);
+
+            error_sending_scheduled_mail(app.clone(), error);
+        }
+    }
+}
+
+//message was built when scheduled, its Date header is replaced by the time it really leaves
+fn with_date(message: &[u8], date: &str) -> Vec<u8> {
+    //the last header line keeps its line ending
+    let header_end = message
+        .windows(4)
+        .position(|window| window == b"\r\n\r\n")
+        .map_or(message.len(), |position| position + 2);
+
+    let mut dated = Vec::with_capacity(message.len());
+    let mut replaced = false;
+
+    for line in message[..header_end].split_inclusive(|byte| *byte == b'\n') {
+        if !replaced && line.len() >= 5 && line[..5].eq_ignore_ascii_case(b"date:") {
+            dated.extend_from_slice(format!("Date: {date}\r\n").as_bytes());
+            replaced = true;
+        } else {
+            dated.extend_from_slice(line);
+        }
+    }
+
+    //message without a Date header gets one
+    if !replaced {
+        dated.splice(0..0, format!("Date: {date}\r\n").into_bytes());
+    }
+
+    dated.extend_from_slice(&message[header_end..]);
+
+    dated
+}
+
+impl Versioned for ScheduledSends {
+    const CURRENT_VERSION: u32 = 1;
+
+    fn migrations() -> &'static [Migration] {
+        &[unchanged]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    static DATE: &str = "Tue, 2 Jan 2024 09:30:00 +0100";
+
+    #[test]
+    fn date_header_is_replaced() {
+        let message =
+            b"From: a@b.cz\r\nDate: Mon, 1 Jan 2024 08:00:00 +0100\r\nSubject: x\r\n\r\nDate: body";
+
+        assert_eq!(
+            with_date(message, DATE),
+            b"From: a@b.cz\r\nDate: Tue, 2 Jan 2024 09:30:00 +0100\r\nSubject: x\r\n\r\nDate: body"
+        );
+    }
+
+    #[test]
+    fn last_date_header_keeps_the_blank_line() {
+        let message = b"From: a@b.cz\r\nDate: Mon, 1 Jan 2024 08:00:00 +0100\r\n\r\nbody";
+
+        assert_eq!(
+            with_date(message, DATE),
+            b"From: a@b.cz\r\nDate: Tue, 2 Jan 2024 09:30:00 +0100\r\n\r\nbody"
+        );
+    }
+}
@@ -0,0 +1,75 @@
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use thiserror::Error;
+
+//---------------------------
+
+#[derive(Error, Debug)]
+pub enum SchemaError {
+    #[error("couldn't decode file: {0}")]
+    Decode(#[from] ron::error::SpannedError),
+
+    #[error("couldn't encode file: {0}")]
+    Encode(#[from] ron::Error),
+
+    #[error("file version {0} is newer than this app")]
+    UnknownVersion(u32),
+}
+
+//migrations[n] upgrades file from version n to version n + 1
+//version is raised when a field is renamed, removed or changes its shape,
+//a new field with a serde default is read from older files as it is and keeps the version
+pub type Migration = fn(&str) -> Result<String, SchemaError>;
+
+pub trait Versioned: DeserializeOwned {
+    const CURRENT_VERSION: u32;
+
+    fn migrations() -> &'static [Migration];
+}
+
+//first migration of files written with the version field from the start, nothing to change
+pub fn unchanged(ron_string: &str) -> Result<String, SchemaError> {
+    Ok(ron_string.to_string())
+}
+
+//files without version field are from the time before versioning (version 0)
+#[derive(Deserialize)]
+struct VersionProbe {
+    #[serde(default)]
+    version: u32,
+}
+
+pub fn file_version(ron_string: &str) -> Result<u32, SchemaError> {
+    let probe: VersionProbe = ron::de::from_str(ron_string)?;
+
+    Ok(probe.version)
+}
+
+pub fn needs_migration<T: Versioned>(ron_string: &str) -> Result<bool, SchemaError> {
+    let version = file_version(ron_string)?;
+
+    if version > T::CURRENT_VERSION {
+        return Err(SchemaError::UnknownVersion(version));
+    }
+
+    Ok(version < T::CURRENT_VERSION)
+}
+
+pub fn upgrade<T: Versioned>(ron_string: &str) -> Result<String, SchemaError> {
+    let version = file_version(ron_string)?;
+
+    if version > T::CURRENT_VERSION {
+        return Err(SchemaError::UnknownVersion(version));
+    }
+
+    T::migrations()
+        .iter()
+        .skip(version as usize)
+        .try_fold(ron_string.to_string(), |ron_string, migration| {
+            migration(&ron_string)
+        })
+}
+
+pub fn decode<T: Versioned>(ron_string: &str) -> Result<T, SchemaError> {
+    Ok(ron::de::from_str(&upgrade::<T>(ron_string)?)?)
+}
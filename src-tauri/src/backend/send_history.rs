@@ -0,0 +1,460 @@
+use std::fs;
+use std::path::PathBuf;
+
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tauri::{AppHandle, Manager};
+
+use crate::backend::error_handling::{error_loading_send_history, error_saving_send_history};
+use crate::backend::schema::{decode, unchanged, Migration, Versioned};
+use crate::backend::storage::{send_history_path, sent_files_path, write_with_backup};
+use crate::AppState;
+
+//---------------------------
+
+static HISTORY_TIME_FORMAT: &str = "%d.%m.%Y %H:%M:%S";
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum HistoryOutcome {
+    Sent,
+    Rejected,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct FileHash {
+    pub name: String,
+    pub sha256: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct HistoryEntry {
+    pub id: usize,
+    pub time: String,
+    pub outcome: HistoryOutcome,
+    pub recipients: Vec<String>,
+    pub subject: String,
+    pub files: Vec<String>,
+    pub approved_by: Option<String>, //who approved or rejected, None when no approval was needed
+    pub note: String,
+    #[serde(default)]
+    pub sent_by: Option<String>, //mechanic who sent it, None before identification existed
+    #[serde(default)]
+    pub archived_files: Vec<PathBuf>, //copies of the attachments, the originals may be gone
+    #[serde(default)]
+    pub resent_from: Option<usize>, //entry this one resends or forwards
+    #[serde(default)]
+    pub file_hashes: Vec<FileHash>,
+    #[serde(default)]
+    pub message_hash: Option<String>, //SHA-256 of the whole message as it left, None - rejected
+    #[serde(default)]
+    pub previous_hash: String, //hash of the entry before, empty for the first one
+    #[serde(default)]
+    pub hash: String, //empty for entries written before the chain existed
+}
+
+//found by verify, entry id is the first field
+#[derive(Debug, Clone, PartialEq)]
+pub enum ChainProblem {
+    Altered(usize),             //content doesn't match its hash
+    Broken(usize),              //entry before it was changed or removed
+    FileChanged(usize, String), //archived copy of the attachment differs from the sent one
+}
+
+impl HistoryEntry {
+    //id and time are filled by the history
+    pub fn new(
+        outcome: HistoryOutcome,
+        recipients: Vec<String>,
+        subject: String,
+        files: Vec<String>,
+    ) -> HistoryEntry {
+        HistoryEntry {
+            id: 0,
+            time: String::new(),
+            outcome,
+            recipients,
+            subject,
+            files,
+            approved_by: None,
+            note: String::new(),
+            sent_by: None,
+            archived_files: vec![],
+            resent_from: None,
+            file_hashes: vec![],
+            message_hash: None,
+            previous_hash: String::new(),
+            hash: String::new(),
+        }
+    }
+
+    //every field that describes the send, paths of the copies can move with the data directory
+    fn chain_content(&self) -> String {
+        [
+            self.id.to_string(),
+            self.time.clone(),
+            format!("{:?}", self.outcome),
+            self.recipients.join(","),
+            self.subject.clone(),
+            self.files.join(","),
+            self.approved_by.clone().unwrap_or_default(),
+            self.note.clone(),
+            self.sent_by.clone().unwrap_or_default(),
+            self.resent_from
+                .map(|id| id.to_string())
+                .unwrap_or_default(),
+            self.file_hashes
+                .iter()
+                .map(|file| format!("{}={}", file.name, file.sha256))
+                .collect::<Vec<String>>()
+                .join(","),
+            self.message_hash.clone().unwrap_or_default(),
+            self.previous_hash.clone(),
+        ]
+        .join("\n")
+    }
+
+    fn chain_hash(&self) -> String {
+        sha256_hex(self.chain_content().as_bytes())
+    }
+
+    //entries sent before archiving existed have no copies
+    pub fn can_resend(&self) -> bool {
+        self.outcome == HistoryOutcome::Sent
+            && !self.archived_files.is_empty()
+            && self.archived_files.iter().all(|path| path.is_file())
+    }
+}
+
+//what left the workshop and who let it go
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(default)]
+pub struct SendHistory {
+    version: u32,
+    next_id: usize,
+    entries: Vec<HistoryEntry>,
+}
+
+impl SendHistory {
+    //missing file is an empty history, broken one is reported and the backup is offered
+    pub fn load(app: AppHandle) -> SendHistory {
+        let empty = SendHistory {
+            version: SendHistory::CURRENT_VERSION,
+            ..Default::default()
+        };
+
+        let Ok(ron_string) = std::fs::read_to_string(send_history_path(&app)) else {
+            return empty;
+        };
+
+        decode::<SendHistory>(&ron_string).unwrap_or_else(|_| {
+            error_loading_send_history(app);
+            empty
+        })
+    }
+
+    pub fn save(&self, app: AppHandle) {
+        let Ok(ron_string) = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+        else {
+            return error_saving_send_history(app);
+        };
+
+        write_with_backup(&send_history_path(&app), &ron_string)
+            .unwrap_or_else(|_| error_saving_send_history(app));
+    }
+
+    //the newest first
+    pub fn entries(&self) -> impl Iterator<Item = &HistoryEntry> {
+        self.entries.iter().rev()
+    }
+
+    pub fn entry(&self, id: usize) -> Option<&HistoryEntry> {
+        self.entries.iter().find(|entry| entry.id == id)
+    }
+
+    fn push(&mut self, app: &AppHandle, mut entry: HistoryEntry, files: &[PathBuf]) {
+        entry.archived_files = archive_files(app, self.next_id, files);
+        entry.file_hashes = files
+            .iter()
+            .filter_map(|path| {
+                Some(FileHash {
+                    name: path.file_name()?.to_string_lossy().to_string(),
+                    sha256: sha256_hex(&fs::read(path).ok()?),
+                })
+            })
+            .collect();
+
+        self.chain(entry);
+    }
+
+    //sends recorded while the user decided about the backup are added after the restored ones
+    pub fn take_over(&mut self, recorded: SendHistory) {
+        for entry in recorded.entries {
+            self.chain(entry);
+        }
+    }
+
+    //entry gets its id and time and is linked to the one before it, time of a taken over entry stays
+    fn chain(&mut self, mut entry: HistoryEntry) {
+        entry.id = self.next_id;
+        if entry.time.is_empty() {
+            entry.time = Local::now().format(HISTORY_TIME_FORMAT).to_string();
+        }
+        entry.previous_hash = self
+            .entries
+            .last()
+            .map(|previous| previous.hash.clone())
+            .unwrap_or_default();
+        entry.hash = entry.chain_hash();
+
+        self.next_id += 1;
+        self.entries.push(entry);
+    }
+
+    //only the entries before the chain existed may have no hash,
+    //an empty hash after the first hashed entry is a change like any other
+    pub fn verify(&self) -> Vec<ChainProblem> {
+        let mut problems = vec![];
+        let mut chained = false;
+
+        for (index, entry) in self.entries.iter().enumerate() {
+            if entry.hash.is_empty() && !chained {
+                continue;
+            }
+            chained = true;
+
+            let previous_hash = index
+                .checked_sub(1)
+                .and_then(|previous| self.entries.get(previous))
+                .map(|previous| previous.hash.as_str())
+                .unwrap_or_default();
+
+            if entry.chain_hash() != entry.hash {
+                problems.push(ChainProblem::Altered(entry.id));
+            }
+            if entry.previous_hash != previous_hash
+                || (index > 0 && self.entries[index - 1].id + 1 != entry.id)
+            {
+                problems.push(ChainProblem::Broken(entry.id));
+            }
+
+            for path in &entry.archived_files {
+                let Ok(bytes) = fs::read(path) else {
+                    continue;
+                };
+                let name = path
+                    .file_name()
+                    .map(|name| name.to_string_lossy().to_string())
+                    .unwrap_or_default();
+
+                let sent_hash = entry.file_hashes.iter().find(|file| file.name == name);
+                if sent_hash.is_some_and(|file| file.sha256 != sha256_hex(&bytes)) {
+                    problems.push(ChainProblem::FileChanged(entry.id, name));
+                }
+            }
+        }
+
+        if let Some(last) = self.entries.last() {
+            if last.id + 1 != self.next_id {
+                problems.push(ChainProblem::Broken(last.id));
+            }
+        }
+
+        problems
+    }
+
+    //entry and every hash after it up to the newest, so the chain can be checked by hand
+    pub fn proof(&self, id: usize) -> Option<String> {
+        let index = self.entries.iter().position(|entry| entry.id == id)?;
+        let entry = &self.entries[index];
+
+        let mut lines = vec![
+            "Doklad o odeslání".to_string(),
+            String::new(),
+            format!("záznam: {}", entry.id),
+            format!("čas: {}", entry.time),
+            format!("výsledek: {:?}", entry.outcome),
+            format!("příjemci: {}", entry.recipients.join(", ")),
+            format!("předmět: {}", entry.subject),
+            format!("přílohy: {}", entry.files.join(", ")),
+            format!("odeslal(a): {}", entry.sent_by.clone().unwrap_or_default()),
+            format!(
+                "schválil(a): {}",
+                entry.approved_by.clone().unwrap_or_default()
+            ),
+            format!("poznámka: {}", entry.note),
+            String::new(),
+        ];
+
+        for file in &entry.file_hashes {
+            lines.push(format!("SHA-256 přílohy {}: {}", file.name, file.sha256));
+        }
+        if let Some(message_hash) = &entry.message_hash {
+            lines.push(format!("SHA-256 celé zprávy (.eml): {message_hash}"));
+        }
+
+        lines.extend([
+            String::new(),
+            "obsah záznamu, ze kterého je počítán jeho hash (SHA-256):".to_string(),
+            entry.chain_content(),
+            String::new(),
+            format!("hash předchozího záznamu: {}", entry.previous_hash),
+            format!("hash záznamu: {}", entry.hash),
+            String::new(),
+            "následující záznamy (id: hash předchozího -> hash):".to_string(),
+        ]);
+
+        for later in &self.entries[index + 1..] {
+            lines.push(format!(
+                "{}: {} -> {}",
+                later.id, later.previous_hash, later.hash
+            ));
+        }
+
+        lines.push(String::new());
+        lines.push(if self.verify().is_empty() {
+            "kontrola historie v době exportu: v pořádku".to_string()
+        } else {
+            "kontrola historie v době exportu: NALEZENY ZMĚNY".to_string()
+        });
+
+        Some(lines.join("\n"))
+    }
+}
+
+//files - attachments that left, they are copied so the message can be sent again
+pub fn record_history(app: &AppHandle, entry: HistoryEntry, files: &[PathBuf]) {
+    let app_state = app.state::<AppState>();
+
+    let mut send_history = app_state.send_history.lock();
+
+    send_history.push(app, entry, files);
+    send_history.save(app.clone());
+}
+
+pub fn sha256_hex(bytes: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(bytes))
+}
+
+//copy that failed only means the message can't be resent, sending itself went fine
+fn archive_files(app: &AppHandle, id: usize, files: &[PathBuf]) -> Vec<PathBuf> {
+    let dir = sent_files_path(app, id);
+
+    if files.is_empty() || fs::create_dir_all(&dir).is_err() {
+        return vec![];
+    }
+
+    files
+        .iter()
+        .filter_map(|path| {
+            let archived = dir.join(path.file_name()?);
+            fs::copy(path, &archived).ok()?;
+            Some(archived)
+        })
+        .collect()
+}
+
+impl Versioned for SendHistory {
+    const CURRENT_VERSION: u32 = 1;
+
+    fn migrations() -> &'static [Migration] {
+        &[unchanged]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn history(count: usize) -> SendHistory {
+        let mut history = SendHistory::default();
+
+        for index in 0..count {
+            history.chain(HistoryEntry::new(
+                HistoryOutcome::Sent,
+                vec![format!("zakaznik{index}@firma.cz")],
+                format!("zakázka {index}"),
+                vec![format!("faktura{index}.pdf")],
+            ));
+        }
+
+        history
+    }
+
+    #[test]
+    fn untouched_history_is_valid() {
+        assert!(history(3).verify().is_empty());
+    }
+
+    #[test]
+    fn edited_entry_is_altered() {
+        let mut history = history(3);
+        history.entries[1].recipients = vec!["jiny@firma.cz".to_string()];
+
+        assert_eq!(history.verify(), vec![ChainProblem::Altered(1)]);
+    }
+
+    #[test]
+    fn deleted_entry_breaks_the_chain() {
+        let mut history = history(3);
+        history.entries.remove(1);
+
+        assert_eq!(history.verify(), vec![ChainProblem::Broken(2)]);
+    }
+
+    #[test]
+    fn deleted_newest_entry_is_found() {
+        let mut history = history(3);
+        history.entries.pop();
+
+        assert_eq!(history.verify(), vec![ChainProblem::Broken(1)]);
+    }
+
+    #[test]
+    fn reordered_entries_break_the_chain() {
+        let mut history = history(3);
+        history.entries.swap(1, 2);
+
+        let problems = history.verify();
+
+        assert!(problems.contains(&ChainProblem::Broken(2)));
+        assert!(problems.contains(&ChainProblem::Broken(1)));
+        assert!(!problems
+            .iter()
+            .any(|problem| matches!(problem, ChainProblem::Altered(_))));
+    }
+
+    #[test]
+    fn blanked_hash_of_newest_entry_is_altered() {
+        let mut history = history(3);
+        history.entries[2].hash.clear();
+        history.entries[2].note = "upraveno".to_string();
+
+        assert_eq!(history.verify(), vec![ChainProblem::Altered(2)]);
+    }
+
+    #[test]
+    fn blanked_hash_in_the_middle_is_altered() {
+        let mut history = history(3);
+        history.entries[1].hash.clear();
+
+        assert!(history.verify().contains(&ChainProblem::Altered(1)));
+    }
+
+    #[test]
+    fn entries_before_the_chain_are_skipped() {
+        let mut history = history(2);
+        for entry in &mut history.entries {
+            entry.hash.clear();
+            entry.previous_hash.clear();
+        }
+        history.chain(HistoryEntry::new(
+            HistoryOutcome::Rejected,
+            vec![],
+            "zakázka 2".to_string(),
+            vec![],
+        ));
+
+        assert!(history.verify().is_empty());
+    }
+}
@@ -0,0 +1,100 @@
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use crate::backend::config::ProfileChoice;
+use crate::backend::error_handling::{error_loading_send_presets, error_saving_send_presets};
+use crate::backend::schema::{decode, unchanged, Migration, Versioned};
+use crate::backend::storage::{send_presets_path, write_with_backup};
+
+//---------------------------
+
+//everything but the file, e.g. "záruka - dovozce"
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct SendPreset {
+    pub id: usize,
+    pub name: String,
+    pub contacts: Vec<usize>, //primary address of each contact is picked
+    pub distribution_lists: Vec<usize>,
+    pub other_addresses: Vec<String>,
+    pub profile: ProfileChoice,
+    pub subject: String, //empty - subject of the profile
+    pub body: String,    //empty - default text
+}
+
+//presets are edited in settings and saved right away, like the scheduled sends
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(default)]
+pub struct SendPresets {
+    version: u32,
+    next_id: usize,
+    presets: Vec<SendPreset>,
+}
+
+impl SendPresets {
+    //missing file means no presets yet
+    pub fn load(app: AppHandle) -> SendPresets {
+        let empty = SendPresets {
+            version: SendPresets::CURRENT_VERSION,
+            ..Default::default()
+        };
+
+        let Ok(ron_string) = std::fs::read_to_string(send_presets_path(&app)) else {
+            return empty;
+        };
+
+        decode::<SendPresets>(&ron_string).unwrap_or_else(|_| {
+            error_loading_send_presets(app);
+            empty
+        })
+    }
+
+    pub fn save(&self, app: AppHandle) {
+        let Ok(ron_string) = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+        else {
+            return error_saving_send_presets(app);
+        };
+
+        write_with_backup(&send_presets_path(&app), &ron_string)
+            .unwrap_or_else(|_| error_saving_send_presets(app));
+    }
+
+    pub fn presets(&self) -> &[SendPreset] {
+        &self.presets
+    }
+
+    pub fn preset(&self, id: usize) -> Option<&SendPreset> {
+        self.presets.iter().find(|preset| preset.id == id)
+    }
+
+    pub fn add(&mut self) -> usize {
+        let id = self.next_id;
+        self.next_id += 1;
+
+        self.presets.push(SendPreset {
+            id,
+            name: "nová předvolba".to_string(),
+            ..Default::default()
+        });
+
+        id
+    }
+
+    pub fn remove(&mut self, id: usize) {
+        self.presets.retain(|preset| preset.id != id);
+    }
+
+    pub fn update(&mut self, id: usize, update: impl FnOnce(&mut SendPreset)) {
+        if let Some(preset) = self.presets.iter_mut().find(|preset| preset.id == id) {
+            update(preset);
+        }
+    }
+}
+
+impl Versioned for SendPresets {
+    const CURRENT_VERSION: u32 = 1;
+
+    fn migrations() -> &'static [Migration] {
+        &[unchanged]
+    }
+}
@@ -0,0 +1,134 @@
+use std::collections::BTreeMap;
+
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use crate::backend::error_handling::error_saving_send_stats;
+use crate::backend::schema::{decode, unchanged, Migration, SchemaError, Versioned};
+use crate::backend::storage::{send_stats_path, write_atomically};
+
+//---------------------------
+
+static LAST_USED_FORMAT: &str = "%d.%m.%Y";
+
+//address typed into "ostatní", offered again in autocomplete
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct FreeAddress {
+    pub mail: String,
+    pub count: u32,
+    pub last_used: String,
+}
+
+//how many times was sent to each address, used for ranking search results
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(default)]
+pub struct SendStats {
+    version: u32,
+    counts: BTreeMap<String, u32>, //lowercase mail -> count
+    free_addresses: Vec<FreeAddress>,
+}
+
+impl SendStats {
+    //stats are only a convenience, missing or broken file starts them again
+    pub fn load(app: AppHandle) -> SendStats {
+        std::fs::read_to_string(send_stats_path(&app))
+            .ok()
+            .and_then(|ron_string| decode::<SendStats>(&ron_string).ok())
+            .unwrap_or(SendStats {
+                version: SendStats::CURRENT_VERSION,
+                ..Default::default()
+            })
+    }
+
+    pub fn save(&self, app: AppHandle) {
+        let Ok(ron_string) = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+        else {
+            return error_saving_send_stats(app);
+        };
+
+        write_atomically(&send_stats_path(&app), &ron_string)
+            .unwrap_or_else(|_| error_saving_send_stats(app));
+    }
+
+    pub fn record<'a>(&mut self, mails: impl IntoIterator<Item = &'a str>) {
+        for mail in mails {
+            *self.counts.entry(mail.trim().to_lowercase()).or_default() += 1;
+        }
+    }
+
+    pub fn record_free_addresses<'a>(&mut self, mails: impl IntoIterator<Item = &'a str>) {
+        let today = Local::now().format(LAST_USED_FORMAT).to_string();
+
+        for mail in mails {
+            let mail = mail.trim();
+
+            match self
+                .free_addresses
+                .iter_mut()
+                .find(|address| address.mail.eq_ignore_ascii_case(mail))
+            {
+                Some(address) => {
+                    address.count += 1;
+                    address.last_used = today.clone();
+                }
+                None => self.free_addresses.push(FreeAddress {
+                    mail: mail.to_string(),
+                    count: 1,
+                    last_used: today.clone(),
+                }),
+            }
+        }
+    }
+
+    //the most used first
+    pub fn free_addresses(&self) -> Vec<&FreeAddress> {
+        let mut free_addresses: Vec<&FreeAddress> = self.free_addresses.iter().collect();
+
+        free_addresses.sort_by(|a, b| b.count.cmp(&a.count).then(a.mail.cmp(&b.mail)));
+
+        free_addresses
+    }
+
+    pub fn counts(&self) -> impl Iterator<Item = (&str, u32)> {
+        self.counts
+            .iter()
+            .map(|(mail, count)| (mail.as_str(), *count))
+    }
+
+    pub fn count(&self, mail: &str) -> u32 {
+        self.counts
+            .get(&mail.trim().to_lowercase())
+            .copied()
+            .unwrap_or_default()
+    }
+}
+
+impl Versioned for SendStats {
+    const CURRENT_VERSION: u32 = 2;
+
+    fn migrations() -> &'static [Migration] {
+        &[unchanged, send_stats_v1_to_v2]
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct SendStatsV1 {
+    #[serde(default)]
+    version: u32,
+    counts: BTreeMap<String, u32>,
+}
+
+//version 2 added history of free addresses, it starts empty
+fn send_stats_v1_to_v2(ron_string: &str) -> Result<String, SchemaError> {
+    let send_stats: SendStatsV1 = ron::de::from_str(ron_string)?;
+
+    Ok(ron::ser::to_string_pretty(
+        &SendStats {
+            version: 2,
+            counts: send_stats.counts,
+            free_addresses: vec![],
+        },
+        ron::ser::PrettyConfig::default(),
+    )?)
+}
@@ -0,0 +1,361 @@
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use chrono::{Duration, Local, NaiveDateTime};
+use flate2::read::ZlibDecoder;
+use lettre::address::Envelope;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::backend::error_handling::{
+    error_archiving_message, error_loading_sent_archive, error_saving_sent_archive,
+};
+use crate::backend::mail_sender::MailSenderError;
+use crate::backend::schema::{decode, unchanged, Migration, Versioned};
+use crate::backend::storage::{archive_dir_path, sent_archive_path, write_atomically};
+use crate::AppState;
+
+//---------------------------
+
+static ARCHIVED_AT_FORMAT: &str = "%d.%m.%Y %H:%M:%S";
+
+//text of the attachments kept in the index for one message, the .eml has everything
+static TEXT_LIMIT: usize = 20_000;
+
+//index entry, the message itself is the .eml file
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ArchivedMessage {
+    pub id: usize,
+    pub time: String,
+    pub path: PathBuf, //relative to the archive directory
+    pub from: String,
+    pub recipients: Vec<String>,
+    pub subject: String,
+    pub files: Vec<String>,
+    pub sent_by: Option<String>,
+    pub text: String, //extracted from PDF attachments, only for searching
+}
+
+impl ArchivedMessage {
+    //id, time and path are filled by the archive
+    pub fn new(
+        envelope: &Envelope,
+        subject: String,
+        files: Vec<String>,
+        sent_by: Option<String>,
+    ) -> ArchivedMessage {
+        ArchivedMessage {
+            id: 0,
+            time: String::new(),
+            path: PathBuf::new(),
+            from: envelope
+                .from()
+                .map(|address| address.to_string())
+                .unwrap_or_default(),
+            recipients: envelope
+                .to()
+                .iter()
+                .map(|address| address.to_string())
+                .collect(),
+            subject,
+            files,
+            sent_by,
+            text: String::new(),
+        }
+    }
+
+    fn sent_at(&self) -> Option<NaiveDateTime> {
+        NaiveDateTime::parse_from_str(&self.time, ARCHIVED_AT_FORMAT).ok()
+    }
+
+    //every word has to be found somewhere, case doesn't matter
+    fn matches(&self, words: &[String]) -> bool {
+        let haystack = format!(
+            "{} {} {} {} {} {}",
+            self.subject,
+            self.from,
+            self.recipients.join(" "),
+            self.files.join(" "),
+            self.sent_by.as_deref().unwrap_or_default(),
+            self.text
+        )
+        .to_lowercase();
+
+        words.iter().all(|word| haystack.contains(word))
+    }
+
+    //message is sent again to the same addresses as it left
+    pub fn envelope(&self) -> Result<Envelope, MailSenderError> {
+        let from = self
+            .from
+            .parse()
+            .map_err(|_| MailSenderError::InvalidSenderMail)?;
+        let recipients = self
+            .recipients
+            .iter()
+            .map(|mail| mail.parse())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| MailSenderError::InvalidMessage)?;
+
+        Envelope::new(Some(from), recipients).map_err(|_| MailSenderError::InvalidMessage)
+    }
+}
+
+//every message that left, for disputes about what exactly was sent
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(default)]
+pub struct SentArchive {
+    version: u32,
+    next_id: usize,
+    messages: Vec<ArchivedMessage>,
+}
+
+impl SentArchive {
+    //missing file is an empty archive, the .eml files are kept even if the index is broken
+    pub fn load(app: AppHandle) -> SentArchive {
+        let empty = SentArchive {
+            version: SentArchive::CURRENT_VERSION,
+            ..Default::default()
+        };
+
+        let Ok(ron_string) = std::fs::read_to_string(sent_archive_path(&app)) else {
+            return empty;
+        };
+
+        decode::<SentArchive>(&ron_string).unwrap_or_else(|_| {
+            error_loading_sent_archive(app);
+            empty
+        })
+    }
+
+    pub fn save(&self, app: AppHandle) {
+        let Ok(ron_string) = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+        else {
+            return error_saving_sent_archive(app);
+        };
+
+        write_atomically(&sent_archive_path(&app), &ron_string)
+            .unwrap_or_else(|_| error_saving_sent_archive(app));
+    }
+
+    pub fn message(&self, id: usize) -> Option<&ArchivedMessage> {
+        self.messages.iter().find(|message| message.id == id)
+    }
+
+    //the newest first, empty query finds everything
+    pub fn search(&self, query: &str) -> Vec<&ArchivedMessage> {
+        let words: Vec<String> = query
+            .split_whitespace()
+            .map(|word| word.to_lowercase())
+            .collect();
+
+        self.messages
+            .iter()
+            .rev()
+            .filter(|message| message.matches(&words))
+            .collect()
+    }
+
+    //text of a resent message is taken over from the original, it has no files
+    fn push(
+        &mut self,
+        app: &AppHandle,
+        mut entry: ArchivedMessage,
+        message: &[u8],
+        files: &[PathBuf],
+    ) -> std::io::Result<()> {
+        let now = Local::now();
+
+        entry.id = self.next_id;
+        entry.time = now.format(ARCHIVED_AT_FORMAT).to_string();
+        entry.path = PathBuf::from(now.format("%Y").to_string())
+            .join(now.format("%m").to_string())
+            .join(format!("{}.eml", entry.id));
+        if !files.is_empty() {
+            entry.text = attachments_text(files);
+        }
+
+        let path = archive_dir_path(app).join(&entry.path);
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, message)?;
+
+        self.next_id += 1;
+        self.messages.push(entry);
+
+        Ok(())
+    }
+
+    //retention_days 0 keeps everything, returns whether something was removed
+    fn purge(&mut self, app: &AppHandle, retention_days: u32) -> bool {
+        if retention_days == 0 {
+            return false;
+        }
+
+        let oldest_kept = Local::now().naive_local() - Duration::days(retention_days.into());
+        let archive_dir = archive_dir_path(app);
+        let count = self.messages.len();
+
+        self.messages.retain(|message| {
+            let keep = message
+                .sent_at()
+                .is_none_or(|sent_at| sent_at >= oldest_kept);
+            if !keep {
+                let _ = fs::remove_file(archive_dir.join(&message.path));
+            }
+            keep
+        });
+
+        self.messages.len() != count
+    }
+}
+
+pub fn archived_message_path(app: &AppHandle, message: &ArchivedMessage) -> PathBuf {
+    archive_dir_path(app).join(&message.path)
+}
+
+//message already left, so failed archiving is only reported
+pub fn record_archive(app: &AppHandle, entry: ArchivedMessage, message: &[u8], files: &[PathBuf]) {
+    let app_state = app.state::<AppState>();
+
+    let retention_days = app_state.config.lock().archive_retention_days();
+
+    let mut sent_archive = app_state.sent_archive.lock();
+
+    if sent_archive.push(app, entry, message, files).is_err() {
+        error_archiving_message(app.clone());
+    }
+    sent_archive.purge(app, retention_days);
+    sent_archive.save(app.clone());
+}
+
+//on start, messages older than the retention period are removed
+pub fn purge_archive(app: &AppHandle) {
+    let app_state = app.state::<AppState>();
+
+    let retention_days = app_state.config.lock().archive_retention_days();
+
+    let mut sent_archive = app_state.sent_archive.lock();
+
+    if sent_archive.purge(app, retention_days) {
+        sent_archive.save(app.clone());
+    }
+}
+
+fn attachments_text(files: &[PathBuf]) -> String {
+    let text: String = files
+        .iter()
+        .filter(|path| is_pdf(path))
+        .filter_map(|path| fs::read(path).ok())
+        .map(|bytes| pdf_text(&bytes))
+        .collect::<Vec<String>>()
+        .join(" ");
+
+    text.chars().take(TEXT_LIMIT).collect()
+}
+
+fn is_pdf(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("pdf"))
+}
+
+//without PDF parser - strings shown by text operators in every stream, compressed or not,
+//only ASCII comes out right, which is enough for order numbers, plates and names
+fn pdf_text(bytes: &[u8]) -> String {
+    let mut text = String::new();
+    let mut rest = bytes;
+
+    while let Some(start) = find(rest, b"stream") {
+        let data = &rest[start + b"stream".len()..];
+        let data = data
+            .strip_prefix(b"\r\n")
+            .or_else(|| data.strip_prefix(b"\n"))
+            .unwrap_or(data);
+
+        let Some(end) = find(data, b"endstream") else {
+            break;
+        };
+
+        let mut decoded = vec![];
+        let _ = ZlibDecoder::new(&data[..end]).read_to_end(&mut decoded);
+
+        if decoded.is_empty() {
+            shown_strings(&data[..end], &mut text);
+        } else {
+            shown_strings(&decoded, &mut text);
+        }
+
+        rest = &data[end + b"endstream".len()..];
+    }
+
+    text
+}
+
+fn find(bytes: &[u8], pattern: &[u8]) -> Option<usize> {
+    bytes
+        .windows(pattern.len())
+        .position(|window| window == pattern)
+}
+
+//literal strings between BT and ET, e.g. (Zakázka 1234) Tj
+fn shown_strings(content: &[u8], text: &mut String) {
+    let mut in_text = false;
+    let mut depth = 0;
+    let mut index = 0;
+
+    while index < content.len() {
+        let byte = content[index];
+
+        if depth > 0 {
+            match byte {
+                b'\\' => {
+                    index += 1;
+                    if let Some(escaped) = content.get(index) {
+                        text.push(match escaped {
+                            b'n' | b'r' | b't' => ' ',
+                            other => *other as char,
+                        });
+                    }
+                }
+                b'(' => {
+                    depth += 1;
+                    text.push('(');
+                }
+                b')' => {
+                    depth -= 1;
+                    if depth > 0 {
+                        text.push(')');
+                    }
+                }
+                other => text.push(other as char),
+            }
+        } else if byte == b'(' && in_text {
+            depth = 1;
+        } else if is_operator(content, index, b"BT") {
+            in_text = true;
+        } else if is_operator(content, index, b"ET") {
+            in_text = false;
+            text.push(' ');
+        }
+
+        index += 1;
+    }
+}
+
+fn is_operator(content: &[u8], index: usize, operator: &[u8]) -> bool {
+    let is_delimiter = |byte: Option<&u8>| byte.is_none_or(|byte| byte.is_ascii_whitespace());
+
+    content[index..].starts_with(operator)
+        && is_delimiter(index.checked_sub(1).and_then(|before| content.get(before)))
+        && is_delimiter(content.get(index + operator.len()))
+}
+
+impl Versioned for SentArchive {
+    const CURRENT_VERSION: u32 = 1;
+
+    fn migrations() -> &'static [Migration] {
+        &[unchanged]
+    }
+}
@@ -0,0 +1,473 @@
+use std::env;
+use std::ffi::OsStr;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use chrono::Local;
+use parking_lot::Mutex;
+use tauri::{AppHandle, Manager};
+
+use crate::backend::error_handling::*;
+use crate::backend::schema::{decode, file_version, needs_migration, upgrade, Versioned};
+
+//---------------------------
+
+pub static CONFIG_FILE_NAME: &str = "config.ron";
+pub static MAIL_LIST_FILE_NAME: &str = "mail_list.ron";
+pub static SEND_STATS_FILE_NAME: &str = "send_stats.ron";
+pub static SCHEDULED_SENDS_FILE_NAME: &str = "scheduled_sends.ron";
+pub static SEND_HISTORY_FILE_NAME: &str = "send_history.ron";
+pub static APPROVAL_QUEUE_FILE_NAME: &str = "approval_queue.ron";
+pub static SEND_PRESETS_FILE_NAME: &str = "send_presets.ron";
+pub static SENT_ARCHIVE_FILE_NAME: &str = "sent_archive.ron";
+
+//prepared messages of scheduled sends, <id>.eml, their attachments in <id>/
+static SCHEDULED_DIR_NAME: &str = "scheduled";
+
+//copies of sent attachments, <history id>/<file name>
+static SENT_FILES_DIR_NAME: &str = "sent_files";
+
+//sent messages as they left, <year>/<month>/<id>.eml
+static ARCHIVE_DIR_NAME: &str = "archive";
+
+//messages "sent" during the tutorial, they never leave the computer, <time>.eml
+static TUTORIAL_DIR_NAME: &str = "tutorial";
+
+//all files are stored in this directory, if it is set
+static DATA_DIR_ENV: &str = "MAILSENDER_DATA_DIR";
+static DATA_DIR_FLAG: &str = "--data-dir";
+
+//portable mode keeps files next to the executable
+static PORTABLE_FLAG: &str = "--portable";
+static PORTABLE_MARKER_FILE_NAME: &str = "portable";
+
+//every save keeps a copy in this subdirectory, only the newest ones are kept
+static BACKUP_DIR_NAME: &str = "backups";
+static BACKUP_COUNT: usize = 10;
+static BACKUP_TIMESTAMP_FORMAT: &str = "%Y%m%d-%H%M%S-%3f";
+
+//broken files waiting for the user to decide about the backup
+//saves made meanwhile are kept and written once the file is released
+static HELD_FILES: Mutex<Vec<HeldFile>> = Mutex::new(Vec::new());
+
+struct HeldFile {
+    path: PathBuf,
+    pending: Option<PendingSave>, //the last save, None - nothing was saved yet
+}
+
+struct PendingSave {
+    contents: String,
+    backup: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct DataPaths {
+    config_dir: PathBuf,
+    data_dir: PathBuf,
+}
+
+impl DataPaths {
+    pub fn resolve(app: AppHandle) -> DataPaths {
+        let data_paths = match override_dir().or_else(portable_dir) {
+            Some(dir) => DataPaths {
+                config_dir: dir.clone(),
+                data_dir: dir,
+            },
+            None => DataPaths {
+                config_dir: app
+                    .path()
+                    .app_config_dir()
+                    .unwrap_or_else(|_| error_resolving_data_dir(app.clone())),
+                data_dir: app
+                    .path()
+                    .app_data_dir()
+                    .unwrap_or_else(|_| error_resolving_data_dir(app.clone())),
+            },
+        };
+
+        for dir in [&data_paths.config_dir, &data_paths.data_dir] {
+            fs::create_dir_all(dir).unwrap_or_else(|_| error_creating_data_dir(app.clone(), dir));
+        }
+
+        data_paths.migrate_from_working_dir(app);
+
+        data_paths
+    }
+
+    pub fn config_file(&self) -> PathBuf {
+        self.config_dir.join(CONFIG_FILE_NAME)
+    }
+
+    pub fn mail_list_file(&self) -> PathBuf {
+        self.data_dir.join(MAIL_LIST_FILE_NAME)
+    }
+
+    pub fn send_stats_file(&self) -> PathBuf {
+        self.data_dir.join(SEND_STATS_FILE_NAME)
+    }
+
+    pub fn scheduled_sends_file(&self) -> PathBuf {
+        self.data_dir.join(SCHEDULED_SENDS_FILE_NAME)
+    }
+
+    pub fn scheduled_message_file(&self, id: usize) -> PathBuf {
+        self.data_dir
+            .join(SCHEDULED_DIR_NAME)
+            .join(format!("{id}.eml"))
+    }
+
+    pub fn scheduled_files_dir(&self, id: usize) -> PathBuf {
+        self.data_dir.join(SCHEDULED_DIR_NAME).join(id.to_string())
+    }
+
+    pub fn send_history_file(&self) -> PathBuf {
+        self.data_dir.join(SEND_HISTORY_FILE_NAME)
+    }
+
+    pub fn sent_files_dir(&self, id: usize) -> PathBuf {
+        self.data_dir.join(SENT_FILES_DIR_NAME).join(id.to_string())
+    }
+
+    pub fn sent_archive_file(&self) -> PathBuf {
+        self.data_dir.join(SENT_ARCHIVE_FILE_NAME)
+    }
+
+    pub fn archive_dir(&self) -> PathBuf {
+        self.data_dir.join(ARCHIVE_DIR_NAME)
+    }
+
+    pub fn tutorial_dir(&self) -> PathBuf {
+        self.data_dir.join(TUTORIAL_DIR_NAME)
+    }
+
+    pub fn approval_queue_file(&self) -> PathBuf {
+        self.data_dir.join(APPROVAL_QUEUE_FILE_NAME)
+    }
+
+    pub fn send_presets_file(&self) -> PathBuf {
+        self.data_dir.join(SEND_PRESETS_FILE_NAME)
+    }
+
+    //older versions saved files into the working directory set by the desktop shortcut
+    fn migrate_from_working_dir(&self, app: AppHandle) {
+        for (file_name, new_path) in [
+            (CONFIG_FILE_NAME, self.config_file()),
+            (MAIL_LIST_FILE_NAME, self.mail_list_file()),
+        ] {
+            let old_path = PathBuf::from(file_name);
+
+            if new_path.exists() || !old_path.is_file() {
+                continue;
+            }
+
+            let migration_result =
+                fs::copy(&old_path, &new_path).and_then(|_| fs::remove_file(&old_path));
+
+            if migration_result.is_err() {
+                error_migrating_data_file(app.clone(), file_name);
+            }
+        }
+    }
+}
+
+pub fn config_path(app: &AppHandle) -> PathBuf {
+    app.state::<DataPaths>().config_file()
+}
+
+pub fn mail_list_path(app: &AppHandle) -> PathBuf {
+    app.state::<DataPaths>().mail_list_file()
+}
+
+pub fn send_stats_path(app: &AppHandle) -> PathBuf {
+    app.state::<DataPaths>().send_stats_file()
+}
+
+pub fn scheduled_sends_path(app: &AppHandle) -> PathBuf {
+    app.state::<DataPaths>().scheduled_sends_file()
+}
+
+pub fn scheduled_message_path(app: &AppHandle, id: usize) -> PathBuf {
+    app.state::<DataPaths>().scheduled_message_file(id)
+}
+
+pub fn scheduled_files_path(app: &AppHandle, id: usize) -> PathBuf {
+    app.state::<DataPaths>().scheduled_files_dir(id)
+}
+
+pub fn send_history_path(app: &AppHandle) -> PathBuf {
+    app.state::<DataPaths>().send_history_file()
+}
+
+pub fn sent_files_path(app: &AppHandle, id: usize) -> PathBuf {
+    app.state::<DataPaths>().sent_files_dir(id)
+}
+
+pub fn sent_archive_path(app: &AppHandle) -> PathBuf {
+    app.state::<DataPaths>().sent_archive_file()
+}
+
+pub fn archive_dir_path(app: &AppHandle) -> PathBuf {
+    app.state::<DataPaths>().archive_dir()
+}
+
+pub fn tutorial_dir_path(app: &AppHandle) -> PathBuf {
+    app.state::<DataPaths>().tutorial_dir()
+}
+
+pub fn approval_queue_path(app: &AppHandle) -> PathBuf {
+    app.state::<DataPaths>().approval_queue_file()
+}
+
+pub fn send_presets_path(app: &AppHandle) -> PathBuf {
+    app.state::<DataPaths>().send_presets_file()
+}
+
+fn override_dir() -> Option<PathBuf> {
+    let mut args = env::args().skip(1);
+
+    while let Some(arg) = args.next() {
+        if arg == DATA_DIR_FLAG {
+            return args.next().map(PathBuf::from);
+        }
+        if let Some(dir) = arg.strip_prefix(&format!("{DATA_DIR_FLAG}=")) {
+            return Some(PathBuf::from(dir));
+        }
+    }
+
+    env::var_os(DATA_DIR_ENV)
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+}
+
+fn portable_dir() -> Option<PathBuf> {
+    let exe_dir = env::current_exe().ok()?.parent()?.to_path_buf();
+
+    let portable_requested = env::args().skip(1).any(|arg| arg == PORTABLE_FLAG)
+        || exe_dir.join(PORTABLE_MARKER_FILE_NAME).exists();
+
+    portable_requested.then_some(exe_dir)
+}
+
+//file is written into temp file first, so crash during save can't leave truncated file
+pub fn write_atomically(path: &Path, contents: &str) -> std::io::Result<()> {
+    if hold_save(path, contents, false) {
+        return Ok(());
+    }
+
+    write_file(path, contents)
+}
+
+fn write_file(path: &Path, contents: &str) -> std::io::Result<()> {
+    let tmp_path = path.with_extension("ron.tmp");
+
+    let mut tmp_file = fs::File::create(&tmp_path)?;
+    tmp_file.write_all(contents.as_bytes())?;
+    tmp_file.sync_all()?;
+    drop(tmp_file);
+
+    fs::rename(&tmp_path, path)
+}
+
+pub fn write_with_backup(path: &Path, contents: &str) -> std::io::Result<()> {
+    if hold_save(path, contents, true) {
+        return Ok(());
+    }
+
+    write_file_with_backup(path, contents)
+}
+
+fn write_file_with_backup(path: &Path, contents: &str) -> std::io::Result<()> {
+    write_file(path, contents)?;
+
+    //file itself is already saved, missing backup is not worth bothering the user
+    let _ = create_backup(path, contents);
+
+    Ok(())
+}
+
+//newest first
+pub fn list_backups(path: &Path) -> Vec<PathBuf> {
+    let prefix = format!("{}-", file_stem(path));
+
+    let mut backups: Vec<PathBuf> = fs::read_dir(backup_dir(path))
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok())
+                .map(|entry| entry.path())
+                .filter(|backup| {
+                    backup
+                        .file_name()
+                        .and_then(OsStr::to_str)
+                        .is_some_and(|name| name.starts_with(&prefix) && name.ends_with(".ron"))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    //timestamp format sorts chronologically
+    backups.sort();
+    backups.reverse();
+
+    backups
+}
+
+pub fn newest_valid_backup<T: Versioned>(path: &Path) -> Option<(PathBuf, T)> {
+    list_backups(path).into_iter().find_map(|backup| {
+        let ron_string = fs::read_to_string(&backup).ok()?;
+        let decoded: T = decode(&ron_string).ok()?;
+        Some((backup, decoded))
+    })
+}
+
+//older file is upgraded in place, original is kept in backups as <name>.v<version>.ron
+pub fn migrate_file<T: Versioned>(app: AppHandle, path: &Path, ron_string: String) -> String {
+    //undecodable files are left for the decoding error handling
+    if !needs_migration::<T>(&ron_string).unwrap_or(false) {
+        return ron_string;
+    }
+
+    let migrated = match upgrade::<T>(&ron_string) {
+        Ok(migrated) => migrated,
+        Err(error) => {
+            error_migrating_file(app, path, error.to_string());
+            return ron_string;
+        }
+    };
+
+    let version = file_version(&ron_string).unwrap_or_default();
+    let pre_migration_backup = backup_dir(path).join(format!("{}.v{version}.ron", file_stem(path)));
+
+    let write_result = fs::create_dir_all(backup_dir(path))
+        .and_then(|_| fs::write(&pre_migration_backup, &ron_string))
+        .and_then(|_| write_atomically(path, &migrated));
+
+    //migrated data is still used, next save will write it
+    if let Err(error) = write_result {
+        error_migrating_file(app, path, error.to_string());
+    }
+
+    migrated
+}
+
+pub fn restore_backup(backup: &Path, path: &Path) -> std::io::Result<()> {
+    let ron_string = fs::read_to_string(backup)?;
+
+    write_atomically(path, &ron_string)
+}
+
+pub fn hold_saving(path: &Path) {
+    HELD_FILES.lock().push(HeldFile {
+        path: path.to_path_buf(),
+        pending: None,
+    });
+}
+
+//save made while the file was held is written now, true - something was written
+pub fn release_saving(path: &Path) -> std::io::Result<bool> {
+    let released: Vec<HeldFile> = {
+        let mut held_files = HELD_FILES.lock();
+        let (released, held) = std::mem::take(&mut *held_files)
+            .into_iter()
+            .partition(|held| held.path == path);
+        *held_files = held;
+        released
+    };
+
+    write_pending(released)
+}
+
+//app is closing without an answer, broken file is already kept next to the backups
+pub fn release_all_saving() -> std::io::Result<()> {
+    let released = std::mem::take(&mut *HELD_FILES.lock());
+
+    write_pending(released).map(|_| ())
+}
+
+fn write_pending(released: Vec<HeldFile>) -> std::io::Result<bool> {
+    let mut written = false;
+
+    for held in released {
+        match held.pending {
+            Some(PendingSave {
+                contents,
+                backup: true,
+            }) => write_file_with_backup(&held.path, &contents)?,
+            Some(PendingSave {
+                contents,
+                backup: false,
+            }) => write_file(&held.path, &contents)?,
+            None => continue,
+        }
+
+        written = true;
+    }
+
+    Ok(written)
+}
+
+//true when the file is held, contents are kept for later instead
+fn hold_save(path: &Path, contents: &str, backup: bool) -> bool {
+    let mut held_files = HELD_FILES.lock();
+
+    let Some(held) = held_files.iter_mut().find(|held| held.path == path) else {
+        return false;
+    };
+
+    held.pending = Some(PendingSave {
+        contents: contents.to_string(),
+        backup,
+    });
+
+    true
+}
+
+//broken file is moved next to the backups, it isn't listed as one, <name>.broken-<time>.ron
+pub fn keep_broken_file(path: &Path) -> std::io::Result<()> {
+    fs::create_dir_all(backup_dir(path))?;
+
+    fs::copy(
+        path,
+        backup_dir(path).join(format!(
+            "{}.broken-{}.ron",
+            file_stem(path),
+            Local::now().format(BACKUP_TIMESTAMP_FORMAT)
+        )),
+    )
+    .map(|_| ())
+}
+
+pub fn backup_name(backup: &Path) -> String {
+    backup
+        .file_name()
+        .and_then(OsStr::to_str)
+        .unwrap_or("záloha")
+        .to_string()
+}
+
+fn create_backup(path: &Path, contents: &str) -> std::io::Result<()> {
+    let backup_dir = backup_dir(path);
+    fs::create_dir_all(&backup_dir)?;
+
+    let backup_path = backup_dir.join(format!(
+        "{}-{}.ron",
+        file_stem(path),
+        Local::now().format(BACKUP_TIMESTAMP_FORMAT)
+    ));
+    fs::write(backup_path, contents)?;
+
+    list_backups(path)
+        .into_iter()
+        .skip(BACKUP_COUNT)
+        .try_for_each(fs::remove_file)
+}
+
+fn backup_dir(path: &Path) -> PathBuf {
+    path.parent()
+        .unwrap_or(Path::new("."))
+        .join(BACKUP_DIR_NAME)
+}
+
+fn file_stem(path: &Path) -> &str {
+    path.file_stem().and_then(OsStr::to_str).unwrap_or("data")
+}
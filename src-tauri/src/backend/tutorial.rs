@@ -0,0 +1,224 @@
+use std::fs;
+use std::path::PathBuf;
+
+use chrono::Local;
+use tauri::AppHandle;
+
+use crate::backend::config::Config;
+use crate::backend::mail_list_utils::Person;
+use crate::backend::mail_sender::{MailSender, MailSenderError};
+use crate::backend::storage::tutorial_dir_path;
+
+//---------------------------
+
+static SANDBOX_FILE_FORMAT: &str = "%Y%m%d-%H%M%S";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Tour {
+    Main,
+    Settings,
+}
+
+impl Tour {
+    pub fn parse(text: &str) -> Option<Tour> {
+        match text {
+            "main" => Some(Tour::Main),
+            "settings" => Some(Tour::Settings),
+            _ => None,
+        }
+    }
+
+    fn steps(self) -> &'static [TutorialStep] {
+        match self {
+            Tour::Main => MAIN_STEPS,
+            Tour::Settings => SETTINGS_STEPS,
+        }
+    }
+}
+
+//what the user has to do on the screen before the tutorial moves on
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TutorialAction {
+    PickPerson,
+    OpenOther,
+    CloseOther,
+    PickFile,
+    PickSender,
+    Send,
+    EditContact,
+    OpenConfig,
+    OpenApprovals,
+    SaveSettings,
+}
+
+pub struct TutorialStep {
+    pub target: Option<&'static str>, //CSS selector of the highlighted element
+    pub text: &'static str,
+    pub waits_for: Option<TutorialAction>, //None - moved on by "další"
+}
+
+static MAIN_STEPS: &[TutorialStep] = &[
+    TutorialStep {
+        target: None,
+        text: "Průvodce vás provede odesláním E-mailu. Nic se skutečně neodešle - E-mail se jen uloží do zkušební složky.",
+        waits_for: None,
+    },
+    TutorialStep {
+        target: Some("#category-sections"),
+        text: "Vyberte příjemce kliknutím na jeho jméno. Lze ho i vyhledat v poli nad výběrem.",
+        waits_for: Some(TutorialAction::PickPerson),
+    },
+    TutorialStep {
+        target: Some("#other-mails-button"),
+        text: "Adresy, které nejsou v kontaktech, se zadávají v \"ostatní...\". Klikněte na něj.",
+        waits_for: Some(TutorialAction::OpenOther),
+    },
+    TutorialStep {
+        target: Some("#overlay-other .overlay-window"),
+        text: "Tlačítkem \"přidat další E-mail\" přidáte pole pro adresu. Teď okno zavřete křížkem.",
+        waits_for: Some(TutorialAction::CloseOther),
+    },
+    TutorialStep {
+        target: Some(".file-picker"),
+        text: "Vyberte soubor, který chcete odeslat.",
+        waits_for: Some(TutorialAction::PickFile),
+    },
+    TutorialStep {
+        target: Some("#sender-button"),
+        text: "Klikněte na \"kdo odesílá?\" a vyberte své jméno, nebo zadejte PIN.",
+        waits_for: Some(TutorialAction::PickSender),
+    },
+    TutorialStep {
+        target: Some(".truck"),
+        text: "Klikněte na náklaďák. V průvodci se E-mail neodešle, uloží se jen do zkušební složky.",
+        waits_for: Some(TutorialAction::Send),
+    },
+    TutorialStep {
+        target: None,
+        text: "Hotovo! Takto se odesílá každý E-mail. Podrobnosti najdete v \"návod k použití\".",
+        waits_for: None,
+    },
+];
+
+static SETTINGS_STEPS: &[TutorialStep] = &[
+    TutorialStep {
+        target: Some("#settings-categories"),
+        text: "Vlevo jsou kategorie a kontakty. Klikněte na jméno kontaktu pro jeho úpravu.",
+        waits_for: Some(TutorialAction::EditContact),
+    },
+    TutorialStep {
+        target: Some("#bottom-bar"),
+        text: "Dole se upravuje jméno, E-maily a další údaje vybraného kontaktu.",
+        waits_for: None,
+    },
+    TutorialStep {
+        target: Some("#settings-distribution-lists"),
+        text: "Vpravo jsou distribuční seznamy - jedním kliknutím vyberou více příjemců najednou.",
+        waits_for: None,
+    },
+    TutorialStep {
+        target: Some("button[hx-post=\"command:open_settings_config\"]"),
+        text: "V configu je nastavení odesílání. Otevřete ho, prohlédněte si ho a zavřete.",
+        waits_for: Some(TutorialAction::OpenConfig),
+    },
+    TutorialStep {
+        target: Some("button[hx-post=\"command:open_approvals\"]"),
+        text: "Zde se schvalují E-maily čekající na vedoucího a je zde historie odeslaných. Otevřete ji.",
+        waits_for: Some(TutorialAction::OpenApprovals),
+    },
+    TutorialStep {
+        target: Some("button[hx-post=\"command:open_send_presets\"]"),
+        text: "Předvolby vyberou na hlavní obrazovce příjemce, předmět i text jedním kliknutím.",
+        waits_for: None,
+    },
+    TutorialStep {
+        target: Some("button[hx-post=\"command:open_sent_archive\"]"),
+        text: "V archivu jsou všechny odeslané E-maily, lze v nich hledat i podle textu v PDF.",
+        waits_for: None,
+    },
+    TutorialStep {
+        target: Some("button[hx-post=\"command:save_and_close_settings\"]"),
+        text: "Změny se uloží až tlačítkem \"uložit a zavřít\". Klikněte na něj.",
+        waits_for: Some(TutorialAction::SaveSettings),
+    },
+    TutorialStep {
+        target: None,
+        text: "Hotovo! Podrobnosti najdete v \"návod k použití\" v nastavení.",
+        waits_for: None,
+    },
+];
+
+//kept only in memory, every start begins from the first step
+#[derive(Debug)]
+pub struct Tutorial {
+    tour: Tour,
+    step: usize,
+    sandbox_file: Option<PathBuf>, //message "sent" during the tutorial
+}
+
+impl Tutorial {
+    pub fn new(tour: Tour) -> Tutorial {
+        Tutorial {
+            tour,
+            step: 0,
+            sandbox_file: None,
+        }
+    }
+
+    pub fn step(&self) -> &'static TutorialStep {
+        &self.tour.steps()[self.step]
+    }
+
+    //number of the step from 1 and count of steps
+    pub fn position(&self) -> (usize, usize) {
+        (self.step + 1, self.tour.steps().len())
+    }
+
+    pub fn is_last(&self) -> bool {
+        self.step + 1 == self.tour.steps().len()
+    }
+
+    //returns whether the tutorial moved on, other actions are ignored
+    pub fn act(&mut self, action: TutorialAction) -> bool {
+        let expected = self.step().waits_for == Some(action);
+
+        if expected {
+            self.next();
+        }
+
+        expected
+    }
+
+    pub fn next(&mut self) {
+        if !self.is_last() {
+            self.step += 1;
+        }
+    }
+
+    pub fn sandbox_file(&self) -> Option<&PathBuf> {
+        self.sandbox_file.as_ref()
+    }
+
+    pub fn set_sandbox_file(&mut self, path: PathBuf) {
+        self.sandbox_file = Some(path);
+    }
+}
+
+//message is built the same way as for sending, but it is only written into the sandbox
+pub fn sandbox_send(
+    app: &AppHandle,
+    mail: &MailSender,
+    other_people: Vec<Person>,
+    config: &Config,
+) -> Result<PathBuf, MailSenderError> {
+    let message = mail.build_message(other_people, config, app.clone())?;
+
+    let dir = tutorial_dir_path(app);
+    let path = dir.join(format!("{}.eml", Local::now().format(SANDBOX_FILE_FORMAT)));
+
+    fs::create_dir_all(&dir)
+        .and_then(|_| fs::write(&path, message.formatted()))
+        .map_err(|_| MailSenderError::InvalidFilePath)?;
+
+    Ok(path)
+}
@@ -2,7 +2,9 @@ use maud::{html, Markup};
 use tauri::Manager;
 
 use crate::backend::error_handling::error_id_parse;
-use crate::backend::mail_list_utils;
+use crate::backend::integrity::refresh_integrity_issues;
+use crate::backend::tutorial::TutorialAction;
+use crate::handlers::tutorial::tutorial_act;
 use crate::AppState;
 use crate::MailList;
 
@@ -109,6 +111,8 @@ pub fn open_settings(app: tauri::AppHandle) -> String {
 
     app_state.mail.lock().clear();
     app_state.other_mail_list.lock().clear();
+    app_state.recipient_search.lock().clear();
+    *app_state.settings_current_person_id.lock() = None;
 
     let markup: Markup = html! {
             div.top-bar{
@@ -120,6 +124,30 @@ pub fn open_settings(app: tauri::AppHandle) -> String {
                     hx-swap="outerHTML"
                     {("config")}
                     button.top-bar-button
+                    hx-post="command:open_contact_exchange"
+                    hx-trigger="click"
+                    hx-target="#contact-exchange-placeholder"
+                    hx-swap="outerHTML"
+                    {("import / export kontaktů")}
+                    button.top-bar-button
+                    hx-post="command:open_approvals"
+                    hx-trigger="click"
+                    hx-target="#approvals-placeholder"
+                    hx-swap="outerHTML"
+                    {("ke schválení")}
+                    button.top-bar-button
+                    hx-post="command:open_send_presets"
+                    hx-trigger="click"
+                    hx-target="#send-presets-placeholder"
+                    hx-swap="outerHTML"
+                    {("předvolby")}
+                    button.top-bar-button
+                    hx-post="command:open_sent_archive"
+                    hx-trigger="click"
+                    hx-target="#sent-archive-placeholder"
+                    hx-swap="outerHTML"
+                    {("archiv")}
+                    button.top-bar-button
                     hx-post="command:open_feedback"
                     hx-trigger="click"
                     hx-target="#feedback-placeholder"
@@ -131,6 +159,13 @@ pub fn open_settings(app: tauri::AppHandle) -> String {
                     hx-target="#settings-manual-placeholder"
                     hx-swap="outerHTML"
                     {("návod k použití")}
+                    button.top-bar-button
+                    hx-post="command:start_tutorial"
+                    hx-trigger="click"
+                    hx-target="#tutorial"
+                    hx-swap="innerHTML"
+                    hx-vals=r#""tour": "settings""#
+                    {("průvodce")}
                 }
                 img.man-logo
                 src="src/assets/man_logo_batch.svg"
@@ -139,43 +174,35 @@ pub fn open_settings(app: tauri::AppHandle) -> String {
 
             }
             div.center-buttons{
-                div.mechanic-buttons
+                div.category-sections #settings-categories
                 hx-trigger="load delay:1ms"
                 hx-swap="innerHTML"
-                hx-post="command:load_settings_mechanics"
+                hx-post="command:load_settings_categories"
                 {}
-                div.right-buttons
+                div.right-buttons #settings-distribution-lists
                 hx-trigger="load delay:1ms"
                 hx-swap="innerHTML"
-                hx-post="command:load_settings_technics"
+                hx-post="command:load_settings_distribution_lists"
                 {}
             }
+            div #distribution-list-placeholder{}
+            div #contact-exchange-placeholder{}
+            div #approvals-placeholder{}
+            div #send-presets-placeholder{}
+            div #sent-archive-placeholder{}
+            div #contact-editor-placeholder{}
             div #feedback-placeholder{}
             div #settings-manual-placeholder{}
             div #settings-config-placeholder{}
             div #valid-mail-placeholder{}
             div #discard-overlay-placeholder {}
-            div.bottom-bar #bottom-bar{
-            div.bottom-part-settings-names{
-                h1.settings-bottom-text{("Vyberte prosím osobu pro úpravu údajů")}
-            }
-            div.bottom-part-settings-names{
-            }
-            div.bottom-part-settings-buttons{
-                button.settings-bottom-button.save
-                hx-post="command:save_and_close_settings"
-                hx-trigger="click"
-                hx-target="#valid-mail-placeholder"
-                hx-swap="outerHTML"
-                {("uložit a zavřít")}
-                button.settings-bottom-button.close
-                hx-post="command:open_discard_overlay"
-                hx-trigger="click"
-                hx-target="#discard-overlay-placeholder"
-                hx-swap="outerHTML"
-                {("zavřít bez uložení")}
-            }
-        }
+            div #tutorial
+            hx-post="command:load_tutorial"
+            hx-trigger="load delay:1ms"
+            hx-swap="innerHTML"
+            tauri-listen="tutorial_step"
+            {}
+            (default_settings_bottom_bar())
     };
 
     markup.into_string()
@@ -224,6 +251,7 @@ pub fn discard_and_close_settings(app: tauri::AppHandle) -> String {
     let app_state = app.state::<AppState>();
 
     *app_state.mail_list.lock() = MailList::load_list(app.clone());
+    refresh_integrity_issues(&app);
 
     close_settings()
 }
@@ -237,6 +265,9 @@ pub fn save_and_close_settings(app: tauri::AppHandle) -> String {
     if let Err(invalid_mails) = mail_list_save {
         wrong_mail_warning(invalid_mails)
     } else {
+        refresh_integrity_issues(&app);
+        tutorial_act(&app, TutorialAction::SaveSettings);
+
         html! {
             div
             hx-trigger="load delay:1ms"
@@ -304,6 +335,13 @@ pub fn close_settings() -> String {
                     hx-target="#manual-placeholder"
                     hx-swap="outerHTML"
                     {("návod k použití")}
+                    button.top-bar-button
+                    hx-post="command:start_tutorial"
+                    hx-trigger="click"
+                    hx-target="#tutorial"
+                    hx-swap="innerHTML"
+                    hx-vals=r#""tour": "main""#
+                    {("průvodce")}
                 }
                 img.man-logo
                 src="src/assets/man_logo_batch.svg"
@@ -311,22 +349,51 @@ pub fn close_settings() -> String {
                 {}
             }
             div.center-buttons{
-                div.mechanic-buttons
-                hx-trigger="load delay:1ms"
-                hx-swap="innerHTML"
-                hx-post="command:load_mechanics"
-                {}
+                div.recipient-column{
+                    div #preset-buttons
+                    hx-post="command:load_preset_buttons"
+                    hx-trigger="load delay:1ms"
+                    hx-swap="outerHTML"
+                    {}
+                    input.recipient-search #recipient-search
+                    type="search"
+                    name="text"
+                    placeholder="hledat jméno, E-mail nebo funkci..."
+                    autocomplete="off"
+                    hx-post="command:search_recipients"
+                    hx-trigger="input changed delay:150ms, search"
+                    hx-target="#category-sections"
+                    hx-swap="innerHTML"
+                    {}
+                    div.category-sections #category-sections
+                    hx-trigger="load delay:1ms"
+                    hx-swap="innerHTML"
+                    hx-post="command:load_categories"
+                    {}
+                }
                 div.right-buttons
                 hx-trigger="load delay:1ms"
                 hx-swap="innerHTML"
-                hx-post="command:load_technics"
+                hx-post="command:load_other_buttons"
                 {}
             }
             div #overlay-other-placeholder{}
+            div #address-picker-placeholder{}
             div #feedback-placeholder{}
             div #manual-placeholder{}
             div #settings-placeholder{}
             div #send-error-placeholder{}
+            div #integrity-banner-placeholder
+            hx-trigger="load delay:1ms"
+            hx-swap="outerHTML"
+            hx-post="command:load_integrity_banner"
+            {}
+            div #tutorial
+            hx-post="command:load_tutorial"
+            hx-trigger="load delay:1ms"
+            hx-swap="innerHTML"
+            tauri-listen="tutorial_step"
+            {}
 
             /*
                     hx-trigger="click"
@@ -361,6 +428,36 @@ pub fn close_settings() -> String {
                     { ("výběr souborů") }
                 } */
                 //      <div tauri-listen="file_picker_text">výběr souborů</div>
+                div.schedule-buttons{
+                    button.schedule-button #sender-button
+                    hx-post="command:load_sender_button"
+                    hx-trigger="load delay:1ms"
+                    hx-swap="outerHTML"
+                    {("kdo odesílá?")}
+                    div #profile-select
+                    hx-post="command:load_profile_select"
+                    hx-trigger="load delay:1ms"
+                    hx-swap="outerHTML"
+                    {}
+                    button.schedule-button
+                    hx-post="command:open_schedule_send"
+                    hx-trigger="click"
+                    hx-target="#send-error-placeholder"
+                    hx-swap="outerHTML"
+                    {("odeslat později")}
+                    button.schedule-button
+                    hx-post="command:open_scheduled_sends"
+                    hx-trigger="click"
+                    hx-target="#send-error-placeholder"
+                    hx-swap="outerHTML"
+                    {("naplánované")}
+                    button.schedule-button
+                    hx-post="command:open_send_history"
+                    hx-trigger="click"
+                    hx-target="#send-error-placeholder"
+                    hx-swap="outerHTML"
+                    {("odeslané")}
+                }
                 input.truck
                 type="image"
                 src="src/assets/send_truck.svg"
@@ -374,70 +471,301 @@ pub fn close_settings() -> String {
     markup.into_string()
 }
 
-#[tauri::command]
-pub fn load_settings_mechanics(app: tauri::AppHandle) -> String {
-    let app_state = app.state::<AppState>();
+fn default_settings_bottom_bar() -> Markup {
+    html! {
+        div.bottom-bar #bottom-bar{
+            div.bottom-part-settings-names{
+                h1.settings-bottom-text{("Vyberte prosím osobu nebo kategorii pro úpravu")}
+            }
+            div.bottom-part-settings-names{
+            }
+            (settings_bottom_buttons())
+        }
+    }
+}
 
-    let mail_list = app_state.mail_list.lock();
+fn settings_bottom_buttons() -> Markup {
+    html! {
+        div.bottom-part-settings-buttons{
+            button.settings-bottom-button.save
+            hx-post="command:save_and_close_settings"
+            hx-trigger="click"
+            hx-target="#valid-mail-placeholder"
+            hx-swap="outerHTML"
+            {("uložit a zavřít")}
+            button.settings-bottom-button.close
+            hx-post="command:open_discard_overlay"
+            hx-trigger="click"
+            hx-target="#discard-overlay-placeholder"
+            hx-swap="outerHTML"
+            {("zavřít bez uložení")}
+        }
+    }
+}
 
-    let markup: Markup = html! {
-        @for i in 0..24 {
-            @if let Some(mechanic) = mail_list.load_person(i){
-                button.middle-button
-                id=(format!("id-{}", i))
+//currently edited person can't be clicked again, inactive people are greyed out
+fn settings_person_button(id: usize, name: &str, clicked: bool, active: bool) -> Markup {
+    html! {
+        @if clicked {
+            button.middle-button.clicked.inactive-person[!active]
+            id=(format!("id-{}", id))
+            {(name)}
+        }
+        @else {
+            button.middle-button.inactive-person[!active]
+            id=(format!("id-{}", id))
+            hx-trigger="click"
+            hx-post="command:edit_person"
+            hx-swap="outerHTML"
+            hx-target="#bottom-bar"
+            hx-vals={(format!(r#""id": {id}"#))}
+            {(name)}
+        }
+    }
+}
+
+fn render_settings_categories(mail_list: &MailList, current_person_id: Option<usize>) -> Markup {
+    html! {
+        @for category in mail_list.categories() {
+            div.category-section{
+                button.category-title-button
+                id=(format!("category-{}", category.id))
                 hx-trigger="click"
-                hx-post="command:edit_person"
+                hx-post="command:edit_category"
                 hx-swap="outerHTML"
                 hx-target="#bottom-bar"
-                hx-vals={(format!(r#""id": {i}"#))}
-                {(mechanic.name)}
+                hx-vals={(format!(r#""id": {}"#, category.id))}
+                {(category.name)}
+                div.category-buttons{
+                    @for contact in &category.contacts {
+                        (settings_person_button(
+                            contact.id,
+                            &contact.name,
+                            current_person_id == Some(contact.id),
+                            contact.active,
+                        ))
+                    }
+                    button.middle-button.add-person-button
+                    hx-trigger="click"
+                    hx-post="command:add_contact"
+                    hx-swap="innerHTML"
+                    hx-target="#settings-categories"
+                    hx-vals={(format!(r#""id": {}"#, category.id))}
+                    {("+ přidat osobu")}
+                }
             }
-            @else{
-                button.middle-button
-                id=(format!("id-{}", i))
-                hx-trigger="click"
-                hx-post="command:edit_person"
-                hx-swap="outerHTML"
-                hx-target="#bottom-bar"
-                hx-vals={(format!(r#""id": {i}"#))}
+        }
+        button.middle-button.add-category-button
+        hx-trigger="click"
+        hx-post="command:add_category"
+        hx-swap="innerHTML"
+        hx-target="#settings-categories"
+        {("+ přidat kategorii")}
+    }
+}
+
+#[tauri::command]
+pub fn load_settings_categories(app: tauri::AppHandle) -> String {
+    let app_state = app.state::<AppState>();
+
+    let current_person_id = *app_state.settings_current_person_id.lock();
+
+    let markup = render_settings_categories(&app_state.mail_list.lock(), current_person_id);
+
+    markup.into_string()
+}
+
+#[tauri::command]
+pub fn reset_settings_bottom_bar() -> String {
+    default_settings_bottom_bar().into_string()
+}
+
+#[tauri::command]
+pub fn add_category(app: tauri::AppHandle) -> String {
+    let app_state = app.state::<AppState>();
+
+    let mut mail_list = app_state.mail_list.lock();
+
+    let id = mail_list.add_category("nová kategorie".to_string());
+
+    let markup: Markup = html! {
+        (render_settings_categories(&mail_list, *app_state.settings_current_person_id.lock()))
+        div
+        hx-trigger="load delay:1ms"
+        hx-swap="outerHTML"
+        hx-target="#bottom-bar"
+        hx-vals={(format!(r#""id": {id}"#))}
+        hx-post="command:edit_category"
+        {}
+    };
+
+    markup.into_string()
+}
+
+#[tauri::command]
+pub fn edit_category(id: String, app: tauri::AppHandle) -> String {
+    let id: usize = id
+        .parse()
+        .unwrap_or_else(|_| error_id_parse(app.clone(), id));
+
+    let app_state = app.state::<AppState>();
+
+    let category_name = app_state
+        .mail_list
+        .lock()
+        .load_category(id)
+        .map(|category| category.name.clone())
+        .unwrap_or_default();
+
+    let markup: Markup = html! {
+        div.bottom-bar #bottom-bar {
+            div.bottom-part-settings-names{
+                h1.settings-bottom-text{("název kategorie")}
+                input.settings-bottom-input
+                type="text"
+                hx-post="command:edit_category_name"
+                name="text"
+                hx-trigger="change"
+                hx-swap="innerHTML"
+                hx-target="#settings-categories"
+                hx-vals={(format!(r#""id": {id}"#))}
+                value=(category_name)
                 {}
             }
+            div.bottom-part-settings-buttons{
+                button.settings-bottom-button
+                hx-post="command:move_category_up"
+                hx-trigger="click"
+                hx-swap="innerHTML"
+                hx-target="#settings-categories"
+                hx-vals={(format!(r#""id": {id}"#))}
+                {("posunout nahoru")}
+                button.settings-bottom-button
+                hx-post="command:move_category_down"
+                hx-trigger="click"
+                hx-swap="innerHTML"
+                hx-target="#settings-categories"
+                hx-vals={(format!(r#""id": {id}"#))}
+                {("posunout dolů")}
+                button.settings-bottom-button.close
+                hx-post="command:remove_category"
+                hx-trigger="click"
+                hx-swap="innerHTML"
+                hx-target="#settings-categories"
+                hx-vals={(format!(r#""id": {id}"#))}
+                {("smazat kategorii i s osobami")}
+            }
+            (settings_bottom_buttons())
+        }
+        @if let Some(id) = *app_state.settings_current_person_id.lock() {
+            div
+            hx-trigger="load delay:1ms"
+            hx-swap="outerHTML"
+            hx-target=(format!("#id-{}", id))
+            hx-vals={(format!(r#""id": {id}"#))}
+            hx-post="command:unmark_person"
+            {}
         }
     };
 
+    *app_state.settings_current_person_id.lock() = None;
+
     markup.into_string()
 }
 
 #[tauri::command]
-pub fn load_settings_technics(app: tauri::AppHandle) -> String {
+pub fn edit_category_name(app: tauri::AppHandle, id: String, text: String) -> String {
+    let id: usize = id
+        .parse()
+        .unwrap_or_else(|_| error_id_parse(app.clone(), id));
+
     let app_state = app.state::<AppState>();
 
-    let mail_list = app_state.mail_list.lock();
+    let mut mail_list = app_state.mail_list.lock();
+
+    mail_list.save_category_name(id, text);
+
+    let markup =
+        render_settings_categories(&mail_list, *app_state.settings_current_person_id.lock());
+
+    markup.into_string()
+}
+
+#[tauri::command]
+pub fn move_category_up(app: tauri::AppHandle, id: String) -> String {
+    move_category(app, id, -1)
+}
+
+#[tauri::command]
+pub fn move_category_down(app: tauri::AppHandle, id: String) -> String {
+    move_category(app, id, 1)
+}
+
+fn move_category(app: tauri::AppHandle, id: String, offset: isize) -> String {
+    let id: usize = id
+        .parse()
+        .unwrap_or_else(|_| error_id_parse(app.clone(), id));
+
+    let app_state = app.state::<AppState>();
+
+    let mut mail_list = app_state.mail_list.lock();
+
+    mail_list.move_category(id, offset);
+
+    let markup =
+        render_settings_categories(&mail_list, *app_state.settings_current_person_id.lock());
+
+    markup.into_string()
+}
+
+#[tauri::command]
+pub fn remove_category(app: tauri::AppHandle, id: String) -> String {
+    let id: usize = id
+        .parse()
+        .unwrap_or_else(|_| error_id_parse(app.clone(), id));
+
+    let app_state = app.state::<AppState>();
+
+    let mut mail_list = app_state.mail_list.lock();
+
+    mail_list.remove_category(id);
 
     let markup: Markup = html! {
-    @for i in 24..29 {
-        @if let Some(technic) = mail_list.load_person(i){
-            button.middle-button
-            id=(format!("id-{}", i))
-            hx-trigger="click"
-            hx-post="command:edit_person"
+        (render_settings_categories(&mail_list, *app_state.settings_current_person_id.lock()))
+        div
+        hx-trigger="load delay:1ms"
+        hx-swap="outerHTML"
+        hx-target="#bottom-bar"
+        hx-post="command:reset_settings_bottom_bar"
+        {}
+    };
+
+    markup.into_string()
+}
+
+#[tauri::command]
+pub fn add_contact(app: tauri::AppHandle, id: String) -> String {
+    let category_id: usize = id
+        .parse()
+        .unwrap_or_else(|_| error_id_parse(app.clone(), id));
+
+    let app_state = app.state::<AppState>();
+
+    let mut mail_list = app_state.mail_list.lock();
+
+    let contact_id = mail_list.add_contact(category_id);
+
+    let markup: Markup = html! {
+        (render_settings_categories(&mail_list, *app_state.settings_current_person_id.lock()))
+        @if let Some(contact_id) = contact_id {
+            div
+            hx-trigger="load delay:1ms"
             hx-swap="outerHTML"
             hx-target="#bottom-bar"
-            hx-vals={(format!(r#""id": {i}"#))}
-            {(technic.name)}
-        }
-        @else{
-            button.middle-button
-            id=(format!("id-{}", i))
-            hx-trigger="click"
+            hx-vals={(format!(r#""id": {contact_id}"#))}
             hx-post="command:edit_person"
-            hx-swap="outerHTML"
-            hx-target="#bottom-bar"
-            hx-vals={(format!(r#""id": {i}"#))}
             {}
         }
-    }
-    button.middle-button.placeholder{}
     };
 
     markup.into_string()
@@ -451,16 +779,13 @@ pub fn edit_person(id: String, app: tauri::AppHandle) -> String {
 
     let app_state = app.state::<AppState>();
 
-    let mail_list = app_state.mail_list.lock();
-
-    let person = match mail_list.load_person(id) {
-        Some(person) => person,
-        None => mail_list_utils::Person {
-            name: "".to_string(),
-            mail: "".to_string(),
-        },
+    let (name, mail) = match app_state.mail_list.lock().load_contact(id) {
+        Some(contact) => (contact.name.clone(), contact.mail().to_string()),
+        None => ("".to_string(), "".to_string()),
     };
 
+    tutorial_act(&app, TutorialAction::EditContact);
+
     let markup: Markup = html! {
         div.bottom-bar #bottom-bar {
             div.bottom-part-settings-names{
@@ -471,35 +796,30 @@ pub fn edit_person(id: String, app: tauri::AppHandle) -> String {
                 name="text"
                 hx-trigger="change"
                 hx-vals={(format!(r#""id": {id}"#))}
-                value=(person.name)
+                value=(name)
                 {}
             }
             div.bottom-part-settings-names{
-                h1.settings-bottom-text{("e-mail")}
+                h1.settings-bottom-text{("primární e-mail")}
                 input.settings-bottom-input
                 type="text"
                 hx-post="command:edit_person_mail"
                 name="text"
                 hx-trigger="change"
                 hx-vals={(format!(r#""id": {id}"#))}
-                value=(person.mail)
+                value=(mail)
                 {}
             }
             div.bottom-part-settings-buttons{
-                button.settings-bottom-button.save
-                hx-post="command:save_and_close_settings"
-                hx-trigger="click"
-                hx-target="#valid-mail-placeholder"
-                hx-swap="outerHTML"
-                {("uložit a zavřít")}
-                button.settings-bottom-button.close
-                hx-post="command:open_discard_overlay"
+                button.settings-bottom-button
+                hx-post="command:open_contact_editor"
                 hx-trigger="click"
-                hx-target="#discard-overlay-placeholder"
+                hx-target="#contact-editor-placeholder"
                 hx-swap="outerHTML"
-                {("zavřít bez uložení")}
-
+                hx-vals={(format!(r#""id": {id}"#))}
+                {("celý kontakt")}
             }
+            (settings_bottom_buttons())
         }
         div
         hx-trigger="load delay:1ms"
@@ -532,23 +852,14 @@ pub fn mark_person(id: String, app: tauri::AppHandle) -> String {
 
     let app_state = app.state::<AppState>();
 
-    let mail_list = app_state.mail_list.lock();
+    let (name, active) = app_state
+        .mail_list
+        .lock()
+        .load_contact(id)
+        .map(|contact| (contact.name, contact.active))
+        .unwrap_or_default();
 
-    let person = match mail_list.load_person(id) {
-        Some(person) => person,
-        None => mail_list_utils::Person {
-            name: "".to_string(),
-            mail: "".to_string(),
-        },
-    };
-
-    let markup: Markup = html! {
-        button.middle-button.clicked
-        id=(format!("id-{}", id))
-        {(person.name)}
-    };
-
-    markup.into_string()
+    settings_person_button(id, &name, true, active).into_string()
 }
 
 #[tauri::command]
@@ -559,28 +870,14 @@ pub fn unmark_person(id: String, app: tauri::AppHandle) -> String {
 
     let app_state = app.state::<AppState>();
 
-    let mail_list = app_state.mail_list.lock();
-
-    let person = match mail_list.load_person(id) {
-        Some(person) => person,
-        None => mail_list_utils::Person {
-            name: "".to_string(),
-            mail: "".to_string(),
-        },
-    };
+    let (name, active) = app_state
+        .mail_list
+        .lock()
+        .load_contact(id)
+        .map(|contact| (contact.name, contact.active))
+        .unwrap_or_default();
 
-    let markup: Markup = html! {
-        button.middle-button
-        id=(format!("id-{}", id))
-        hx-trigger="click"
-        hx-post="command:edit_person"
-        hx-swap="outerHTML"
-        hx-target="#bottom-bar"
-        hx-vals={(format!(r#""id": {id}"#))}
-        {(person.name)}
-    };
-
-    markup.into_string()
+    settings_person_button(id, &name, false, active).into_string()
 }
 
 #[tauri::command]
@@ -0,0 +1,401 @@
+use maud::{html, Markup};
+use tauri::Manager;
+use tauri_plugin_opener::OpenerExt;
+
+use crate::backend::approval_queue::{ApprovalQueue, ApprovalRequest};
+use crate::backend::config::Config;
+use crate::backend::error_handling::{error_id_parse, error_sending_approved_mail};
+use crate::backend::mail_list_utils::Person;
+use crate::backend::mail_sender::{AttachmentInfo, MailSender};
+use crate::backend::send_history::{record_history, sha256_hex, HistoryEntry, HistoryOutcome};
+use crate::backend::tutorial::TutorialAction;
+use crate::handlers::mail_send::format_size;
+use crate::handlers::send_history::render_send_history;
+use crate::handlers::tutorial::{tutorial_act, tutorial_running};
+use crate::AppState;
+
+//---------------------------
+
+//sending_allowed - false while the tutorial runs, its sends go only to the sandbox
+fn render_approval_requests(
+    approval_queue: &ApprovalQueue,
+    config: &Config,
+    missing_name: bool,
+    sending_allowed: bool,
+) -> Markup {
+    html! {
+        @if !sending_allowed {
+            p.send-summary-row{("během průvodce se nic neodesílá, ukončete ho pro odeslání")}
+        }
+        @if missing_name {
+            p.other-mail-error{("Před schválením nebo zamítnutím vyplňte prosím své jméno.")}
+        }
+        @if approval_queue.requests().is_empty() {
+            p.send-summary-row{("žádné E-maily nečekají na schválení")}
+        }
+        @for request in approval_queue.requests() {
+            @let (request_config, mail) = request_message(request, config);
+            div.scheduled-send{
+                div.other-mail-button-row{
+                    p.scheduled-send-state{(format!("odesláno ke schválení {}", request.requested_at))}
+                    button.add-button
+                    hx-post="command:approve_request"
+                    hx-trigger="click"
+                    hx-target="#approval-requests"
+                    hx-swap="innerHTML"
+                    hx-vals={(format!(r#""id": {}"#, request.id))}
+                    disabled[!sending_allowed]
+                    {("schválit a odeslat")}
+                    button.remove-button
+                    hx-post="command:reject_request"
+                    hx-trigger="click"
+                    hx-target="#approval-requests"
+                    hx-swap="innerHTML"
+                    hx-vals={(format!(r#""id": {}"#, request.id))}
+                    {("zamítnout")}
+                }
+                @if let Some(sender) = &request.sender {
+                    p.send-summary-row{(format!("odesílá: {} <{}>", sender.name, sender.mail))}
+                }
+                p.send-summary-row{(format!("příjemci: {}", request.mails().join(", ")))}
+                p.send-summary-row{(format!("předmět: {}", request_config.title()))}
+                p.send-summary-row.send-summary-body{
+                    (mail.body_text(&request_config).unwrap_or_else(|| "bez textu - pouze přílohy".to_string()))
+                }
+                @for (index, path) in request.files.iter().enumerate() {
+                    @let attachment = AttachmentInfo::load(path);
+                    div.other-mail-button-row{
+                        p.send-summary-row{
+                            (format!("{} - {}", attachment.name, format_size(attachment.size)))
+                            @if let Some(pages) = attachment.pages {
+                                (format!(", stran: {pages}"))
+                            }
+                        }
+                        button.add-button
+                        hx-post="command:open_approval_attachment"
+                        hx-trigger="click"
+                        hx-swap="none"
+                        hx-vals={(format!(r#""id": {}, "index": {}"#, request.id, index))}
+                        {("otevřít")}
+                    }
+                }
+                input.config-row-input-field
+                type="text"
+                name="text"
+                placeholder="poznámka, např. důvod zamítnutí"
+                hx-post="command:save_approval_note"
+                hx-trigger="change"
+                hx-swap="none"
+                hx-vals={(format!(r#""id": {}"#, request.id))}
+                value=(request.note)
+                {}
+            }
+        }
+    }
+}
+
+//subject and text are previewed from the same message that is sent once approved
+fn request_message(request: &ApprovalRequest, config: &Config) -> (Config, MailSender) {
+    let mechanic = request.sender.as_ref().map(|sender| sender.name.as_str());
+
+    let mut config = config.for_send(request.profile, mechanic);
+    if let Some(template) = &request.template {
+        config.override_title(&template.subject, mechanic);
+    }
+
+    let mut mail = MailSender::with_files(request.files.clone());
+    mail.set_sender(request.sender.clone());
+    mail.set_template(request.template.clone());
+    mail.set_resent_from(request.resent_from);
+
+    (config, mail)
+}
+
+#[tauri::command]
+pub fn open_approvals(app: tauri::AppHandle) -> String {
+    let app_state = app.state::<AppState>();
+
+    tutorial_act(&app, TutorialAction::OpenApprovals);
+
+    let config = app_state.config.lock().clone();
+
+    let markup: Markup = html! {
+        div #overlay-approvals .overlay .most-top
+        {
+            div.overlay-window
+            {
+                button.close-button
+                hx-post="command:close_approvals"
+                hx-trigger="click"
+                hx-target="#overlay-approvals"
+                hx-swap="outerHTML"
+                {("X")}
+                h1.overlay-title{("E-maily ke schválení")}
+                div.config-row{
+                    h1.config-row-title
+                    {("schvaluje:")}
+                    input.config-row-input-field
+                    type="text"
+                    name="text"
+                    hx-post="command:save_approver_name"
+                    hx-trigger="change"
+                    hx-swap="none"
+                    value=(app_state.approver_name.lock().clone())
+                    {}
+                }
+                div.send-summary #approval-requests{
+                    (render_approval_requests(&app_state.approval_queue.lock(), &config, false, !tutorial_running(&app)))
+                }
+                h2.send-summary-title{("historie odeslaných")}
+                div.other-mail-button-row{
+                    button.add-button
+                    hx-post="command:verify_send_history"
+                    hx-trigger="click"
+                    hx-target="#history-verification"
+                    hx-swap="innerHTML"
+                    {("ověřit historii")}
+                }
+                div.send-summary #history-verification {}
+                div.send-summary{
+                    (render_send_history(&app_state.send_history.lock(), false))
+                }
+            }
+        }
+    };
+
+    markup.into_string()
+}
+
+#[tauri::command]
+pub fn close_approvals() -> String {
+    let markup: Markup = html! {
+        div #approvals-placeholder {}
+    };
+
+    markup.into_string()
+}
+
+#[tauri::command]
+pub fn save_approver_name(app: tauri::AppHandle, text: String) {
+    let app_state = app.state::<AppState>();
+
+    *app_state.approver_name.lock() = text.trim().to_string();
+}
+
+#[tauri::command]
+pub fn save_approval_note(app: tauri::AppHandle, id: String, text: String) {
+    let id: usize = id
+        .parse()
+        .unwrap_or_else(|_| error_id_parse(app.clone(), id));
+
+    let app_state = app.state::<AppState>();
+
+    let mut approval_queue = app_state.approval_queue.lock();
+
+    approval_queue.save_note(id, text.trim().to_string());
+    approval_queue.save(app.clone());
+}
+
+//admin checks the report in the default PDF viewer before approving
+#[tauri::command]
+pub fn open_approval_attachment(app: tauri::AppHandle, id: String, index: String) {
+    let id: usize = id
+        .parse()
+        .unwrap_or_else(|_| error_id_parse(app.clone(), id));
+    let index: usize = index
+        .parse()
+        .unwrap_or_else(|_| error_id_parse(app.clone(), index));
+
+    let app_state = app.state::<AppState>();
+
+    let path = app_state
+        .approval_queue
+        .lock()
+        .requests()
+        .iter()
+        .find(|request| request.id == id)
+        .and_then(|request| request.files.get(index).cloned());
+
+    if let Some(path) = path {
+        let _ = app.opener().open_path(path.to_string_lossy(), None::<&str>);
+    }
+}
+
+//request leaves the queue while it is being sent, failed one is put back
+#[tauri::command]
+pub fn approve_request(app: tauri::AppHandle, id: String) -> String {
+    let id: usize = id
+        .parse()
+        .unwrap_or_else(|_| error_id_parse(app.clone(), id));
+
+    let app_state = app.state::<AppState>();
+
+    let approver_name = app_state.approver_name.lock().clone();
+
+    if approver_name.is_empty() || tutorial_running(&app) {
+        return render_approval_requests(
+            &app_state.approval_queue.lock(),
+            &app_state.config.lock(),
+            approver_name.is_empty(),
+            !tutorial_running(&app),
+        )
+        .into_string();
+    }
+
+    let request = {
+        let mut approval_queue = app_state.approval_queue.lock();
+        let request = approval_queue.take(id);
+        approval_queue.save(app.clone());
+        request
+    };
+
+    let Some(request) = request else {
+        return render_approval_requests(
+            &app_state.approval_queue.lock(),
+            &app_state.config.lock(),
+            false,
+            !tutorial_running(&app),
+        )
+        .into_string();
+    };
+
+    let (config, mut mail) = request_message(&request, &app_state.config.lock());
+    let subject = config.title().to_string();
+
+    let people: Vec<Person> = request
+        .recipients
+        .iter()
+        .chain(request.other_people.iter())
+        .cloned()
+        .collect();
+
+    let mail_result = mail.send(people, config, app.clone());
+
+    match mail_result {
+        Ok(message) => {
+            let mut send_stats = app_state.send_stats.lock();
+            send_stats.record(request.recipients.iter().map(|person| person.mail.as_str()));
+            send_stats.record_free_addresses(
+                request
+                    .other_people
+                    .iter()
+                    .map(|person| person.mail.as_str()),
+            );
+            send_stats.save(app.clone());
+            drop(send_stats);
+
+            record_history(
+                &app,
+                HistoryEntry {
+                    approved_by: Some(approver_name),
+                    sent_by: request.sender.as_ref().map(|sender| sender.name.clone()),
+                    note: request.note.clone(),
+                    resent_from: request.resent_from,
+                    message_hash: Some(sha256_hex(&message)),
+                    ..HistoryEntry::new(
+                        HistoryOutcome::Sent,
+                        request.mails(),
+                        subject,
+                        request.file_names(),
+                    )
+                },
+                &request.files,
+            );
+        }
+        Err(error) => {
+            let mut approval_queue = app_state.approval_queue.lock();
+            approval_queue.put_back(request);
+            approval_queue.save(app.clone());
+            drop(approval_queue);
+
+            error_sending_approved_mail(app.clone(), error);
+        }
+    }
+
+    let markup = render_approval_requests(
+        &app_state.approval_queue.lock(),
+        &app_state.config.lock(),
+        false,
+        !tutorial_running(&app),
+    );
+
+    markup.into_string()
+}
+
+#[tauri::command]
+pub fn reject_request(app: tauri::AppHandle, id: String) -> String {
+    let id: usize = id
+        .parse()
+        .unwrap_or_else(|_| error_id_parse(app.clone(), id));
+
+    let app_state = app.state::<AppState>();
+
+    let approver_name = app_state.approver_name.lock().clone();
+
+    if approver_name.is_empty() {
+        return render_approval_requests(
+            &app_state.approval_queue.lock(),
+            &app_state.config.lock(),
+            true,
+            !tutorial_running(&app),
+        )
+        .into_string();
+    }
+
+    let request = {
+        let mut approval_queue = app_state.approval_queue.lock();
+        let request = approval_queue.take(id);
+        approval_queue.save(app.clone());
+        request
+    };
+
+    if let Some(request) = request {
+        let (config, _) = request_message(&request, &app_state.config.lock());
+        let subject = config.title().to_string();
+
+        record_history(
+            &app,
+            HistoryEntry {
+                approved_by: Some(approver_name),
+                sent_by: request.sender.as_ref().map(|sender| sender.name.clone()),
+                note: request.note.clone(),
+                ..HistoryEntry::new(
+                    HistoryOutcome::Rejected,
+                    request.mails(),
+                    subject,
+                    request.file_names(),
+                )
+            },
+            &[],
+        );
+    }
+
+    let markup = render_approval_requests(
+        &app_state.approval_queue.lock(),
+        &app_state.config.lock(),
+        false,
+        !tutorial_running(&app),
+    );
+
+    markup.into_string()
+}
+
+//shown on the main screen instead of the truck animation
+#[tauri::command]
+pub fn open_approval_notice() -> String {
+    html! {
+        div .overlay .most-top #send-error-overlay{
+            div .overlay-error-window{
+                button.close-button
+                hx-post="command:close_send_error"
+                hx-trigger="click"
+                hx-target="#send-error-overlay"
+                hx-swap="outerHTML"
+                {("X")}
+                h1.overlay-error-title{("E-mail čeká na schválení vedoucím")}
+                h2.overlay-error-subtitle{("Odešle se, až ho vedoucí v nastavení schválí.")}
+            }
+        }
+    }
+    .into_string()
+}
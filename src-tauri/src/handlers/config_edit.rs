@@ -1,7 +1,11 @@
 use maud::{html, Markup};
 use tauri::Manager;
 
-use crate::backend::config::Config;
+use crate::backend::config::{ApprovalRule, Config, SendConfirmation};
+use crate::backend::error_handling::error_id_parse;
+use crate::backend::integrity::refresh_integrity_issues;
+use crate::backend::tutorial::TutorialAction;
+use crate::handlers::tutorial::tutorial_act;
 use crate::AppState;
 
 //---------------------------
@@ -10,6 +14,9 @@ use crate::AppState;
 pub fn open_settings_config(app: tauri::AppHandle) -> String {
     let app_state = app.state::<AppState>();
     let config = app_state.config.lock().clone();
+    let categories = category_names(&app_state);
+
+    tutorial_act(&app, TutorialAction::OpenConfig);
 
     let markup: Markup = html! {
         div #overlay-settings-config .overlay{
@@ -66,6 +73,17 @@ pub fn open_settings_config(app: tauri::AppHandle) -> String {
                         value=(config.title())
                         {}
                     }
+                    div.config-row{
+                        h1.config-row-title
+                        {("podpis:")}
+                        input.config-row-input-field
+                        type="text"
+                        hx-post="command:save_signature"
+                        hx-trigger="change"
+                        name="text"
+                        value=(config.signature())
+                        {}
+                    }
                     div.config-row{
                         h1.config-row-title
                         {("smtp transport:")}
@@ -121,6 +139,105 @@ pub fn open_settings_config(app: tauri::AppHandle) -> String {
                         value=(config.settings_password())
                         {}
                     }
+                    div.config-row{
+                        h1.config-row-title
+                        {("interní domény:")}
+                        input.config-row-input-field
+                        type="text"
+                        hx-post="command:save_internal_domains"
+                        hx-trigger="change"
+                        name="text"
+                        placeholder="prázdné - všechny adresy jsou externí"
+                        value=(config.internal_domains().join(", "))
+                        {}
+                    }
+                    div.config-row{
+                        h1.config-row-title
+                        {("povolené externí domény:")}
+                        input.config-row-input-field
+                        type="text"
+                        hx-post="command:save_allowed_domains"
+                        hx-trigger="change"
+                        name="text"
+                        placeholder="prázdné - všechny kromě zakázaných"
+                        value=(config.allowed_domains().join(", "))
+                        {}
+                    }
+                    div.config-row{
+                        h1.config-row-title
+                        {("zakázané domény:")}
+                        input.config-row-input-field
+                        type="text"
+                        hx-post="command:save_blocked_domains"
+                        hx-trigger="change"
+                        name="text"
+                        value=(config.blocked_domains().join(", "))
+                        {}
+                    }
+                    div.config-row{
+                        h1.config-row-title
+                        {("souhrn před odesláním:")}
+                        select.config-row-input-field
+                        hx-post="command:save_send_confirmation"
+                        hx-trigger="change"
+                        name="text"
+                        {
+                            option value="never" selected[config.send_confirmation() == SendConfirmation::Never] {("nezobrazovat")}
+                            option value="external" selected[config.send_confirmation() == SendConfirmation::External] {("jen při externích příjemcích")}
+                            option value="always" selected[config.send_confirmation() == SendConfirmation::Always] {("vždy")}
+                        }
+                    }
+                    div.config-row{
+                        h1.config-row-title
+                        {("čas na zrušení odeslání (s):")}
+                        input.config-row-input-field
+                        type="number"
+                        min="0"
+                        hx-post="command:save_undo_delay"
+                        hx-trigger="change"
+                        name="text"
+                        value=(config.undo_delay())
+                        {}
+                    }
+                    div.config-row{
+                        h1.config-row-title
+                        {("archiv odeslaných (dní, 0 - navždy):")}
+                        input.config-row-input-field
+                        type="number"
+                        min="0"
+                        hx-post="command:save_archive_retention_days"
+                        hx-trigger="change"
+                        name="text"
+                        value=(config.archive_retention_days())
+                        {}
+                    }
+                    div.config-row{
+                        h1.config-row-title
+                        {("schválení vedoucím:")}
+                        select.config-row-input-field
+                        hx-post="command:save_approval"
+                        hx-trigger="change"
+                        name="text"
+                        {
+                            option value="never" selected[config.approval() == ApprovalRule::Never] {("nevyžadovat")}
+                            option value="external" selected[config.approval() == ApprovalRule::External] {("jen při externích příjemcích")}
+                            option value="always" selected[config.approval() == ApprovalRule::Always] {("vždy")}
+                        }
+                    }
+                    div.config-row{
+                        h1.config-row-title
+                        {("schválení vždy pro funkce:")}
+                        input.config-row-input-field
+                        type="text"
+                        hx-post="command:save_approval_roles"
+                        hx-trigger="change"
+                        name="text"
+                        value=(config.approval_roles().join(", "))
+                        {}
+                    }
+                    div #sender-profiles{
+                        (render_profiles(&config, &categories))
+                    }
                 }
                 div.bottom-button-row{
                     button.save-config.save
@@ -137,6 +254,142 @@ pub fn open_settings_config(app: tauri::AppHandle) -> String {
     markup.into_string()
 }
 
+fn category_names(app_state: &AppState) -> Vec<(usize, String)> {
+    app_state
+        .mail_list
+        .lock()
+        .categories()
+        .iter()
+        .map(|category| (category.id, category.name.clone()))
+        .collect()
+}
+
+fn profile_row(id: usize, field: &str, title: &str, value: &str) -> Markup {
+    html! {
+        div.config-row{
+            h1.config-row-title
+            {(title)}
+            input.config-row-input-field
+            type="text"
+            hx-post="command:edit_profile"
+            hx-trigger="change"
+            hx-swap="none"
+            hx-vals={(format!(r#""id": {id}, "field": "{field}""#))}
+            name="text"
+            value=(value)
+            {}
+        }
+    }
+}
+
+//{mechanik} and {datum} in the subject are filled in when sending
+fn render_profiles(config: &Config, categories: &[(usize, String)]) -> Markup {
+    html! {
+        h2.category-title{("profily odesilatele")}
+        @for profile in config.profiles() {
+            div.sender-profile{
+                (profile_row(profile.id, "name", "název profilu:", &profile.name))
+                (profile_row(profile.id, "sender_name", "Jméno odesilatele:", &profile.sender_name))
+                (profile_row(profile.id, "sender_mail", "E-mail odesilatele:", &profile.sender_mail))
+                (profile_row(profile.id, "sender_password", "heslo odesilatele:", &profile.sender_password))
+                (profile_row(profile.id, "smtp_transport", "smtp transport:", &profile.smtp_transport))
+                (profile_row(profile.id, "title", "předmět E-mailu:", &profile.title))
+                (profile_row(profile.id, "signature", "podpis:", &profile.signature))
+                button.remove-button
+                hx-post="command:remove_profile"
+                hx-trigger="click"
+                hx-target="#sender-profiles"
+                hx-swap="innerHTML"
+                hx-vals={(format!(r#""id": {}"#, profile.id))}
+                {("smazat profil")}
+            }
+        }
+        button.add-button
+        hx-post="command:add_profile"
+        hx-trigger="click"
+        hx-target="#sender-profiles"
+        hx-swap="innerHTML"
+        {("+ přidat profil")}
+        @if !config.profiles().is_empty() {
+            h2.category-title{("výchozí profil podle kategorie příjemců")}
+            @for (category_id, name) in categories {
+                @let group_profile = config.group_profile(*category_id);
+                div.config-row{
+                    h1.config-row-title
+                    {(format!("{name}:"))}
+                    select.config-row-input-field
+                    hx-post="command:save_group_profile"
+                    hx-trigger="change"
+                    hx-swap="none"
+                    hx-vals={(format!(r#""id": {category_id}"#))}
+                    name="text"
+                    {
+                        option value="main" selected[group_profile.is_none()] {("hlavní")}
+                        @for profile in config.profiles() {
+                            option value=(profile.id) selected[group_profile == Some(profile.id)] {(profile.name)}
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[tauri::command]
+pub fn add_profile(app: tauri::AppHandle) -> String {
+    let app_state = app.state::<AppState>();
+    let categories = category_names(&app_state);
+
+    let mut config = app_state.config.lock();
+    config.add_profile();
+
+    render_profiles(&config, &categories).into_string()
+}
+
+#[tauri::command]
+pub fn remove_profile(app: tauri::AppHandle, id: String) -> String {
+    let id: usize = id
+        .parse()
+        .unwrap_or_else(|_| error_id_parse(app.clone(), id));
+
+    let app_state = app.state::<AppState>();
+    let categories = category_names(&app_state);
+
+    let mut config = app_state.config.lock();
+    config.remove_profile(id);
+
+    render_profiles(&config, &categories).into_string()
+}
+
+#[tauri::command]
+pub fn edit_profile(app: tauri::AppHandle, id: String, field: String, text: String) {
+    let id: usize = id
+        .parse()
+        .unwrap_or_else(|_| error_id_parse(app.clone(), id));
+
+    let app_state = app.state::<AppState>();
+
+    app_state.config.lock().edit_profile(id, &field, text);
+}
+
+#[tauri::command]
+pub fn save_group_profile(app: tauri::AppHandle, id: String, text: String) {
+    let id: usize = id
+        .parse()
+        .unwrap_or_else(|_| error_id_parse(app.clone(), id));
+
+    let app_state = app.state::<AppState>();
+
+    app_state.config.lock().save_group_profile(id, text);
+}
+
+#[tauri::command]
+pub fn save_signature(app: tauri::AppHandle, text: String) {
+    let app_state = app.state::<AppState>();
+
+    app_state.config.lock().save_signature(text);
+}
+
 #[tauri::command]
 pub fn save_sender_name(app: tauri::AppHandle, text: String) {
     let app_state = app.state::<AppState>();
@@ -200,11 +453,68 @@ pub fn save_settings_password(app: tauri::AppHandle, text: String) {
     app_state.config.lock().save_settings_password(text);
 }
 
+#[tauri::command]
+pub fn save_internal_domains(app: tauri::AppHandle, text: String) {
+    let app_state = app.state::<AppState>();
+
+    app_state.config.lock().save_internal_domains(text);
+}
+
+#[tauri::command]
+pub fn save_allowed_domains(app: tauri::AppHandle, text: String) {
+    let app_state = app.state::<AppState>();
+
+    app_state.config.lock().save_allowed_domains(text);
+}
+
+#[tauri::command]
+pub fn save_blocked_domains(app: tauri::AppHandle, text: String) {
+    let app_state = app.state::<AppState>();
+
+    app_state.config.lock().save_blocked_domains(text);
+}
+
+#[tauri::command]
+pub fn save_send_confirmation(app: tauri::AppHandle, text: String) {
+    let app_state = app.state::<AppState>();
+
+    app_state.config.lock().save_send_confirmation(text);
+}
+
+#[tauri::command]
+pub fn save_undo_delay(app: tauri::AppHandle, text: String) {
+    let app_state = app.state::<AppState>();
+
+    app_state.config.lock().save_undo_delay(text);
+}
+
+#[tauri::command]
+pub fn save_archive_retention_days(app: tauri::AppHandle, text: String) {
+    let app_state = app.state::<AppState>();
+
+    app_state.config.lock().save_archive_retention_days(text);
+}
+
+#[tauri::command]
+pub fn save_approval(app: tauri::AppHandle, text: String) {
+    let app_state = app.state::<AppState>();
+
+    app_state.config.lock().save_approval(text);
+}
+
+#[tauri::command]
+pub fn save_approval_roles(app: tauri::AppHandle, text: String) {
+    let app_state = app.state::<AppState>();
+
+    app_state.config.lock().save_approval_roles(text);
+}
+
 #[tauri::command]
 pub fn save_and_close_settings_config(app: tauri::AppHandle) -> String {
     let app_state = app.state::<AppState>();
 
     app_state.config.lock().save_config(app.clone());
+    refresh_integrity_issues(&app);
 
     close_settings_config()
 }
@@ -214,6 +524,7 @@ pub fn discard_and_close_settings_config(app: tauri::AppHandle) -> String {
     let app_state = app.state::<AppState>();
 
     *app_state.config.lock() = Config::load_config(app.clone());
+    refresh_integrity_issues(&app);
 
     close_settings_config()
 }
@@ -0,0 +1,405 @@
+use maud::{html, Markup};
+use tauri::Manager;
+
+use crate::backend::domain_suggestions::DomainSuggestions;
+use crate::backend::error_handling::{error_id_parse, error_load_person};
+use crate::backend::mail_list_utils::Contact;
+use crate::handlers::other_mail::domain_suggestions;
+use crate::AppState;
+
+//---------------------------
+
+fn render_addresses(contact: &Contact, domains: &DomainSuggestions) -> Markup {
+    let id = contact.id;
+
+    html! {
+        @for (index, address) in contact.addresses.iter().enumerate() {
+            div.other-mail-button-row{
+                input.contact-address-label
+                type="text"
+                hx-post="command:edit_contact_address_label"
+                name="text"
+                hx-trigger="change"
+                hx-swap="none"
+                placeholder="označení"
+                hx-vals={(format!(r#""id": {id}, "index": {index}"#))}
+                value=(address.label)
+                {}
+                input.other-mail-input-field
+                type="text"
+                hx-post="command:edit_contact_address_mail"
+                name="text"
+                hx-trigger="change"
+                hx-target="#contact-addresses"
+                hx-swap="innerHTML"
+                placeholder="Zadejte prosím E-mail"
+                hx-vals={(format!(r#""id": {id}, "index": {index}"#))}
+                value=(address.mail)
+                {}
+                button.add-button.clicked[index == contact.primary]
+                hx-post="command:set_contact_primary_address"
+                hx-trigger="click"
+                hx-target="#contact-addresses"
+                hx-swap="innerHTML"
+                hx-vals={(format!(r#""id": {id}, "index": {index}"#))}
+                {("primární")}
+                button.remove-button
+                hx-post="command:remove_contact_address"
+                hx-trigger="click"
+                hx-target="#contact-addresses"
+                hx-swap="innerHTML"
+                hx-vals={(format!(r#""id": {id}, "index": {index}"#))}
+                {("odstranit")}
+            }
+            @if let Some(suggestion) = domains.suggest(&address.mail) {
+                div.other-mail-suggestion{
+                    p.other-mail-suggestion-text{(format!("Nemysleli jste \"{suggestion}\"?"))}
+                    button.add-button
+                    hx-post="command:apply_contact_address_suggestion"
+                    hx-trigger="click"
+                    hx-target="#contact-addresses"
+                    hx-swap="innerHTML"
+                    hx-vals={(format!(r#""id": {id}, "index": {index}"#))}
+                    {("opravit")}
+                }
+            }
+        }
+        button.add-button
+        hx-post="command:add_contact_address"
+        hx-trigger="click"
+        hx-target="#contact-addresses"
+        hx-swap="innerHTML"
+        hx-vals={(format!(r#""id": {id}"#))}
+        {("přidat další E-mail")}
+    }
+}
+
+fn active_button(id: usize, active: bool) -> Markup {
+    html! {
+        button.add-button.clicked[!active]
+        hx-post="command:toggle_contact_active"
+        hx-trigger="click"
+        hx-swap="outerHTML"
+        hx-vals={(format!(r#""id": {id}"#))}
+        {(if active { "aktivní" } else { "neaktivní - skrytý na hlavní obrazovce" })}
+    }
+}
+
+#[tauri::command]
+pub fn open_contact_editor(id: String, app: tauri::AppHandle) -> String {
+    let id: usize = id
+        .parse()
+        .unwrap_or_else(|_| error_id_parse(app.clone(), id));
+
+    let app_state = app.state::<AppState>();
+
+    let domains = domain_suggestions(&app_state);
+
+    let Some(contact) = app_state.mail_list.lock().load_contact(id) else {
+        return error_load_person(app, id);
+    };
+
+    let markup: Markup = html! {
+        div #overlay-contact-editor .overlay .most-top
+        {
+            div.overlay-window
+            {
+                button.close-button
+                hx-post="command:close_contact_editor"
+                hx-trigger="click"
+                hx-target="#overlay-contact-editor"
+                hx-swap="outerHTML"
+                hx-vals={(format!(r#""id": {id}"#))}
+                {("X")}
+                h1.overlay-title{("úprava kontaktu")}
+                div.contact-editor-fields{
+                    label.contact-editor-label{
+                        ("jméno")
+                        input.contact-editor-input
+                        type="text"
+                        hx-post="command:edit_person_name"
+                        name="text"
+                        hx-trigger="change"
+                        hx-swap="none"
+                        hx-vals={(format!(r#""id": {id}"#))}
+                        value=(contact.name)
+                        {}
+                    }
+                    label.contact-editor-label{
+                        ("funkce")
+                        input.contact-editor-input
+                        type="text"
+                        hx-post="command:edit_contact_role"
+                        name="text"
+                        hx-trigger="change"
+                        hx-swap="none"
+                        hx-vals={(format!(r#""id": {id}"#))}
+                        value=(contact.role)
+                        {}
+                    }
+                    label.contact-editor-label{
+                        ("telefon")
+                        input.contact-editor-input
+                        type="tel"
+                        hx-post="command:edit_contact_phone"
+                        name="text"
+                        hx-trigger="change"
+                        hx-swap="none"
+                        hx-vals={(format!(r#""id": {id}"#))}
+                        value=(contact.phone)
+                        {}
+                    }
+                    label.contact-editor-label{
+                        ("PIN")
+                        input.contact-editor-input
+                        type="password"
+                        inputmode="numeric"
+                        autocomplete="off"
+                        hx-post="command:edit_contact_pin"
+                        name="text"
+                        hx-trigger="change"
+                        hx-swap="none"
+                        hx-vals={(format!(r#""id": {id}"#))}
+                        value=(contact.pin)
+                        {}
+                    }
+                    (active_button(id, contact.active))
+                }
+                h2.category-title{("E-maily")}
+                div.other-mail-buttons #contact-addresses{
+                    (render_addresses(&contact, &domains))
+                }
+                h2.category-title{("poznámky")}
+                textarea.contact-editor-notes
+                name="text"
+                hx-post="command:edit_contact_notes"
+                hx-trigger="change"
+                hx-swap="none"
+                hx-vals={(format!(r#""id": {id}"#))}
+                {(contact.notes)}
+            }
+        }
+    };
+
+    markup.into_string()
+}
+
+//bottom bar and the person button are reloaded, name and primary mail could change
+#[tauri::command]
+pub fn close_contact_editor(id: String, app: tauri::AppHandle) -> String {
+    let id: usize = id
+        .parse()
+        .unwrap_or_else(|_| error_id_parse(app.clone(), id));
+
+    //edit_person marks the person again
+    *app.state::<AppState>().settings_current_person_id.lock() = None;
+
+    let markup: Markup = html! {
+        div #contact-editor-placeholder {}
+        div
+        hx-trigger="load delay:1ms"
+        hx-swap="innerHTML"
+        hx-target="#settings-categories"
+        hx-post="command:load_settings_categories"
+        {}
+        div
+        hx-trigger="load delay:1ms"
+        hx-swap="outerHTML"
+        hx-target="#bottom-bar"
+        hx-vals={(format!(r#""id": {id}"#))}
+        hx-post="command:edit_person"
+        {}
+    };
+
+    markup.into_string()
+}
+
+#[tauri::command]
+pub fn edit_contact_role(app: tauri::AppHandle, id: String, text: String) {
+    let id: usize = id
+        .parse()
+        .unwrap_or_else(|_| error_id_parse(app.clone(), id));
+
+    let app_state = app.state::<AppState>();
+
+    app_state
+        .mail_list
+        .lock()
+        .update_contact(id, |contact| contact.role = text);
+}
+
+#[tauri::command]
+pub fn edit_contact_phone(app: tauri::AppHandle, id: String, text: String) {
+    let id: usize = id
+        .parse()
+        .unwrap_or_else(|_| error_id_parse(app.clone(), id));
+
+    let app_state = app.state::<AppState>();
+
+    app_state
+        .mail_list
+        .lock()
+        .update_contact(id, |contact| contact.phone = text);
+}
+
+#[tauri::command]
+pub fn edit_contact_pin(app: tauri::AppHandle, id: String, text: String) {
+    let id: usize = id
+        .parse()
+        .unwrap_or_else(|_| error_id_parse(app.clone(), id));
+
+    let app_state = app.state::<AppState>();
+
+    app_state
+        .mail_list
+        .lock()
+        .update_contact(id, |contact| contact.pin = text.trim().to_string());
+}
+
+#[tauri::command]
+pub fn edit_contact_notes(app: tauri::AppHandle, id: String, text: String) {
+    let id: usize = id
+        .parse()
+        .unwrap_or_else(|_| error_id_parse(app.clone(), id));
+
+    let app_state = app.state::<AppState>();
+
+    app_state
+        .mail_list
+        .lock()
+        .update_contact(id, |contact| contact.notes = text);
+}
+
+#[tauri::command]
+pub fn toggle_contact_active(app: tauri::AppHandle, id: String) -> String {
+    let id: usize = id
+        .parse()
+        .unwrap_or_else(|_| error_id_parse(app.clone(), id));
+
+    let app_state = app.state::<AppState>();
+
+    let mut mail_list = app_state.mail_list.lock();
+
+    mail_list.update_contact(id, |contact| contact.active = !contact.active);
+
+    let active = mail_list
+        .load_contact(id)
+        .is_some_and(|contact| contact.active);
+
+    active_button(id, active).into_string()
+}
+
+#[tauri::command]
+pub fn add_contact_address(app: tauri::AppHandle, id: String) -> String {
+    edit_addresses(app, id, |contact, _| contact.add_address())
+}
+
+#[tauri::command]
+pub fn remove_contact_address(app: tauri::AppHandle, id: String, index: String) -> String {
+    let index: usize = index
+        .parse()
+        .unwrap_or_else(|_| error_id_parse(app.clone(), index));
+
+    edit_addresses(app, id, |contact, _| contact.remove_address(index))
+}
+
+#[tauri::command]
+pub fn set_contact_primary_address(app: tauri::AppHandle, id: String, index: String) -> String {
+    let index: usize = index
+        .parse()
+        .unwrap_or_else(|_| error_id_parse(app.clone(), index));
+
+    edit_addresses(app, id, |contact, _| contact.set_primary(index))
+}
+
+#[tauri::command]
+pub fn apply_contact_address_suggestion(
+    app: tauri::AppHandle,
+    id: String,
+    index: String,
+) -> String {
+    let index: usize = index
+        .parse()
+        .unwrap_or_else(|_| error_id_parse(app.clone(), index));
+
+    edit_addresses(app, id, |contact, domains| {
+        if let Some(address) = contact.addresses.get_mut(index) {
+            if let Some(suggestion) = domains.suggest(&address.mail) {
+                address.mail = suggestion;
+            }
+        }
+    })
+}
+
+//domains are passed in for applying suggestions, they can't be built under mail_list lock
+fn edit_addresses(
+    app: tauri::AppHandle,
+    id: String,
+    edit: impl FnOnce(&mut Contact, &DomainSuggestions),
+) -> String {
+    let id: usize = id
+        .parse()
+        .unwrap_or_else(|_| error_id_parse(app.clone(), id));
+
+    let app_state = app.state::<AppState>();
+
+    let domains = domain_suggestions(&app_state);
+
+    let mut mail_list = app_state.mail_list.lock();
+
+    mail_list.update_contact(id, |contact| edit(contact, &domains));
+
+    match mail_list.load_contact(id) {
+        Some(contact) => render_addresses(&contact, &domains).into_string(),
+        None => "".to_string(),
+    }
+}
+
+#[tauri::command]
+pub fn edit_contact_address_label(app: tauri::AppHandle, id: String, index: String, text: String) {
+    edit_address(app, id, index, |contact, index| {
+        if let Some(address) = contact.addresses.get_mut(index) {
+            address.label = text;
+        }
+    });
+}
+
+//addresses are rendered again to show a suggestion for mistyped domain
+#[tauri::command]
+pub fn edit_contact_address_mail(
+    app: tauri::AppHandle,
+    id: String,
+    index: String,
+    text: String,
+) -> String {
+    let index: usize = index
+        .parse()
+        .unwrap_or_else(|_| error_id_parse(app.clone(), index));
+
+    edit_addresses(app, id, |contact, _| {
+        if let Some(address) = contact.addresses.get_mut(index) {
+            address.mail = text;
+        }
+    })
+}
+
+fn edit_address(
+    app: tauri::AppHandle,
+    id: String,
+    index: String,
+    edit: impl FnOnce(&mut Contact, usize),
+) {
+    let id: usize = id
+        .parse()
+        .unwrap_or_else(|_| error_id_parse(app.clone(), id));
+    let index: usize = index
+        .parse()
+        .unwrap_or_else(|_| error_id_parse(app.clone(), index));
+
+    let app_state = app.state::<AppState>();
+
+    app_state
+        .mail_list
+        .lock()
+        .update_contact(id, |contact| edit(contact, index));
+}
@@ -0,0 +1,268 @@
+use maud::{html, Markup};
+use tauri::{Emitter, Manager};
+use tauri_plugin_dialog::DialogExt;
+
+use crate::backend::contact_exchange::{
+    apply_import, export_contacts, ContactFormat, ContactImport, CsvField, ImportChange,
+};
+use crate::backend::error_handling::{error_exporting_contacts, error_importing_contacts};
+use crate::AppState;
+
+//---------------------------
+
+#[tauri::command]
+pub fn open_contact_exchange() -> String {
+    let markup: Markup = html! {
+        div #overlay-contact-exchange .overlay .most-top
+        {
+            div.overlay-window
+            {
+                button.close-button
+                hx-post="command:close_contact_exchange"
+                hx-trigger="click"
+                hx-target="#overlay-contact-exchange"
+                hx-swap="outerHTML"
+                {("X")}
+                h1.overlay-title{("import a export kontaktů")}
+                div.bottom-button-row{
+                    button.add-button
+                    hx-post="command:import_contacts"
+                    hx-trigger="click"
+                    hx-swap="none"
+                    {("importovat CSV / vCard")}
+                    button.add-button
+                    hx-post="command:export_contacts_csv"
+                    hx-trigger="click"
+                    hx-swap="none"
+                    {("exportovat CSV")}
+                    button.add-button
+                    hx-post="command:export_contacts_vcard"
+                    hx-trigger="click"
+                    hx-swap="none"
+                    {("exportovat vCard")}
+                }
+                //filled after the file is picked
+                div.contact-import-preview #contact-import-preview
+                tauri-listen="contact_import_preview"
+                hx-swap="innerHTML"
+                {}
+            }
+        }
+    };
+
+    markup.into_string()
+}
+
+#[tauri::command]
+pub fn close_contact_exchange(app: tauri::AppHandle) -> String {
+    let app_state = app.state::<AppState>();
+
+    *app_state.contact_import.lock() = None;
+
+    let markup: Markup = html! {
+        div #contact-exchange-placeholder {}
+    };
+
+    markup.into_string()
+}
+
+#[tauri::command]
+pub fn import_contacts(app: tauri::AppHandle) {
+    app.dialog()
+        .file()
+        .add_filter("kontakty", &["csv", "txt", "vcf", "vcard"])
+        .pick_file(move |file_path| {
+            let Some(path) = file_path.and_then(|file_path| file_path.into_path().ok()) else {
+                return;
+            };
+
+            match ContactImport::load(&path) {
+                Ok(import) => {
+                    let app_state = app.state::<AppState>();
+
+                    let preview = render_import_preview(&import, &app_state);
+                    *app_state.contact_import.lock() = Some(import);
+
+                    let _ = app.emit("contact_import_preview", preview.into_string());
+                }
+                Err(error) => error_importing_contacts(app.clone(), error),
+            }
+        });
+}
+
+fn render_import_preview(import: &ContactImport, app_state: &AppState) -> Markup {
+    let changes = import.plan(&app_state.mail_list.lock());
+
+    let count = |filter: fn(&ImportChange) -> bool| changes.iter().filter(|c| filter(c)).count();
+    let adds = count(|change| matches!(change, ImportChange::Add(_)));
+    let updates = count(|change| matches!(change, ImportChange::Update { .. }));
+    let unchanged = count(|change| matches!(change, ImportChange::Unchanged(_)));
+    let conflicts = count(|change| matches!(change, ImportChange::Conflict { .. }));
+
+    html! {
+        @if import.format == ContactFormat::Csv {
+            div.contact-import-mapping{
+                @for (field, label) in [
+                    (CsvField::Name, "jméno"),
+                    (CsvField::Mail, "E-mail"),
+                    (CsvField::Phone, "telefon"),
+                    (CsvField::Role, "funkce"),
+                    (CsvField::Category, "kategorie"),
+                ] {
+                    label.contact-import-mapping-label{
+                        (label)
+                        select
+                        name="text"
+                        hx-post="command:map_import_column"
+                        hx-trigger="change"
+                        hx-target="#contact-import-preview"
+                        hx-swap="innerHTML"
+                        hx-vals={(format!(r#""field": "{}""#, field_key(field)))}
+                        {
+                            option value="" selected[import.mapping.column(field).is_none()] {("-")}
+                            @for (index, header) in import.headers.iter().enumerate() {
+                                option
+                                value=(index)
+                                selected[import.mapping.column(field) == Some(index)]
+                                {(header)}
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        p.contact-import-summary{
+            (format!("nové: {adds} | změněné: {updates} | beze změny: {unchanged} | konflikty: {conflicts}"))
+        }
+        div.contact-import-rows{
+            @for change in &changes {
+                @match change {
+                    ImportChange::Add(contact) => {
+                        p.contact-import-row.contact-import-add{
+                            (format!("+ {} - {}", contact.name, contact.mail))
+                            @if let Some(category) = &contact.category {
+                                (format!(" ({category})"))
+                            }
+                        }
+                    }
+                    ImportChange::Update { old_name, old_mail, contact, .. } => {
+                        p.contact-import-row.contact-import-update{
+                            (format!("~ {old_name} - {old_mail} → {} - {}", contact.name, contact.mail))
+                        }
+                    }
+                    ImportChange::Unchanged(contact) => {
+                        p.contact-import-row{
+                            (format!("= {} - {}", contact.name, contact.mail))
+                        }
+                    }
+                    ImportChange::Conflict { contact, reason } => {
+                        p.contact-import-row.contact-import-conflict{
+                            (format!("! {} - {} - {reason}", contact.name, contact.mail))
+                        }
+                    }
+                }
+            }
+        }
+        div.bottom-button-row{
+            button.add-button
+            hx-post="command:apply_contact_import"
+            hx-trigger="click"
+            hx-target="#overlay-contact-exchange"
+            hx-swap="outerHTML"
+            disabled[adds + updates == 0]
+            {("použít import")}
+            button.remove-button
+            hx-post="command:close_contact_exchange"
+            hx-trigger="click"
+            hx-target="#overlay-contact-exchange"
+            hx-swap="outerHTML"
+            {("zrušit")}
+        }
+    }
+}
+
+fn field_key(field: CsvField) -> &'static str {
+    match field {
+        CsvField::Name => "name",
+        CsvField::Mail => "mail",
+        CsvField::Phone => "phone",
+        CsvField::Role => "role",
+        CsvField::Category => "category",
+    }
+}
+
+#[tauri::command]
+pub fn map_import_column(app: tauri::AppHandle, field: String, text: String) -> String {
+    let app_state = app.state::<AppState>();
+
+    let field = match field.as_str() {
+        "name" => CsvField::Name,
+        "mail" => CsvField::Mail,
+        "phone" => CsvField::Phone,
+        "role" => CsvField::Role,
+        _ => CsvField::Category,
+    };
+
+    let mut contact_import = app_state.contact_import.lock();
+
+    let Some(import) = contact_import.as_mut() else {
+        return "".to_string();
+    };
+
+    import.mapping.set_column(field, text.parse().ok());
+
+    render_import_preview(import, &app_state).into_string()
+}
+
+//changes are kept in memory, they are saved with "uložit a zavřít" like any other edit
+#[tauri::command]
+pub fn apply_contact_import(app: tauri::AppHandle) -> String {
+    let app_state = app.state::<AppState>();
+
+    if let Some(import) = app_state.contact_import.lock().take() {
+        let mut mail_list = app_state.mail_list.lock();
+
+        let changes = import.plan(&mail_list);
+        apply_import(&mut mail_list, &changes);
+    }
+
+    let markup: Markup = html! {
+        div #contact-exchange-placeholder {}
+        div
+        hx-trigger="load delay:1ms"
+        hx-swap="innerHTML"
+        hx-target="#settings-categories"
+        hx-post="command:load_settings_categories"
+        {}
+    };
+
+    markup.into_string()
+}
+
+#[tauri::command]
+pub fn export_contacts_csv(app: tauri::AppHandle) {
+    export(app, ContactFormat::Csv);
+}
+
+#[tauri::command]
+pub fn export_contacts_vcard(app: tauri::AppHandle) {
+    export(app, ContactFormat::VCard);
+}
+
+fn export(app: tauri::AppHandle, format: ContactFormat) {
+    let contents = export_contacts(&app.state::<AppState>().mail_list.lock(), format);
+
+    app.dialog()
+        .file()
+        .add_filter("kontakty", &[format.extension()])
+        .set_file_name(format!("kontakty.{}", format.extension()))
+        .save_file(move |file_path| {
+            let Some(path) = file_path.and_then(|file_path| file_path.into_path().ok()) else {
+                return;
+            };
+
+            if std::fs::write(path, contents).is_err() {
+                error_exporting_contacts(app);
+            }
+        });
+}
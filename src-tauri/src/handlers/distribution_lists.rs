@@ -0,0 +1,331 @@
+use maud::{html, Markup};
+use tauri::Manager;
+
+use crate::backend::error_handling::error_id_parse;
+use crate::backend::mail_list_utils::{ListMember, MailList};
+use crate::AppState;
+
+//---------------------------
+
+pub fn distribution_list_button(id: usize, name: &str, selected: bool) -> Markup {
+    let command = if selected {
+        "command:deselect_distribution_list"
+    } else {
+        "command:select_distribution_list"
+    };
+
+    html! {
+        button.middle-button.distribution-list-button.clicked[selected]
+        #{"distribution-list-"(id)}
+        hx-post=(command)
+        hx-trigger="click"
+        hx-swap="outerHTML"
+        hx-vals={(format!(r#""id": {id}"#))}
+        {(name)}
+    }
+}
+
+//people picked through list are shown on the category buttons, so they are reloaded too
+fn reload_categories() -> Markup {
+    html! {
+        div
+        hx-trigger="load delay:1ms"
+        hx-swap="innerHTML"
+        hx-target="#category-sections"
+        hx-post="command:load_categories"
+        {}
+    }
+}
+
+#[tauri::command]
+pub fn select_distribution_list(app: tauri::AppHandle, id: String) -> String {
+    let id: usize = id
+        .parse()
+        .unwrap_or_else(|_| error_id_parse(app.clone(), id));
+
+    let app_state = app.state::<AppState>();
+
+    let mail_list = app_state.mail_list.lock();
+
+    let Some(list) = mail_list.load_distribution_list(id) else {
+        return "".to_string();
+    };
+
+    app_state.mail.lock().select_distribution_list(
+        id,
+        mail_list.distribution_list_people(id),
+        app.clone(),
+    );
+
+    let markup: Markup = html! {
+        (distribution_list_button(id, &list.name, true))
+        (reload_categories())
+    };
+
+    markup.into_string()
+}
+
+#[tauri::command]
+pub fn deselect_distribution_list(app: tauri::AppHandle, id: String) -> String {
+    let id: usize = id
+        .parse()
+        .unwrap_or_else(|_| error_id_parse(app.clone(), id));
+
+    let app_state = app.state::<AppState>();
+
+    let mail_list = app_state.mail_list.lock();
+
+    let Some(list) = mail_list.load_distribution_list(id) else {
+        return "".to_string();
+    };
+
+    app_state.mail.lock().deselect_distribution_list(id);
+
+    let markup: Markup = html! {
+        (distribution_list_button(id, &list.name, false))
+        (reload_categories())
+    };
+
+    markup.into_string()
+}
+
+//---------------------------settings
+
+fn render_settings_distribution_lists(mail_list: &MailList) -> Markup {
+    html! {
+        h2.category-title{("distribuční seznamy")}
+        @for list in mail_list.distribution_lists() {
+            button.middle-button.distribution-list-button
+            hx-post="command:open_distribution_list"
+            hx-trigger="click"
+            hx-target="#distribution-list-placeholder"
+            hx-swap="outerHTML"
+            hx-vals={(format!(r#""id": {}"#, list.id))}
+            {(list.name)}
+        }
+        button.middle-button.add-category-button
+        hx-post="command:add_distribution_list"
+        hx-trigger="click"
+        hx-target="#distribution-list-placeholder"
+        hx-swap="outerHTML"
+        {("+ přidat seznam")}
+    }
+}
+
+fn member_button(list_id: usize, contact_id: usize, name: &str, member: bool) -> Markup {
+    html! {
+        button.middle-button.clicked[member]
+        #{"list-member-"(contact_id)}
+        hx-post="command:toggle_distribution_list_member"
+        hx-trigger="click"
+        hx-swap="outerHTML"
+        hx-vals={(format!(r#""id": {list_id}, "contact": {contact_id}"#))}
+        {(name)}
+    }
+}
+
+fn reload_settings_distribution_lists() -> Markup {
+    html! {
+        div
+        hx-trigger="load delay:1ms"
+        hx-swap="innerHTML"
+        hx-target="#settings-distribution-lists"
+        hx-post="command:load_settings_distribution_lists"
+        {}
+    }
+}
+
+#[tauri::command]
+pub fn load_settings_distribution_lists(app: tauri::AppHandle) -> String {
+    let app_state = app.state::<AppState>();
+
+    let markup = render_settings_distribution_lists(&app_state.mail_list.lock());
+
+    markup.into_string()
+}
+
+#[tauri::command]
+pub fn add_distribution_list(app: tauri::AppHandle) -> String {
+    let app_state = app.state::<AppState>();
+
+    let id = app_state
+        .mail_list
+        .lock()
+        .add_distribution_list("nový seznam".to_string());
+
+    open_distribution_list(app, id.to_string())
+}
+
+#[tauri::command]
+pub fn open_distribution_list(app: tauri::AppHandle, id: String) -> String {
+    let id: usize = id
+        .parse()
+        .unwrap_or_else(|_| error_id_parse(app.clone(), id));
+
+    let app_state = app.state::<AppState>();
+
+    let mail_list = app_state.mail_list.lock();
+
+    let Some(list) = mail_list.load_distribution_list(id) else {
+        return close_distribution_list();
+    };
+
+    let addresses: Vec<&str> = list
+        .members
+        .iter()
+        .filter_map(|member| match member {
+            ListMember::Address(mail) => Some(mail.as_str()),
+            ListMember::Contact(_) => None,
+        })
+        .collect();
+
+    let markup: Markup = html! {
+        div #overlay-distribution-list .overlay .most-top
+        {
+            div.overlay-window
+            {
+                button.close-button
+                hx-post="command:close_distribution_list"
+                hx-trigger="click"
+                hx-target="#overlay-distribution-list"
+                hx-swap="outerHTML"
+                {("X")}
+                h1.overlay-title{("úprava distribučního seznamu")}
+                input.settings-bottom-input
+                type="text"
+                hx-post="command:edit_distribution_list_name"
+                name="text"
+                hx-trigger="change"
+                hx-swap="innerHTML"
+                hx-target="#settings-distribution-lists"
+                hx-vals={(format!(r#""id": {id}"#))}
+                value=(list.name)
+                {}
+                div.distribution-list-members{
+                    @for category in mail_list.categories() {
+                        h2.category-title{(category.name)}
+                        div.category-buttons{
+                            @for contact in category.contacts.iter().filter(|c| !c.name.trim().is_empty()) {
+                                (member_button(
+                                    id,
+                                    contact.id,
+                                    &contact.name,
+                                    list.members.contains(&ListMember::Contact(contact.id)),
+                                ))
+                            }
+                        }
+                    }
+                }
+                h2.category-title{("další E-maily (oddělené čárkou)")}
+                textarea.distribution-list-addresses
+                name="text"
+                hx-post="command:edit_distribution_list_addresses"
+                hx-trigger="change"
+                hx-swap="none"
+                hx-vals={(format!(r#""id": {id}"#))}
+                {(addresses.join(", "))}
+                div.bottom-button-row{
+                    button.settings-bottom-button.close
+                    hx-post="command:remove_distribution_list"
+                    hx-trigger="click"
+                    hx-target="#overlay-distribution-list"
+                    hx-swap="outerHTML"
+                    hx-vals={(format!(r#""id": {id}"#))}
+                    {("smazat seznam")}
+                }
+            }
+        }
+        (reload_settings_distribution_lists())
+    };
+
+    markup.into_string()
+}
+
+#[tauri::command]
+pub fn close_distribution_list() -> String {
+    let markup: Markup = html! {
+        div #distribution-list-placeholder {}
+        (reload_settings_distribution_lists())
+    };
+
+    markup.into_string()
+}
+
+#[tauri::command]
+pub fn edit_distribution_list_name(app: tauri::AppHandle, id: String, text: String) -> String {
+    let id: usize = id
+        .parse()
+        .unwrap_or_else(|_| error_id_parse(app.clone(), id));
+
+    let app_state = app.state::<AppState>();
+
+    let mut mail_list = app_state.mail_list.lock();
+
+    mail_list.save_distribution_list_name(id, text);
+
+    render_settings_distribution_lists(&mail_list).into_string()
+}
+
+#[tauri::command]
+pub fn toggle_distribution_list_member(
+    app: tauri::AppHandle,
+    id: String,
+    contact: String,
+) -> String {
+    let id: usize = id
+        .parse()
+        .unwrap_or_else(|_| error_id_parse(app.clone(), id));
+    let contact_id: usize = contact
+        .parse()
+        .unwrap_or_else(|_| error_id_parse(app.clone(), contact));
+
+    let app_state = app.state::<AppState>();
+
+    let mut mail_list = app_state.mail_list.lock();
+
+    mail_list.toggle_distribution_list_contact(id, contact_id);
+
+    let name = mail_list
+        .load_contact(contact_id)
+        .map(|contact| contact.name)
+        .unwrap_or_default();
+    let member = mail_list
+        .load_distribution_list(id)
+        .is_some_and(|list| list.members.contains(&ListMember::Contact(contact_id)));
+
+    member_button(id, contact_id, &name, member).into_string()
+}
+
+//addresses are validated by save_list together with the contacts
+#[tauri::command]
+pub fn edit_distribution_list_addresses(app: tauri::AppHandle, id: String, text: String) {
+    let id: usize = id
+        .parse()
+        .unwrap_or_else(|_| error_id_parse(app.clone(), id));
+
+    let addresses: Vec<String> = text
+        .split(|c: char| c == ',' || c == ';' || c.is_whitespace())
+        .filter(|mail| !mail.is_empty())
+        .map(|mail| mail.to_string())
+        .collect();
+
+    let app_state = app.state::<AppState>();
+
+    app_state
+        .mail_list
+        .lock()
+        .save_distribution_list_addresses(id, addresses);
+}
+
+#[tauri::command]
+pub fn remove_distribution_list(app: tauri::AppHandle, id: String) -> String {
+    let id: usize = id
+        .parse()
+        .unwrap_or_else(|_| error_id_parse(app.clone(), id));
+
+    let app_state = app.state::<AppState>();
+
+    app_state.mail_list.lock().remove_distribution_list(id);
+
+    close_distribution_list()
+}
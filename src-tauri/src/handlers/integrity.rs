@@ -0,0 +1,45 @@
+use maud::{html, Markup};
+use tauri::Manager;
+
+use crate::AppState;
+
+//---------------------------
+
+#[tauri::command]
+pub fn load_integrity_banner(app: tauri::AppHandle) -> String {
+    let app_state = app.state::<AppState>();
+
+    let issues = app_state.integrity_issues.lock();
+
+    if issues.is_empty() {
+        return close_integrity_banner();
+    }
+
+    let markup: Markup = html! {
+        div.integrity-banner #integrity-banner{
+            div.integrity-banner-rows{
+                h2.integrity-banner-title{("Kontrola údajů našla problémy, informujte prosím vedoucího:")}
+                @for issue in issues.iter() {
+                    p.integrity-banner-row{(issue.description())}
+                }
+            }
+            button.integrity-banner-close
+            hx-post="command:close_integrity_banner"
+            hx-trigger="click"
+            hx-target="#integrity-banner"
+            hx-swap="outerHTML"
+            {("X")}
+        }
+    };
+
+    markup.into_string()
+}
+
+#[tauri::command]
+pub fn close_integrity_banner() -> String {
+    let markup: Markup = html! {
+        div #integrity-banner-placeholder {}
+    };
+
+    markup.into_string()
+}
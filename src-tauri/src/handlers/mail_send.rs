@@ -1,51 +1,189 @@
 use std::thread;
+use std::time::{Duration, Instant};
 
-use maud::{html, Markup};
+use maud::{html, Markup, PreEscaped};
 use tauri::{Emitter, Manager};
 use tauri_plugin_dialog::DialogExt;
 
+use crate::backend::config::{ApprovalRule, Config, RecipientDomain, SendConfirmation};
 use crate::backend::error_handling::{
-    error_id_parse, error_load_person, error_pick_file, error_sending_mail, error_showing_file_name,
+    error_id_parse, error_load_person, error_pick_file, error_scheduling_send, error_sending_mail,
+    error_showing_file_name,
 };
+use crate::backend::integrity::IntegrityIssue;
+use crate::backend::mail_list_utils::{Contact, Person};
+use crate::backend::mail_sender::{MailSender, PendingSend};
+use crate::backend::scheduled_sends::{ScheduleState, ScheduledSend, SCHEDULE_FORMAT};
+use crate::backend::send_history::{record_history, sha256_hex, HistoryEntry, HistoryOutcome};
+use crate::backend::tutorial::TutorialAction;
+use crate::handlers::distribution_lists::distribution_list_button;
+use crate::handlers::other_mail::domain_suggestions;
+use crate::handlers::sender::{current_sender, reset_sender, sender_button_reload};
+use crate::handlers::sender_profiles::send_config;
+use crate::handlers::tutorial::{send_to_sandbox, tutorial_act, tutorial_running};
 use crate::AppState;
 //---------------------------
 
+//invalid people are disabled, suspicious ones (duplicates) are only highlighted
+//listed - picked through selected distribution list
+//contacts with more addresses get a small button for picking the address
+pub fn person_button(
+    contact: &Contact,
+    clicked: bool,
+    listed: bool,
+    issues: &[IntegrityIssue],
+) -> Markup {
+    let id = contact.id;
+    let name = &contact.name;
+
+    let person_issues: Vec<&IntegrityIssue> = issues
+        .iter()
+        .filter(|issue| issue.person_id() == Some(id))
+        .collect();
+
+    let blocked = person_issues.iter().any(|issue| issue.blocks_sending());
+    let suspicious = !person_issues.is_empty() && !blocked;
+
+    let issue_text = (!person_issues.is_empty()).then(|| {
+        person_issues
+            .iter()
+            .map(|issue| issue.description())
+            .collect::<Vec<String>>()
+            .join("\n")
+    });
+
+    let command = if clicked {
+        "command:remove_person"
+    } else {
+        "command:add_person"
+    };
+
+    html! {
+        @if blocked {
+            button.middle-button.invalid-person
+            disabled
+            title=[issue_text]
+            {(name)}
+        }
+        @else if contact.addresses.len() > 1 {
+            div.person-button-group {
+                button.middle-button.clicked[clicked].list-selected[listed && !clicked].suspicious-person[suspicious]
+                hx-trigger="click"
+                hx-post=(command)
+                hx-target="closest .person-button-group"
+                hx-swap="outerHTML"
+                hx-vals={(format!(r#""id": {id}"#))}
+                data-person-id=(id)
+                title=[issue_text]
+                {(name)}
+                button.address-picker-button
+                hx-trigger="click"
+                hx-post="command:open_address_picker"
+                hx-target="#address-picker-placeholder"
+                hx-swap="outerHTML"
+                hx-vals={(format!(r#""id": {id}"#))}
+                title="vybrat E-mail"
+                {("@")}
+            }
+        }
+        @else {
+            button.middle-button.clicked[clicked].list-selected[listed && !clicked].suspicious-person[suspicious]
+            hx-trigger="click"
+            hx-post=(command)
+            hx-swap="outerHTML"
+            hx-vals={(format!(r#""id": {id}"#))}
+            data-person-id=(id)
+            title=[issue_text]
+            {(name)}
+        }
+    }
+}
+
+fn is_contact_picked(mail: &MailSender, contact: &Contact) -> bool {
+    contact.people().iter().any(|person| mail.is_picked(person))
+}
+
+fn is_contact_listed(mail: &MailSender, contact: &Contact) -> bool {
+    contact
+        .people()
+        .iter()
+        .any(|person| mail.is_in_selected_list(person))
+}
+
 #[tauri::command]
 pub fn send(app: tauri::AppHandle) -> String {
     let app_state = app.state::<AppState>();
+    let domains = domain_suggestions(&app_state);
+    let sender = current_sender(&app_state);
+    let (profile, config) = send_config(&app_state);
     let mut mail = app_state.mail.lock();
     let mut other_mail_list = app_state.other_mail_list.lock();
-    let config = app_state.config.lock().clone();
 
     let file_valid = mail.file_is_valid();
     let basic_list_valid = mail.person_list_is_valid() && other_mail_list.is_empty();
     let other_mail_list_valid = other_mail_list.is_valid() && other_mail_list.is_filled();
+    //suggestion has to be applied or kept in the other mail window first
+    let typo_suspected = other_mail_list.has_suggestions(&domains);
 
     //Show error when sending
     let error_message = if !other_mail_list.is_valid() {
         "Některý E-mail v kategorii ostatní není správný."
+    } else if typo_suspected {
+        "Některý E-mail v kategorii ostatní má možná překlep v doméně, zkontrolujte ho prosím."
     } else if !(basic_list_valid || other_mail_list_valid) {
         "Vyberte prosím příjemce před odesláním."
     } else if !file_valid {
         "Vyberte prosím soubor k odeslání."
+    } else if sender.is_none() {
+        "Vyberte prosím, kdo odesílá."
     } else {
         ""
     };
 
-    let valid_request = file_valid && (basic_list_valid || other_mail_list_valid);
+    let valid_request = file_valid
+        && (basic_list_valid || other_mail_list_valid)
+        && !typo_suspected
+        && sender.is_some();
 
     if !valid_request {
         let custom_val = format!(r#""text": "{error_message}""#);
 
         return html! {
-            input.truck
-            type="image"
-            src="src/assets/send_truck.svg"
-            alt="truck-icon"
-            hx-trigger="click"
-            hx-post="command:send"
+            (render_truck(false))
+
+            div.non-display
+            hx-post="command:open_send_error"
+            hx-trigger="load delay:1ms"
+            hx-target="#send-error-placeholder"
+            hx-swap="outerHTML"
+            hx-vals=(custom_val)
             {}
 
+        }
+        .into_string();
+    }
+
+    let other_people = other_mail_list.export_other_mail_list();
+
+    mail.set_sender(sender.as_ref().map(|sender| sender.person()));
+
+    let sent_to = sent_to(&mail, &other_people);
+
+    let blocked: Vec<&String> = sent_to
+        .iter()
+        .filter(|mail| config.recipient_domain(mail) == RecipientDomain::Blocked)
+        .collect();
+
+    if !blocked.is_empty() {
+        let blocked: Vec<&str> = blocked.iter().map(|mail| mail.as_str()).collect();
+        let custom_val = format!(
+            r#""text": "Na tyto E-maily není dovoleno posílat: {}""#,
+            blocked.join(", ")
+        );
+
+        return html! {
+            (render_truck(false))
+
             div.non-display
             hx-post="command:open_send_error"
             hx-trigger="load delay:1ms"
@@ -53,34 +191,230 @@ pub fn send(app: tauri::AppHandle) -> String {
             hx-swap="outerHTML"
             hx-vals=(custom_val)
             {}
+        }
+        .into_string();
+    }
+
+    //confirmation is valid only for the addresses shown, and only for this one sending
+    let confirmed = std::mem::take(&mut *app_state.confirmed_external.lock());
+    let summary_confirmed = std::mem::take(&mut *app_state.send_summary_confirmed.lock());
+
+    let has_external = sent_to
+        .iter()
+        .any(|mail| config.recipient_domain(mail) == RecipientDomain::External);
+    let unconfirmed = sent_to.iter().any(|mail| {
+        config.recipient_domain(mail) == RecipientDomain::External && !confirmed.contains(mail)
+    });
+
+    let summary_needed = !summary_confirmed
+        && match config.send_confirmation() {
+            SendConfirmation::Never => false,
+            SendConfirmation::External => has_external,
+            SendConfirmation::Always => true,
+        };
+
+    //summary lists external recipients too, so it replaces the external confirmation
+    let confirmation = if summary_needed {
+        Some("command:open_send_summary")
+    } else if unconfirmed {
+        Some("command:open_external_confirmation")
+    } else {
+        None
+    };
+
+    if let Some(confirmation) = confirmation {
+        return html! {
+            (render_truck(false))
 
+            div.non-display
+            hx-post=(confirmation)
+            hx-trigger="load delay:1ms"
+            hx-target="#send-error-placeholder"
+            hx-swap="outerHTML"
+            {}
         }
         .into_string();
     }
 
-    let mail_result = mail.send(
-        other_mail_list.export_other_mail_list(),
-        config,
-        app.clone(),
-    );
+    //tutorial message is only saved, approval, scheduling and the undo delay are skipped
+    if tutorial_running(&app) {
+        *app_state.schedule_at.lock() = None;
+
+        if let Err(error) = send_to_sandbox(&app, &mail, other_people, &config) {
+            error_sending_mail(app.clone(), error);
+
+            return render_truck(false).into_string();
+        }
+
+        mail.clear();
+        other_mail_list.clear();
+        reset_sender(&app_state);
 
-    if let Err(error) = mail_result {
-        error_sending_mail(app.clone(), error);
+        drop(mail);
+        drop(other_mail_list);
 
         return html! {
-            input.truck
-            type="image"
-            src="src/assets/send_truck.svg"
-            alt="truck-icon"
-            hx-trigger="click"
-            hx-post="command:send"
+            (render_truck(true))
+            (reload_selection(&app))
+        }
+        .into_string();
+    }
+
+    let approval_needed = match config.approval() {
+        ApprovalRule::Never => false,
+        ApprovalRule::External => has_external,
+        ApprovalRule::Always => true,
+    } || sender
+        .as_ref()
+        .is_some_and(|sender| config.role_needs_approval(&sender.role));
+
+    //message waits in settings, the admin sends it right after approving,
+    //so the time of "odeslat později" can't be kept
+    if approval_needed {
+        *app_state.schedule_at.lock() = None;
+
+        let recipients: Vec<Person> = mail
+            .recipients()
+            .iter()
+            .map(|recipient| Person {
+                name: recipient.name.clone(),
+                mail: recipient.mail.to_string(),
+            })
+            .collect();
+
+        let mut approval_queue = app_state.approval_queue.lock();
+        approval_queue.add(recipients, other_people, &mail, profile);
+        approval_queue.save(app.clone());
+        drop(approval_queue);
+
+        mail.clear();
+        other_mail_list.clear();
+        reset_sender(&app_state);
+
+        drop(mail);
+        drop(other_mail_list);
+
+        return html! {
+            (render_truck(false))
+
+            div.non-display
+            hx-post="command:open_approval_notice"
+            hx-trigger="load delay:1ms"
+            hx-target="#send-error-placeholder"
+            hx-swap="outerHTML"
+            {}
+            (reload_selection(&app))
+        }
+        .into_string();
+    }
+
+    //"odeslat později" - message is stored into the queue instead of sending
+    let schedule_at = app_state.schedule_at.lock().take();
+
+    if let Some(send_at) = schedule_at {
+        let scheduled = mail
+            .build_message(other_people.clone(), &config, app.clone())
+            .ok()
+            .and_then(|message| {
+                let send = ScheduledSend {
+                    id: 0,
+                    send_at: send_at.format(SCHEDULE_FORMAT).to_string(),
+                    state: ScheduleState::Waiting,
+                    from: config.sender_mail().to_string(),
+                    recipients: sent_to.clone(),
+                    free_addresses: other_people
+                        .iter()
+                        .map(|person| person.mail.clone())
+                        .collect(),
+                    subject: config.title().to_string(),
+                    files: mail.file_names(),
+                    sent_by: mail.sender().map(|sender| sender.name.clone()),
+                    profile,
+                    file_paths: mail.files(),
+                    resent_from: mail.resent_from(),
+                };
+
+                let mut scheduled_sends = app_state.scheduled_sends.lock();
+                scheduled_sends.add(&app, send, &message.formatted()).ok()?;
+                scheduled_sends.save(app.clone());
+
+                Some(())
+            });
+
+        if scheduled.is_none() {
+            error_scheduling_send(app.clone());
+
+            return render_truck(false).into_string();
+        }
+
+        mail.clear();
+        other_mail_list.clear();
+        reset_sender(&app_state);
+
+        drop(mail);
+        drop(other_mail_list);
+
+        return html! {
+            (render_truck(false))
+
+            div.non-display
+            hx-post="command:open_scheduled_sends"
+            hx-trigger="load delay:1ms"
+            hx-target="#send-error-placeholder"
+            hx-swap="outerHTML"
+            {}
+            (reload_selection(&app))
+        }
+        .into_string();
+    }
+
+    let delay = config.undo_delay();
+
+    //selection is moved away, so the main screen is empty while the message waits
+    //the timer is owned here, the countdown in the UI only shows it
+    if delay > 0 {
+        let wait = Duration::from_secs(delay.into());
+        let send_at = Instant::now() + wait;
+
+        *app_state.pending_send.lock() = Some(PendingSend {
+            mail: std::mem::take(&mut *mail),
+            other_mail_list: std::mem::take(&mut *other_mail_list),
+            send_at,
+            config: config.clone(),
+            sender_id: *app_state.current_sender.lock(),
+        });
+        reset_sender(&app_state);
+
+        let app_timer = app.clone();
+        thread::spawn(move || {
+            thread::sleep(wait);
+            finish_send(&app_timer, send_at);
+        });
+
+        drop(mail);
+        drop(other_mail_list);
+
+        return html! {
+            (render_truck(false))
+
+            div.non-display
+            hx-post="command:open_send_countdown"
+            hx-trigger="load delay:1ms"
+            hx-target="#send-error-placeholder"
+            hx-swap="outerHTML"
             {}
+            (reload_selection(&app))
         }
         .into_string();
     }
 
+    deliver(&app, &mut mail, other_people, config)
+}
+
+//send button, animated - the message has just left
+fn render_truck(animated: bool) -> Markup {
     html! {
-        input.truck.drive-animation
+        input.truck.drive-animation[animated]
         type="image"
         src="src/assets/send_truck.svg"
         alt="truck-icon"
@@ -89,9 +423,199 @@ pub fn send(app: tauri::AppHandle) -> String {
         hx-swap="outerHTML"
         {}
     }
+}
+
+fn sent_to(mail: &MailSender, other_people: &[Person]) -> Vec<String> {
+    mail.recipients()
+        .iter()
+        .map(|recipient| recipient.mail.to_string())
+        .chain(other_people.iter().map(|person| person.mail.clone()))
+        .collect()
+}
+
+//sends the message and records where it went, returns the truck
+fn deliver(
+    app: &tauri::AppHandle,
+    mail: &mut MailSender,
+    other_people: Vec<Person>,
+    config: Config,
+) -> String {
+    let app_state = app.state::<AppState>();
+
+    let sent_to = sent_to(mail, &other_people);
+    let free_addresses: Vec<String> = other_people
+        .iter()
+        .map(|person| person.mail.clone())
+        .collect();
+
+    let subject = config.title().to_string();
+    let files = mail.file_names();
+    let file_paths = mail.files();
+    let sent_by = mail.sender().map(|sender| sender.name.clone());
+    let resent_from = mail.resent_from();
+
+    let message = match mail.send(other_people, config, app.clone()) {
+        Ok(message) => message,
+        Err(error) => {
+            error_sending_mail(app.clone(), error);
+
+            return render_truck(false).into_string();
+        }
+    };
+
+    let mut send_stats = app_state.send_stats.lock();
+    send_stats.record(sent_to.iter().map(|mail| mail.as_str()));
+    send_stats.record_free_addresses(free_addresses.iter().map(|mail| mail.as_str()));
+    send_stats.save(app.clone());
+    drop(send_stats);
+
+    record_history(
+        app,
+        HistoryEntry {
+            sent_by,
+            resent_from,
+            message_hash: Some(sha256_hex(&message)),
+            ..HistoryEntry::new(HistoryOutcome::Sent, sent_to, subject, files)
+        },
+        &file_paths,
+    );
+
+    reset_sender(&app_state);
+
+    html! {
+        (render_truck(true))
+        (sender_button_reload())
+    }
     .into_string()
 }
 
+//main screen shows what is in MailSender and OtherMailList again
+pub fn reload_selection(app: &tauri::AppHandle) -> Markup {
+    let text = file_picker_text(&app.state::<AppState>().mail.lock().file_names());
+
+    let app_emit = app.clone();
+    thread::spawn(move || {
+        app_emit
+            .emit("file_picker_text", text)
+            .unwrap_or_else(|_| error_showing_file_name(app_emit.clone()));
+    });
+
+    html! {
+        div
+        hx-trigger="load delay:1ms"
+        hx-swap="innerHTML"
+        hx-target="#category-sections"
+        hx-post="command:load_categories"
+        {}
+        div
+        hx-trigger="load delay:1ms"
+        hx-swap="innerHTML"
+        hx-target=".right-buttons"
+        hx-post="command:load_other_buttons"
+        {}
+        (sender_button_reload())
+    }
+}
+
+fn countdown(app_state: &AppState) -> Markup {
+    let remaining = app_state
+        .pending_send
+        .lock()
+        .as_ref()
+        .map(|pending| pending.send_at.saturating_duration_since(Instant::now()));
+
+    html! {
+        @match remaining {
+            Some(remaining) if !remaining.is_zero() => {
+                p.overlay-error-subtitle #send-countdown
+                hx-post="command:send_countdown"
+                hx-trigger="every 1s"
+                hx-swap="outerHTML"
+                {(format!("odeslání za {} s", remaining.as_secs_f32().ceil()))}
+            }
+            _ => {
+                p.overlay-error-subtitle #send-countdown{("odesílám...")}
+            }
+        }
+    }
+}
+
+#[tauri::command]
+pub fn open_send_countdown(app: tauri::AppHandle) -> String {
+    let app_state = app.state::<AppState>();
+
+    html! {
+        div .overlay .most-top #send-error-overlay{
+            div .overlay-error-window{
+                h1.overlay-error-title{("E-mail bude odeslán")}
+                (countdown(&app_state))
+                div.bottom-button-row{
+                    button.remove-button
+                    hx-post="command:undo_send"
+                    hx-trigger="click"
+                    hx-target="#send-error-overlay"
+                    hx-swap="outerHTML"
+                    {("vrátit zpět")}
+                }
+            }
+        }
+    }
+    .into_string()
+}
+
+#[tauri::command]
+pub fn send_countdown(app: tauri::AppHandle) -> String {
+    countdown(&app.state::<AppState>()).into_string()
+}
+
+//called by the timer of the send, nothing is sent when undo already took it
+//send_at tells apart a newer pending send made after an undo
+fn finish_send(app: &tauri::AppHandle, send_at: Instant) {
+    let app_state = app.state::<AppState>();
+
+    let mut pending_send = app_state.pending_send.lock();
+    if pending_send.as_ref().map(|pending| pending.send_at) != Some(send_at) {
+        return;
+    }
+    let Some(mut pending) = pending_send.take() else {
+        return;
+    };
+    drop(pending_send);
+
+    let other_people = pending.other_mail_list.export_other_mail_list();
+    let truck = deliver(app, &mut pending.mail, other_people, pending.config);
+
+    let markup: Markup = html! {
+        (PreEscaped(truck))
+        div.non-display
+        hx-post="command:close_send_error"
+        hx-trigger="load delay:1ms"
+        hx-target="#send-error-overlay"
+        hx-swap="outerHTML"
+        {}
+    };
+
+    let _ = app.emit("send_finished", markup.into_string());
+}
+
+#[tauri::command]
+pub fn undo_send(app: tauri::AppHandle) -> String {
+    let app_state = app.state::<AppState>();
+
+    if let Some(pending) = app_state.pending_send.lock().take() {
+        *app_state.mail.lock() = pending.mail;
+        *app_state.other_mail_list.lock() = pending.other_mail_list;
+        *app_state.current_sender.lock() = pending.sender_id;
+    }
+
+    let markup: Markup = html! {
+        div #send-error-placeholder {}
+        (reload_selection(&app))
+    };
+
+    markup.into_string()
+}
+
 #[tauri::command]
 pub fn open_send_error(text: String) -> String {
     html! {
@@ -111,33 +635,241 @@ pub fn open_send_error(text: String) -> String {
     .into_string()
 }
 
+//external addresses are listed again here, the overlay is modal so they can't change meanwhile
+fn external_recipients(app_state: &AppState) -> Vec<String> {
+    let config = app_state.config.lock().clone();
+
+    app_state
+        .mail
+        .lock()
+        .recipients()
+        .iter()
+        .map(|recipient| recipient.mail.to_string())
+        .chain(
+            app_state
+                .other_mail_list
+                .lock()
+                .export_other_mail_list()
+                .into_iter()
+                .map(|person| person.mail),
+        )
+        .filter(|mail| config.recipient_domain(mail) == RecipientDomain::External)
+        .collect()
+}
+
+#[tauri::command]
+pub fn open_external_confirmation(app: tauri::AppHandle) -> String {
+    let app_state = app.state::<AppState>();
+
+    let external = external_recipients(&app_state);
+
+    html! {
+        div .overlay .most-top #send-error-overlay{
+            div .overlay-error-window{
+                button.close-button
+                hx-post="command:close_send_error"
+                hx-trigger="click"
+                hx-target="#send-error-overlay"
+                hx-swap="outerHTML"
+                {("X")}
+                h1.overlay-error-title{("E-mail odchází mimo firmu")}
+                h2.overlay-error-subtitle{("Potvrďte prosím, že zprávu se zákaznickými údaji chcete poslat na:")}
+                div.external-recipients{
+                    @for mail in &external {
+                        p.external-recipient{(mail)}
+                    }
+                }
+                div.bottom-button-row{
+                    button.add-button
+                    hx-post="command:confirm_external_recipients"
+                    hx-trigger="click"
+                    hx-target="#send-error-overlay"
+                    hx-swap="outerHTML"
+                    {("potvrdit a odeslat")}
+                    button.remove-button
+                    hx-post="command:close_send_error"
+                    hx-trigger="click"
+                    hx-target="#send-error-overlay"
+                    hx-swap="outerHTML"
+                    {("zrušit")}
+                }
+            }
+        }
+    }
+    .into_string()
+}
+
 #[tauri::command]
-pub fn close_send_error() -> String {
+pub fn confirm_external_recipients(app: tauri::AppHandle) -> String {
+    let app_state = app.state::<AppState>();
+
+    let external = external_recipients(&app_state);
+    *app_state.confirmed_external.lock() = external;
+
+    send_confirmed()
+}
+
+//"1,2 MB"
+pub fn format_size(bytes: u64) -> String {
+    let size = if bytes >= 1024 * 1024 {
+        format!("{:.1} MB", bytes as f64 / (1024.0 * 1024.0))
+    } else {
+        format!("{:.1} kB", bytes as f64 / 1024.0)
+    };
+
+    size.replace('.', ",")
+}
+
+#[tauri::command]
+pub fn open_send_summary(app: tauri::AppHandle) -> String {
+    let app_state = app.state::<AppState>();
+    let (_, config) = send_config(&app_state);
+    let mail = app_state.mail.lock();
+    let other_people = app_state.other_mail_list.lock().export_other_mail_list();
+
+    let recipients: Vec<(String, String)> = mail
+        .recipients()
+        .iter()
+        .map(|recipient| (recipient.name.clone(), recipient.mail.to_string()))
+        .chain(
+            other_people
+                .iter()
+                .map(|person| (person.name.clone(), person.mail.clone())),
+        )
+        .collect();
+
+    let attachments = mail.attachments();
+
+    //whole message with encoded attachments, as it goes to the server
+    let total_size = mail
+        .build_message(other_people, &config, app.clone())
+        .map(|message| format_size(message.formatted().len() as u64))
+        .unwrap_or_else(|_| "?".to_string());
+
+    html! {
+        div .overlay .most-top #send-error-overlay{
+            div .overlay-error-window{
+                button.close-button
+                hx-post="command:close_send_error"
+                hx-trigger="click"
+                hx-target="#send-error-overlay"
+                hx-swap="outerHTML"
+                {("X")}
+                h1.overlay-error-subtitle{("zkontrolujte prosím zprávu před odesláním")}
+                div.send-summary{
+                    h2.send-summary-title{("příjemci")}
+                    @for (name, mail) in &recipients {
+                        p.send-summary-row.external-recipient[config.recipient_domain(mail) == RecipientDomain::External]{
+                            @if name.is_empty() || name == mail {
+                                (mail)
+                            } @else {
+                                (format!("{name} <{mail}>"))
+                            }
+                        }
+                    }
+                    h2.send-summary-title{("přílohy")}
+                    @for attachment in &attachments {
+                        p.send-summary-row{
+                            (format!("{} - {}", attachment.name, format_size(attachment.size)))
+                            @if let Some(pages) = attachment.pages {
+                                (format!(", stran: {pages}"))
+                            }
+                        }
+                    }
+                    h2.send-summary-title{("předmět")}
+                    p.send-summary-row{(config.title())}
+                    h2.send-summary-title{("text zprávy")}
+                    p.send-summary-row.send-summary-body{
+                        (mail.body_text(&config).unwrap_or_else(|| "bez textu - pouze přílohy".to_string()))
+                    }
+                    h2.send-summary-title{(format!("celková velikost: {total_size}"))}
+                }
+                div.bottom-button-row{
+                    button.add-button
+                    hx-post="command:confirm_send_summary"
+                    hx-trigger="click"
+                    hx-target="#send-error-overlay"
+                    hx-swap="outerHTML"
+                    {("odeslat")}
+                    button.remove-button
+                    hx-post="command:close_send_error"
+                    hx-trigger="click"
+                    hx-target="#send-error-overlay"
+                    hx-swap="outerHTML"
+                    {("zpět")}
+                }
+            }
+        }
+    }
+    .into_string()
+}
+
+//external recipients were listed in the summary, so they are confirmed as well
+#[tauri::command]
+pub fn confirm_send_summary(app: tauri::AppHandle) -> String {
+    let app_state = app.state::<AppState>();
+
+    let external = external_recipients(&app_state);
+    *app_state.confirmed_external.lock() = external;
+    *app_state.send_summary_confirmed.lock() = true;
+
+    send_confirmed()
+}
+
+//overlay is closed and sending is started again, now without confirmation
+fn send_confirmed() -> String {
     let markup: Markup = html! {
         div #send-error-placeholder {}
+        div
+        hx-post="command:send"
+        hx-trigger="load delay:1ms"
+        hx-target="input.truck"
+        hx-swap="outerHTML"
+        {}
     };
 
     markup.into_string()
 }
 
+//closing any overlay of the sending ends "odeslat později" too
 #[tauri::command]
-pub fn load_mechanics(app: tauri::AppHandle) -> String {
+pub fn close_send_error(app: tauri::AppHandle) -> String {
+    *app.state::<AppState>().schedule_at.lock() = None;
+
+    let markup: Markup = html! {
+        div #send-error-placeholder {}
+    };
+
+    markup.into_string()
+}
+
+#[tauri::command]
+pub fn load_categories(app: tauri::AppHandle) -> String {
     let app_state = app.state::<AppState>();
 
+    let search = app_state.recipient_search.lock().trim().to_lowercase();
+    if !search.is_empty() {
+        return render_search_results(&app_state, &search).into_string();
+    }
+
     let mail_list = app_state.mail_list.lock();
+    let issues = app_state.integrity_issues.lock();
+    let mail = app_state.mail.lock();
 
     let markup: Markup = html! {
-        @for i in 0..24 {
-            @if let Some(mechanic) = mail_list.load_person(i){
-                button.middle-button
-                hx-trigger="click"
-                hx-post="command:add_person"
-                hx-swap="outerHTML"
-                hx-vals={(format!(r#""id": {i}"#))}
-                {(mechanic.name)}
-            }
-            @else{
-                button.middle-button.placeholder{}
+        @for category in mail_list.categories() {
+            div.category-section{
+                h2.category-title{(category.name)}
+                div.category-buttons{
+                    @for contact in category.contacts.iter().filter(|c| !c.name.trim().is_empty() && c.active) {
+                        (person_button(
+                            contact,
+                            is_contact_picked(&mail, contact),
+                            is_contact_listed(&mail, contact),
+                            &issues,
+                        ))
+                    }
+                }
             }
         }
     };
@@ -145,27 +877,77 @@ pub fn load_mechanics(app: tauri::AppHandle) -> String {
     markup.into_string()
 }
 
+//matches in name, any address or role, the most used people first
+fn render_search_results(app_state: &AppState, search: &str) -> Markup {
+    let mail_list = app_state.mail_list.lock();
+    let issues = app_state.integrity_issues.lock();
+    let mail = app_state.mail.lock();
+    let send_stats = app_state.send_stats.lock();
+
+    let mut results: Vec<(&Contact, u32)> = mail_list
+        .contacts()
+        .filter(|contact| contact.active)
+        .filter(|contact| {
+            contact.name.to_lowercase().contains(search)
+                || contact.role.to_lowercase().contains(search)
+                || contact
+                    .addresses
+                    .iter()
+                    .any(|address| address.mail.to_lowercase().contains(search))
+        })
+        .map(|contact| {
+            let sent = contact
+                .addresses
+                .iter()
+                .map(|address| send_stats.count(&address.mail))
+                .sum();
+            (contact, sent)
+        })
+        .collect();
+
+    results.sort_by(|(a, a_sent), (b, b_sent)| b_sent.cmp(a_sent).then(a.name.cmp(&b.name)));
+
+    html! {
+        div.category-section{
+            h2.category-title{("výsledky hledání")}
+            div.category-buttons.search-results{
+                @for (contact, _) in &results {
+                    (person_button(
+                        contact,
+                        is_contact_picked(&mail, contact),
+                        is_contact_listed(&mail, contact),
+                        &issues,
+                    ))
+                }
+            }
+            @if results.is_empty() {
+                p.search-no-results{("nikdo nenalezen")}
+            }
+        }
+    }
+}
+
+#[tauri::command]
+pub fn search_recipients(app: tauri::AppHandle, text: String) -> String {
+    *app.state::<AppState>().recipient_search.lock() = text;
+
+    load_categories(app)
+}
+
 #[tauri::command]
-pub fn load_technics(app: tauri::AppHandle) -> String {
+pub fn load_other_buttons(app: tauri::AppHandle) -> String {
     let app_state = app.state::<AppState>();
 
     let mail_list = app_state.mail_list.lock();
+    let mail = app_state.mail.lock();
+
+    let other_filled = app_state.other_mail_list.lock().is_filled();
 
     let markup: Markup = html! {
-        @for i in 24..29 {
-            @if let Some(technic) = mail_list.load_person(i){
-                button.middle-button
-                hx-trigger="click"
-                hx-post="command:add_person"
-                hx-swap="outerHTML"
-                hx-vals={(format!(r#""id": {i}"#))}
-                {(technic.name)}
-            }
-            @else{
-                button.middle-button.placeholder{}
-            }
+        @for list in mail_list.distribution_lists() {
+            (distribution_list_button(list.id, &list.name, mail.is_distribution_list_selected(list.id)))
         }
-        button.middle-button #other-mails-button
+        button.middle-button.clicked[other_filled] #other-mails-button
         hx-post="command:open_other"
         hx-trigger="click"
         hx-target="#overlay-other-placeholder"
@@ -183,20 +965,18 @@ pub fn add_person(id: String, app: tauri::AppHandle) -> String {
         .unwrap_or_else(|_| error_id_parse(app.clone(), id));
     let app_state = app.state::<AppState>();
 
-    if let Some(person) = app_state.mail_list.lock().load_person(id) {
-        app_state
-            .mail
-            .lock()
-            .add_person(person.clone(), app.clone());
+    if let Some(contact) = app_state.mail_list.lock().load_contact(id) {
+        let mut mail = app_state.mail.lock();
+        mail.add_person(contact.person(), app.clone());
 
-        let markup: Markup = html! {
-            button.middle-button.clicked
-                hx-trigger="click"
-                hx-post="command:remove_person"
-                hx-swap="outerHTML"
-                hx-vals={(format!(r#""id": {id}"#))}
-            {(person.name)}
-        };
+        tutorial_act(&app, TutorialAction::PickPerson);
+
+        let markup: Markup = person_button(
+            &contact,
+            true,
+            is_contact_listed(&mail, &contact),
+            &app_state.integrity_issues.lock(),
+        );
 
         return markup.into_string();
     }
@@ -211,20 +991,19 @@ pub fn remove_person(id: String, app: tauri::AppHandle) -> String {
         .unwrap_or_else(|_| error_id_parse(app.clone(), id));
     let app_state = app.state::<AppState>();
 
-    if let Some(person) = app_state.mail_list.lock().load_person(id) {
-        app_state
-            .mail
-            .lock()
-            .remove_person(person.clone(), app.clone());
+    if let Some(contact) = app_state.mail_list.lock().load_contact(id) {
+        //whichever address was picked is removed
+        let mut mail = app_state.mail.lock();
+        for person in contact.people() {
+            mail.remove_person(person);
+        }
 
-        let markup: Markup = html! {
-            button.middle-button
-                hx-trigger="click"
-                hx-post="command:add_person"
-                hx-swap="outerHTML"
-                hx-vals={(format!(r#""id": {id}"#))}
-            {(person.name)}
-        };
+        let markup: Markup = person_button(
+            &contact,
+            false,
+            is_contact_listed(&mail, &contact),
+            &app_state.integrity_issues.lock(),
+        );
 
         return markup.into_string();
     }
@@ -232,35 +1011,149 @@ pub fn remove_person(id: String, app: tauri::AppHandle) -> String {
     error_load_person(app, id)
 }
 
+#[tauri::command]
+pub fn open_address_picker(id: String, app: tauri::AppHandle) -> String {
+    let id: usize = id
+        .parse()
+        .unwrap_or_else(|_| error_id_parse(app.clone(), id));
+    let app_state = app.state::<AppState>();
+
+    let Some(contact) = app_state.mail_list.lock().load_contact(id) else {
+        return error_load_person(app, id);
+    };
+
+    let mail = app_state.mail.lock();
+
+    let picked: Vec<usize> = contact
+        .people()
+        .iter()
+        .filter(|person| mail.is_picked(person))
+        .filter_map(|person| {
+            contact
+                .addresses
+                .iter()
+                .position(|address| address.mail == person.mail)
+        })
+        .collect();
+
+    let markup: Markup = html! {
+        div #overlay-address-picker .overlay .most-top
+        {
+            div.overlay-window
+            {
+                button.close-button
+                hx-post="command:close_address_picker"
+                hx-trigger="click"
+                hx-target="#overlay-address-picker"
+                hx-swap="outerHTML"
+                {("X")}
+                h1.overlay-title{(format!("{} - vyberte E-mail", contact.name))}
+                div.address-picker-buttons{
+                    @for (index, address) in contact.addresses.iter().enumerate() {
+                        button.middle-button.address-picker-option.clicked[picked.contains(&index)]
+                        hx-post="command:pick_person_address"
+                        hx-trigger="click"
+                        hx-target="#overlay-address-picker"
+                        hx-swap="outerHTML"
+                        hx-vals={(format!(r#""id": {id}, "index": {index}"#))}
+                        {
+                            (format!("{} - {}", address.label, address.mail))
+                            @if index == contact.primary {
+                                (" (primární)")
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    };
+
+    markup.into_string()
+}
+
+#[tauri::command]
+pub fn close_address_picker() -> String {
+    let markup: Markup = html! {
+        div #address-picker-placeholder {}
+    };
+
+    markup.into_string()
+}
+
+//only one address of the person is picked at a time
+#[tauri::command]
+pub fn pick_person_address(id: String, index: String, app: tauri::AppHandle) -> String {
+    let id: usize = id
+        .parse()
+        .unwrap_or_else(|_| error_id_parse(app.clone(), id));
+    let index: usize = index
+        .parse()
+        .unwrap_or_else(|_| error_id_parse(app.clone(), index));
+    let app_state = app.state::<AppState>();
+
+    let Some(contact) = app_state.mail_list.lock().load_contact(id) else {
+        return error_load_person(app, id);
+    };
+
+    if let Some(address) = contact.addresses.get(index) {
+        let mut mail = app_state.mail.lock();
+
+        for person in contact.people() {
+            mail.remove_person(person);
+        }
+        mail.add_person(
+            Person {
+                name: contact.name.clone(),
+                mail: address.mail.clone(),
+            },
+            app.clone(),
+        );
+    }
+
+    let markup: Markup = html! {
+        div #address-picker-placeholder {}
+        div
+        hx-trigger="load delay:1ms"
+        hx-swap="innerHTML"
+        hx-target="#category-sections"
+        hx-post="command:load_categories"
+        {}
+    };
+
+    markup.into_string()
+}
+
+fn file_picker_text(file_names: &[String]) -> String {
+    let file_picker_text = match file_names {
+        [] => "výběr souborů".to_string(),
+        [file_name] => format!("vybráno: {file_name}"),
+        _ if file_names.len() < 5 => format!("vybrány {} soubory", file_names.len()),
+        _ => format!("vybráno {} souborů", file_names.len()),
+    };
+
+    if file_picker_text.len() > 28 {
+        let shortened_text: String = file_picker_text.chars().take(27).collect();
+        format!("{shortened_text}...")
+    } else {
+        file_picker_text
+    }
+}
+
 #[tauri::command]
 pub fn pick_file(app: tauri::AppHandle) {
     app.dialog().file().pick_files(move |file_path| {
         let app_state = app.state::<AppState>();
 
         if let Some(path) = file_path {
-            if app_state.mail.lock().add_file(path.clone()).is_err() {
+            let added = app_state.mail.lock().add_file(path);
+
+            if added.is_err() {
                 error_pick_file(app.clone());
+            } else {
+                tutorial_act(&app, TutorialAction::PickFile);
             }
 
-            let mut file_picker_text = match path.len() {
-                1 => {
-                    let path_string = path.first().expect("path should exist").to_string();
-
-                    let file_name = path_string
-                        .split_terminator("/")
-                        .last()
-                        .expect("file name should exist");
-
-                    format!("vybráno: {file_name}")
-                }
-                2..5 => format!("vybrány {} soubory", path.len()),
-                _ => format!("vybráno {} souborů", path.len()),
-            };
-
-            if file_picker_text.len() > 28 {
-                let shortened_text: String = file_picker_text.chars().take(27).collect();
-                file_picker_text = format!("{shortened_text}...");
-            }
+            let file_picker_text = file_picker_text(&app_state.mail.lock().file_names());
 
             thread::spawn(move || {
                 app.emit("file_picker_text", file_picker_text)
@@ -18,16 +18,37 @@ pub fn open_manual() -> String {
                     li{("Vyberte přjemce (možné vybrat více)")}
                     ol{
                         li{("Kliknutím na jméno ve výběru")}
+                        li{("Vyhledáním v poli nad výběrem - šipkami se lze pohybovat mezi nalezenými, Enter vybere")}
+                        li{("U osob s více E-maily lze tlačítkem \"@\" vybrat, na který E-mail se odešle")}
+                        li{("Kliknutím na distribuční seznam vpravo - vybere všechny jeho členy najednou")}
                         li{("Kliknutím na \"Ostatní...\"")}
                         ol{
                             li{("Kliknutím na \"přidat další E-mail\"")}
                             li{("Zadáním E-mailu do nově přidaného pole")}
+                            li{("Lze vložit i více E-mailů najednou, např. \"Jan Novák <jan@firma.cz>; petr@firma.cz\" - rozdělí se do více řádků")}
+                            li{("Při psaní se nabízejí dříve použité E-maily, často používané lze uložit jako kontakt")}
                             li{("V případě potřeby lze pole smazat tlačítkem \"smazat\"")}
+                            li{("Chybný E-mail je označen červeně i s důvodem chyby")}
+                            li{("U možného překlepu v doméně (např. seznam.cs) se nabídne oprava - klikněte na \"opravit\", nebo \"ponechat\", pokud je E-mail správně")}
                             li{("Po zadání všech E-mailů můžete okno standardně zavřít křížkem")}
+                            li{("Okno nelze zavřít s chybnými E-maily - opravte je, nebo klikněte na \"zahodit neplatné a zavřít\"")}
                         }
                     }
+                    li{("Pro častá odeslání klikněte na předvolbu nad vyhledáváním - vybere příjemce, odesílatele, předmět i text, zbývá vybrat soubor")}
                     li{("Vyberte soubor k odeslání (možné vybrat více)")}
+                    li{("Klikněte na \"kdo odesílá?\" a vyberte své jméno, nebo zadejte svůj PIN - odpovědi zákazníků pak přijdou přímo vám")}
+                    li{("Pokud je nastaveno více profilů, odesílatele lze změnit v nabídce \"odesílatel\" - jinak se zvolí podle kategorie příjemců")}
                     li{("Klikněte na odeslat")}
+                    li{("Pokud E-mail odchází mimo firmu, potvrďte seznam externích adres tlačítkem \"potvrdit a odeslat\"")}
+                    li{("Na zakázané domény nelze odeslat vůbec")}
+                    li{("Pokud je zapnutý souhrn, zkontrolujte příjemce, přílohy a předmět a klikněte na \"odeslat\", nebo se vraťte tlačítkem \"zpět\"")}
+                    li{("Pokud je nastavený čas na zrušení, E-mail se odešle až po odpočtu - do té doby lze kliknout na \"vrátit zpět\" a výběr se obnoví")}
+                    li{("Pro odeslání později klikněte na \"odeslat později\", zvolte čas a klikněte na \"naplánovat\"")}
+                    li{("V seznamu \"naplánované\" lze čas změnit, E-mail odeslat hned, nebo ho zrušit - E-maily zmeškané během vypnuté aplikace se neodešlou samy")}
+                    li{("Pokud je vyžadováno schválení, E-mail se neodešle hned - čeká, až ho vedoucí schválí v nastavení")}
+                    li{("V seznamu \"odeslané\" lze E-mail \"odeslat znovu\" stejným příjemcům, nebo ho \"přeposlat\" na nové adresy - přílohy se vezmou z archivu a výběr se dá před odesláním upravit")}
+                    li{("Tlačítko \"průvodce\" nahoře vás provede odesláním krok za krokem - zvýrazní, kam kliknout, a pokračuje, jakmile to uděláte")}
+                    li{("E-mail odeslaný v průvodci se nikam neodešle, uloží se jen do zkušební složky")}
                 }
             }
         }
@@ -61,10 +82,66 @@ pub fn open_settings_manual() -> String {
                     ol.manual-text{
                         li{("Vyberte osobu ke změně nebo smazání údajů")}
                         ol{
-                            li{("Přidání osoby - Přidejte jméno a E-mail vybrané osoby")}
+                            li{("Přidání osoby - Klikněte na \"+ přidat osobu\" v kategorii a vyplňte jméno a E-mail")}
                             li{("Úprava osoby - Upravte jméno nebo E-mail vybrané osoby")}
+                            li{("Celý kontakt - Další E-maily s označením a volbou primárního, telefon, funkce, PIN, poznámky a aktivní / neaktivní osoba")}
+                            li{("Při možném překlepu v doméně E-mailu nabídne celý kontakt opravu")}
                             li{("Smazání osoby - Smažte jméno osoby")}
                         }
+                        li{("Vyberte kategorii kliknutím na její název")}
+                        ol{
+                            li{("Přidání kategorie - Klikněte na \"+ přidat kategorii\"")}
+                            li{("Úprava kategorie - Přejmenujte ji nebo ji posuňte nahoru či dolů")}
+                            li{("Smazání kategorie - Klikněte na \"smazat kategorii i s osobami\"")}
+                        }
+                        li{("Import a export kontaktů - Klikněte na \"import / export kontaktů\"")}
+                        ol{
+                            li{("Import - Vyberte soubor CSV nebo vCard, u CSV zvolte sloupce se jménem, E-mailem a kategorií")}
+                            li{("Zkontrolujte náhled nových a změněných osob, konflikty se neimportují")}
+                            li{("Klikněte na \"použít import\" a změny uložte tlačítkem \"uložit a zavřít\"")}
+                            li{("Export - Klikněte na \"exportovat CSV\" nebo \"exportovat vCard\" a vyberte kam soubor uložit")}
+                        }
+                        li{("Distribuční seznamy upravíte v pravém sloupci")}
+                        ol{
+                            li{("Přidání seznamu - Klikněte na \"+ přidat seznam\"")}
+                            li{("Úprava seznamu - Klikněte na jeho název, vyberte členy a případně doplňte další E-maily")}
+                            li{("Smazání seznamu - Klikněte na \"smazat seznam\"")}
+                        }
+                        li{("Pravidla pro domény příjemců nastavíte v konfiguraci")}
+                        ol{
+                            li{("Interní domény - E-maily na ně odcházejí bez potvrzení")}
+                            li{("Povolené externí domény - pokud jsou vyplněné, mimo firmu lze posílat jen na ně")}
+                            li{("Zakázané domény - na ně nelze odeslat nikdy")}
+                            li{("Souhrn před odesláním - nezobrazovat, jen při externích příjemcích, nebo vždy")}
+                            li{("Čas na zrušení odeslání - kolik sekund lze odeslání vrátit zpět, 0 - odešle se hned")}
+                            li{("Schválení vedoucím - nevyžadovat, jen při externích příjemcích, nebo vždy")}
+                            li{("Schválení vždy pro funkce - např. učeň, E-maily osob s touto funkcí vždy čekají na schválení")}
+                            li{("Podpis - text pod jménem mechanika, prázdný - jméno odesilatele")}
+                            li{("Profily odesilatele - další schránky (např. servis, záruky, díly) s vlastním SMTP, předmětem a podpisem")}
+                            li{("V předmětu lze použít {mechanik} a {datum}, vyplní se při odeslání")}
+                            li{("Výchozí profil podle kategorie - profil, který se zvolí, když je vybrán někdo z kategorie")}
+                        }
+                        li{("E-maily ke schválení - Klikněte na \"ke schválení\" a vyplňte své jméno")}
+                        ol{
+                            li{("Přílohy si před schválením prohlédněte tlačítkem \"otevřít\"")}
+                            li{("Klikněte na \"schválit a odeslat\", nebo vyplňte poznámku a klikněte na \"zamítnout\"")}
+                            li{("Pod seznamem je historie odeslaných a zamítnutých E-mailů i s tím, kdo je schválil")}
+                            li{("\"ověřit historii\" - zkontroluje, že žádný záznam nebyl dodatečně změněn nebo smazán")}
+                            li{("\"doklad\" - uloží textový doklad o odeslání s otisky (SHA-256) E-mailu a příloh")}
+                        }
+                        li{("Předvolby - Klikněte na \"předvolby\" a \"+ přidat předvolbu\"")}
+                        ol{
+                            li{("Vyberte příjemce, distribuční seznamy, ostatní E-maily a odesílatele")}
+                            li{("Předmět a text lze vyplnit i s {mechanik} a {datum}, prázdné pole - použije se výchozí")}
+                            li{("Předvolby se ukládají hned, na hlavní obrazovce se objeví po zavření nastavení")}
+                        }
+                        li{("Archiv - každý odeslaný E-mail je uložen celý jako .eml soubor")}
+                        ol{
+                            li{("Hledat lze podle předmětu, adres, názvů příloh i textu v PDF")}
+                            li{("E-mail lze \"otevřít\" v poštovním programu, \"exportovat\" do souboru, nebo \"odeslat znovu\" stejným příjemcům")}
+                            li{("V configu lze nastavit, kolik dní se E-maily v archivu drží, 0 - navždy")}
+                        }
+                        li{("Tlačítko \"průvodce\" provede nastavením krok za krokem")}
                         li{("Po dokončení změn")}
                         ol{
                             li{("Pro uložení změn - klikněte na \"uložit a zavřít\"")}
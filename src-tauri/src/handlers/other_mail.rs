@@ -1,7 +1,12 @@
-use maud::{html, Markup};
+use maud::{html, Markup, PreEscaped};
 use tauri::Manager;
 
+use crate::backend::domain_suggestions::DomainSuggestions;
 use crate::backend::error_handling::error_id_parse;
+use crate::backend::integrity::refresh_integrity_issues;
+use crate::backend::tutorial::TutorialAction;
+use crate::handlers::app_settings::wrong_mail_warning;
+use crate::handlers::tutorial::tutorial_act;
 use crate::AppState;
 //---------------------------
 
@@ -9,7 +14,21 @@ use crate::AppState;
 pub fn open_other(app: tauri::AppHandle) -> String {
     let app_state = app.state::<AppState>();
 
-    let markup: Markup = html! {
+    tutorial_act(&app, TutorialAction::OpenOther);
+
+    render_other_overlay(&app_state, false).into_string()
+}
+
+//has to be built before other locks are taken, it locks mail_list and send_stats
+pub fn domain_suggestions(app_state: &AppState) -> DomainSuggestions {
+    DomainSuggestions::new(&app_state.mail_list.lock(), &app_state.send_stats.lock())
+}
+
+//invalid_warning - shown when closing was refused
+fn render_other_overlay(app_state: &AppState, invalid_warning: bool) -> Markup {
+    let domains = domain_suggestions(app_state);
+
+    html! {
         div #overlay-other .overlay .most-top
         {
             div.overlay-window
@@ -21,8 +40,19 @@ pub fn open_other(app: tauri::AppHandle) -> String {
                 hx-swap="outerHTML"
                 {("X")}
                 h1.overlay-title{("zadejte prosím E-mailové adresy")}
+                @if invalid_warning {
+                    div.other-mail-warning{
+                        p.other-mail-warning-text{("Některé E-maily nejsou správně. Opravte je, nebo je zahoďte.")}
+                        button.remove-button
+                        hx-post="command:drop_invalid_and_close_other"
+                        hx-trigger="click"
+                        hx-target="#overlay-other"
+                        hx-swap="outerHTML"
+                        {("zahodit neplatné a zavřít")}
+                    }
+                }
                 div.other-mail-buttons #other-mail-buttons
-                {(app_state.other_mail_list.lock().render_input_fields())}
+                {(app_state.other_mail_list.lock().render_input_fields(&domains))}
                 div.bottom-button-row{
                     button.add-button
                     hx-post="command:add_other_mail_row"
@@ -31,9 +61,106 @@ pub fn open_other(app: tauri::AppHandle) -> String {
                     hx-swap="outerHTML"
                     {("přidat další E-mail")}
                 }
+                div.frequent-addresses #frequent-addresses{
+                    (render_frequent_addresses(app_state))
+                }
+                datalist #address-history{
+                    @for address in app_state.send_stats.lock().free_addresses() {
+                        option
+                        value=(address.mail)
+                        label=(format!("{}x, naposledy {}", address.count, address.last_used))
+                        {}
+                    }
+                }
+            }
+        }
+    }
+}
+
+//addresses used more times, that aren't contacts yet
+fn render_frequent_addresses(app_state: &AppState) -> Markup {
+    static MIN_COUNT: u32 = 3;
+    static MAX_SHOWN: usize = 5;
+
+    let mail_list = app_state.mail_list.lock();
+    let send_stats = app_state.send_stats.lock();
+
+    let frequent: Vec<_> = send_stats
+        .free_addresses()
+        .into_iter()
+        .filter(|address| address.count >= MIN_COUNT)
+        .filter(|address| {
+            !mail_list
+                .contacts()
+                .any(|contact| contact.has_mail(&address.mail))
+        })
+        .take(MAX_SHOWN)
+        .collect();
+
+    html! {
+        @if !frequent.is_empty() {
+            h2.frequent-addresses-title{("často používané - uložit jako kontakt")}
+            @for address in frequent {
+                div.other-mail-button-row{
+                    p.frequent-address{
+                        (format!("{} ({}x, naposledy {})", address.mail, address.count, address.last_used))
+                    }
+                    button.add-button
+                    hx-post="command:promote_address"
+                    hx-trigger="click"
+                    hx-target="#frequent-addresses"
+                    hx-swap="innerHTML"
+                    hx-vals={(format!(r#""mail": "{}""#, address.mail))}
+                    {("uložit jako kontakt")}
+                }
             }
         }
+    }
+}
+
+//contact is saved right away, name can be changed later in settings
+#[tauri::command]
+pub fn promote_address(app: tauri::AppHandle, mail: String) -> String {
+    static CATEGORY_NAME: &str = "ostatní kontakty";
+
+    let app_state = app.state::<AppState>();
+
+    let mail_list_save = {
+        let mut mail_list = app_state.mail_list.lock();
+
+        let category_id = match mail_list
+            .categories()
+            .iter()
+            .find(|category| category.name == CATEGORY_NAME)
+        {
+            Some(category) => category.id,
+            None => mail_list.add_category(CATEGORY_NAME.to_string()),
+        };
+
+        if let Some(id) = mail_list.add_contact(category_id) {
+            mail_list.save_person_name(id, mail.clone());
+            mail_list.save_person_mail(id, mail);
+        }
+
+        //fails only when the list was already invalid, contact stays in memory and the invalid ones are shown
+        mail_list.save_list(app.clone())
     };
+
+    refresh_integrity_issues(&app);
+
+    let markup: Markup = html! {
+        (render_frequent_addresses(&app_state))
+        @if let Err(invalid_mails) = mail_list_save {
+            (PreEscaped(wrong_mail_warning(invalid_mails)))
+        }
+        div
+        hx-trigger="load delay:1ms"
+        hx-swap="innerHTML"
+        hx-target="#category-sections"
+        hx-post="command:load_categories"
+        {}
+    };
+
     markup.into_string()
 }
 
@@ -50,7 +177,11 @@ pub fn add_other_mail_row(app: tauri::AppHandle) -> String {
             hx-post="command:edit_mail"
             name="text"
             hx-trigger="change"
+            hx-target="#other-mail-buttons"
+            hx-swap="innerHTML"
             placeholder="Zadejte prosím E-mail"
+            list="address-history"
+            autocomplete="off"
             hx-vals={(format!(r#""index": {index}"#))}
             {}
             button.remove-button
@@ -72,14 +203,55 @@ pub fn add_other_mail_row(app: tauri::AppHandle) -> String {
 }
 
 #[tauri::command]
-pub fn edit_mail(app: tauri::AppHandle, index: String, text: String) {
+pub fn edit_mail(app: tauri::AppHandle, index: String, text: String) -> String {
     let app_state = app.state::<AppState>();
 
     let index: usize = index
         .parse()
         .unwrap_or_else(|_| error_id_parse(app.clone(), index));
 
-    app_state.other_mail_list.lock().edit_person(&text, index);
+    let domains = domain_suggestions(&app_state);
+
+    let mut other_mail_list = app_state.other_mail_list.lock();
+
+    //pasted list can add more rows
+    other_mail_list.edit_person(&text, index);
+
+    other_mail_list.render_input_fields(&domains).into_string()
+}
+
+#[tauri::command]
+pub fn apply_mail_suggestion(app: tauri::AppHandle, index: String) -> String {
+    let app_state = app.state::<AppState>();
+
+    let index: usize = index
+        .parse()
+        .unwrap_or_else(|_| error_id_parse(app.clone(), index));
+
+    let domains = domain_suggestions(&app_state);
+
+    let mut other_mail_list = app_state.other_mail_list.lock();
+
+    other_mail_list.apply_suggestion(index, &domains);
+
+    other_mail_list.render_input_fields(&domains).into_string()
+}
+
+#[tauri::command]
+pub fn keep_mail(app: tauri::AppHandle, index: String) -> String {
+    let app_state = app.state::<AppState>();
+
+    let index: usize = index
+        .parse()
+        .unwrap_or_else(|_| error_id_parse(app.clone(), index));
+
+    let domains = domain_suggestions(&app_state);
+
+    let mut other_mail_list = app_state.other_mail_list.lock();
+
+    other_mail_list.keep_mail(index);
+
+    other_mail_list.render_input_fields(&domains).into_string()
 }
 
 #[tauri::command]
@@ -91,7 +263,12 @@ pub fn remove_other_row(app: tauri::AppHandle, index: String) -> String {
 
     app_state.other_mail_list.lock().remove_person(index);
 
-    let markup: Markup = app_state.other_mail_list.lock().render_input_fields();
+    let domains = domain_suggestions(&app_state);
+
+    let markup: Markup = app_state
+        .other_mail_list
+        .lock()
+        .render_input_fields(&domains);
 
     markup.into_string()
 }
@@ -102,8 +279,15 @@ pub fn close_other(app: tauri::AppHandle) -> String {
 
     app_state.other_mail_list.lock().remove_empty_persons();
 
+    //invalid rows have to be fixed or dropped explicitly
+    if !app_state.other_mail_list.lock().is_valid() {
+        return render_other_overlay(&app_state, true).into_string();
+    }
+
     let has_other_mails = !app_state.other_mail_list.lock().is_empty();
 
+    tutorial_act(&app, TutorialAction::CloseOther);
+
     let markup: Markup = html! {
         @if has_other_mails {
             div #overlay-other-placeholder {}
@@ -128,6 +312,15 @@ pub fn close_other(app: tauri::AppHandle) -> String {
     markup.into_string()
 }
 
+#[tauri::command]
+pub fn drop_invalid_and_close_other(app: tauri::AppHandle) -> String {
+    let app_state = app.state::<AppState>();
+
+    app_state.other_mail_list.lock().remove_invalid_persons();
+
+    close_other(app)
+}
+
 #[tauri::command]
 pub fn mark_other() -> String {
     let markup: Markup = html! {
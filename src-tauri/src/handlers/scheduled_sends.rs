@@ -0,0 +1,250 @@
+use std::thread;
+
+use chrono::{Days, Local, NaiveDateTime, NaiveTime};
+use maud::{html, Markup};
+use tauri::Manager;
+
+use crate::backend::error_handling::error_id_parse;
+use crate::backend::scheduled_sends::{
+    begin_dispatch, deliver, ScheduleState, ScheduledSends, SCHEDULE_FORMAT,
+};
+use crate::handlers::tutorial::tutorial_running;
+use crate::AppState;
+
+//---------------------------
+
+//workshop opens at seven, so the next morning is offered first
+fn default_send_at() -> NaiveDateTime {
+    let tomorrow = Local::now()
+        .date_naive()
+        .checked_add_days(Days::new(1))
+        .unwrap_or(Local::now().date_naive());
+
+    tomorrow.and_time(NaiveTime::from_hms_opt(7, 0, 0).unwrap_or_default())
+}
+
+fn render_schedule_overlay(send_at: NaiveDateTime, warning: bool) -> Markup {
+    html! {
+        div .overlay .most-top #send-error-overlay{
+            div .overlay-error-window{
+                button.close-button
+                hx-post="command:close_send_error"
+                hx-trigger="click"
+                hx-target="#send-error-overlay"
+                hx-swap="outerHTML"
+                {("X")}
+                h1.overlay-error-subtitle{("kdy se má E-mail odeslat?")}
+                input.schedule-input
+                type="datetime-local"
+                name="text"
+                hx-post="command:save_schedule_time"
+                hx-trigger="change"
+                hx-swap="none"
+                value=(send_at.format(SCHEDULE_FORMAT))
+                {}
+                @if warning {
+                    p.other-mail-error{("Zvolte prosím čas v budoucnosti.")}
+                }
+                div.bottom-button-row{
+                    button.add-button
+                    hx-post="command:schedule_send"
+                    hx-trigger="click"
+                    hx-target="#send-error-overlay"
+                    hx-swap="outerHTML"
+                    {("naplánovat")}
+                    button.remove-button
+                    hx-post="command:close_send_error"
+                    hx-trigger="click"
+                    hx-target="#send-error-overlay"
+                    hx-swap="outerHTML"
+                    {("zrušit")}
+                }
+            }
+        }
+    }
+}
+
+#[tauri::command]
+pub fn open_schedule_send(app: tauri::AppHandle) -> String {
+    let app_state = app.state::<AppState>();
+
+    let send_at = default_send_at();
+    *app_state.schedule_at.lock() = Some(send_at);
+
+    render_schedule_overlay(send_at, false).into_string()
+}
+
+#[tauri::command]
+pub fn save_schedule_time(app: tauri::AppHandle, text: String) {
+    let app_state = app.state::<AppState>();
+
+    *app_state.schedule_at.lock() = NaiveDateTime::parse_from_str(&text, SCHEDULE_FORMAT).ok();
+}
+
+//the message goes through the same checks as sending right away,
+//send stores it into the queue when it finds the time set
+#[tauri::command]
+pub fn schedule_send(app: tauri::AppHandle) -> String {
+    let app_state = app.state::<AppState>();
+
+    let send_at = *app_state.schedule_at.lock();
+
+    match send_at {
+        Some(send_at) if send_at > Local::now().naive_local() => html! {
+            div #send-error-placeholder {}
+            div
+            hx-post="command:send"
+            hx-trigger="load delay:1ms"
+            hx-target="input.truck"
+            hx-swap="outerHTML"
+            {}
+        }
+        .into_string(),
+        _ => {
+            let send_at = default_send_at();
+            *app_state.schedule_at.lock() = Some(send_at);
+
+            render_schedule_overlay(send_at, true).into_string()
+        }
+    }
+}
+
+//---------------------------list of scheduled sends
+
+//sending_allowed - false while the tutorial runs, its sends go only to the sandbox
+fn render_scheduled_sends(scheduled_sends: &ScheduledSends, sending_allowed: bool) -> Markup {
+    html! {
+        @if !sending_allowed {
+            p.send-summary-row{("během průvodce se nic neodesílá, ukončete ho pro odeslání")}
+        }
+        @if scheduled_sends.sends().is_empty() {
+            p.send-summary-row{("žádné naplánované E-maily")}
+        }
+        @for send in scheduled_sends.sends() {
+            div.scheduled-send{
+                div.other-mail-button-row{
+                    input.schedule-input
+                    type="datetime-local"
+                    name="text"
+                    hx-post="command:reschedule_send"
+                    hx-trigger="change"
+                    hx-target="#scheduled-sends"
+                    hx-swap="innerHTML"
+                    hx-vals={(format!(r#""id": {}"#, send.id))}
+                    disabled[send.state == ScheduleState::Sending || !sending_allowed]
+                    value=(send.send_at)
+                    {}
+                    p.scheduled-send-state.scheduled-send-problem[matches!(send.state, ScheduleState::Missed | ScheduleState::Failed)]{
+                        (match send.state {
+                            ScheduleState::Waiting => "čeká na odeslání",
+                            ScheduleState::Sending => "odesílá se",
+                            ScheduleState::Missed => "zmeškáno - aplikace nebyla spuštěná",
+                            ScheduleState::Failed => "odeslání se nezdařilo",
+                        })
+                    }
+                    button.add-button
+                    hx-post="command:send_scheduled_now"
+                    hx-trigger="click"
+                    hx-target="#scheduled-sends"
+                    hx-swap="innerHTML"
+                    hx-vals={(format!(r#""id": {}"#, send.id))}
+                    disabled[send.state == ScheduleState::Sending]
+                    {("odeslat hned")}
+                    button.remove-button
+                    hx-post="command:cancel_scheduled_send"
+                    hx-trigger="click"
+                    hx-target="#scheduled-sends"
+                    hx-swap="innerHTML"
+                    hx-vals={(format!(r#""id": {}"#, send.id))}
+                    disabled[send.state == ScheduleState::Sending]
+                    {("zrušit")}
+                }
+                p.send-summary-row{(format!("příjemci: {}", send.recipients.join(", ")))}
+                p.send-summary-row{(format!("přílohy: {}", send.files.join(", ")))}
+                p.send-summary-row{(format!("předmět: {}", send.subject))}
+            }
+        }
+    }
+}
+
+#[tauri::command]
+pub fn open_scheduled_sends(app: tauri::AppHandle) -> String {
+    let app_state = app.state::<AppState>();
+
+    html! {
+        div .overlay .most-top #send-error-overlay{
+            div .overlay-error-window{
+                button.close-button
+                hx-post="command:close_send_error"
+                hx-trigger="click"
+                hx-target="#send-error-overlay"
+                hx-swap="outerHTML"
+                {("X")}
+                h1.overlay-error-subtitle{("naplánované E-maily")}
+                div.send-summary #scheduled-sends{
+                    (render_scheduled_sends(&app_state.scheduled_sends.lock(), !tutorial_running(&app)))
+                }
+            }
+        }
+    }
+    .into_string()
+}
+
+#[tauri::command]
+pub fn reschedule_send(app: tauri::AppHandle, id: String, text: String) -> String {
+    let id: usize = id
+        .parse()
+        .unwrap_or_else(|_| error_id_parse(app.clone(), id));
+
+    let app_state = app.state::<AppState>();
+
+    let mut scheduled_sends = app_state.scheduled_sends.lock();
+
+    //time in the past would be sent right away, so it is ignored
+    if let Ok(send_at) = NaiveDateTime::parse_from_str(&text, SCHEDULE_FORMAT) {
+        if send_at > Local::now().naive_local() {
+            scheduled_sends.reschedule(id, send_at);
+            scheduled_sends.save(app.clone());
+        }
+    }
+
+    render_scheduled_sends(&scheduled_sends, !tutorial_running(&app)).into_string()
+}
+
+#[tauri::command]
+pub fn cancel_scheduled_send(app: tauri::AppHandle, id: String) -> String {
+    let id: usize = id
+        .parse()
+        .unwrap_or_else(|_| error_id_parse(app.clone(), id));
+
+    let app_state = app.state::<AppState>();
+
+    let mut scheduled_sends = app_state.scheduled_sends.lock();
+
+    scheduled_sends.cancel(&app, id);
+    scheduled_sends.save(app.clone());
+
+    render_scheduled_sends(&scheduled_sends, !tutorial_running(&app)).into_string()
+}
+
+//sending takes a while, the list shows it as sending until it is opened again
+#[tauri::command]
+pub fn send_scheduled_now(app: tauri::AppHandle, id: String) -> String {
+    let id: usize = id
+        .parse()
+        .unwrap_or_else(|_| error_id_parse(app.clone(), id));
+
+    let app_state = app.state::<AppState>();
+    let sending_allowed = !tutorial_running(&app);
+
+    if sending_allowed {
+        if let Some(send) = begin_dispatch(&app, id) {
+            let app_deliver = app.clone();
+            thread::spawn(move || deliver(&app_deliver, send));
+        }
+    }
+
+    let markup = render_scheduled_sends(&app_state.scheduled_sends.lock(), sending_allowed);
+
+    markup.into_string()
+}
@@ -0,0 +1,251 @@
+use std::fs;
+
+use maud::{html, Markup};
+use tauri::Manager;
+use tauri_plugin_dialog::DialogExt;
+
+use crate::backend::error_handling::{error_exporting_history_proof, error_id_parse};
+use crate::backend::mail_list_utils::Person;
+use crate::backend::mail_sender::MessageTemplate;
+use crate::backend::send_history::{ChainProblem, HistoryEntry, HistoryOutcome, SendHistory};
+use crate::handlers::mail_send::reload_selection;
+use crate::AppState;
+
+//---------------------------
+
+//how many sends are shown, the file keeps all of them
+static HISTORY_SHOWN: usize = 30;
+
+//actions - resend and forward buttons, shown on the main screen
+pub fn render_send_history(send_history: &SendHistory, actions: bool) -> Markup {
+    html! {
+        @for entry in send_history.entries().take(HISTORY_SHOWN) {
+            div.scheduled-send{
+                div.other-mail-button-row{
+                    p.scheduled-send-state.scheduled-send-problem[entry.outcome == HistoryOutcome::Rejected]{
+                        (match entry.outcome {
+                            HistoryOutcome::Sent => format!("{} odesláno", entry.time),
+                            HistoryOutcome::Rejected => format!("{} zamítnuto", entry.time),
+                        })
+                        @if let Some(sent_by) = &entry.sent_by {
+                            (format!(" - odeslal(a) {sent_by}"))
+                        }
+                        @if let Some(approved_by) = &entry.approved_by {
+                            @if entry.outcome == HistoryOutcome::Rejected {
+                                (format!(", zamítl(a) {approved_by}"))
+                            } @else {
+                                (format!(", schválil(a) {approved_by}"))
+                            }
+                        }
+                    }
+                    @if actions && entry.can_resend() {
+                        button.add-button
+                        hx-post="command:resend_from_history"
+                        hx-trigger="click"
+                        hx-target="#send-error-overlay"
+                        hx-swap="outerHTML"
+                        hx-vals={(format!(r#""id": {}"#, entry.id))}
+                        {("odeslat znovu")}
+                        button.add-button
+                        hx-post="command:forward_from_history"
+                        hx-trigger="click"
+                        hx-target="#send-error-overlay"
+                        hx-swap="outerHTML"
+                        hx-vals={(format!(r#""id": {}"#, entry.id))}
+                        {("přeposlat")}
+                    }
+                    @if !entry.hash.is_empty() {
+                        button.add-button
+                        hx-post="command:export_history_proof"
+                        hx-trigger="click"
+                        hx-swap="none"
+                        hx-vals={(format!(r#""id": {}"#, entry.id))}
+                        {("doklad")}
+                    }
+                }
+                @if let Some(original) = entry.resent_from.and_then(|id| send_history.entry(id)) {
+                    p.send-summary-row{(format!("znovu odesláno, původně {}", original.time))}
+                }
+                p.send-summary-row{(format!("příjemci: {}", entry.recipients.join(", ")))}
+                p.send-summary-row{(format!("přílohy: {}", entry.files.join(", ")))}
+                @if !entry.note.is_empty() {
+                    p.send-summary-row{(format!("poznámka: {}", entry.note))}
+                }
+            }
+        }
+    }
+}
+
+#[tauri::command]
+pub fn open_send_history(app: tauri::AppHandle) -> String {
+    let app_state = app.state::<AppState>();
+
+    html! {
+        div .overlay .most-top #send-error-overlay{
+            div .overlay-error-window{
+                button.close-button
+                hx-post="command:close_send_error"
+                hx-trigger="click"
+                hx-target="#send-error-overlay"
+                hx-swap="outerHTML"
+                {("X")}
+                h1.overlay-error-subtitle{("odeslané E-maily")}
+                div.send-summary{
+                    (render_send_history(&app_state.send_history.lock(), true))
+                }
+            }
+        }
+    }
+    .into_string()
+}
+
+//archived files and the subject replace the selection, it is sent the usual way
+fn load_entry(app: &tauri::AppHandle, entry: &HistoryEntry, with_recipients: bool) {
+    let app_state = app.state::<AppState>();
+
+    let mut other_addresses: Vec<String> = vec![];
+
+    {
+        let mail_list = app_state.mail_list.lock();
+        let mut mail = app_state.mail.lock();
+
+        mail.clear_recipients();
+        mail.set_files(entry.archived_files.clone());
+        mail.set_template(Some(MessageTemplate {
+            subject: entry.subject.clone(),
+            body: String::new(),
+        }));
+        mail.set_resent_from(Some(entry.id));
+
+        //addresses of contacts are picked on their buttons, the rest goes to "ostatní"
+        if with_recipients {
+            for address in &entry.recipients {
+                match mail_list
+                    .contacts()
+                    .find(|contact| contact.active && contact.has_mail(address))
+                {
+                    Some(contact) => {
+                        mail.add_person(
+                            Person {
+                                name: contact.name.clone(),
+                                mail: address.clone(),
+                            },
+                            app.clone(),
+                        );
+                    }
+                    None => other_addresses.push(address.clone()),
+                }
+            }
+        }
+    }
+
+    let mut other_mail_list = app_state.other_mail_list.lock();
+    other_mail_list.clear();
+    for address in other_addresses {
+        other_mail_list.add_mail(address);
+    }
+}
+
+fn history_entry(app: &tauri::AppHandle, id: String) -> Option<HistoryEntry> {
+    let id: usize = id
+        .parse()
+        .unwrap_or_else(|_| error_id_parse(app.clone(), id));
+
+    app.state::<AppState>()
+        .send_history
+        .lock()
+        .entry(id)
+        .filter(|entry| entry.can_resend())
+        .cloned()
+}
+
+#[tauri::command]
+pub fn resend_from_history(app: tauri::AppHandle, id: String) -> String {
+    if let Some(entry) = history_entry(&app, id) {
+        load_entry(&app, &entry, true);
+    }
+
+    html! {
+        div #send-error-placeholder {}
+        (reload_selection(&app))
+    }
+    .into_string()
+}
+
+//new addresses are typed into "ostatní", which is opened right away
+#[tauri::command]
+pub fn forward_from_history(app: tauri::AppHandle, id: String) -> String {
+    let Some(entry) = history_entry(&app, id) else {
+        return html! {
+            div #send-error-placeholder {}
+        }
+        .into_string();
+    };
+
+    load_entry(&app, &entry, false);
+
+    html! {
+        div #send-error-placeholder {}
+        (reload_selection(&app))
+        div
+        hx-trigger="load delay:1ms"
+        hx-swap="outerHTML"
+        hx-target="#overlay-other-placeholder"
+        hx-post="command:open_other"
+        {}
+    }
+    .into_string()
+}
+
+//every entry is checked against its hash and the one before it
+#[tauri::command]
+pub fn verify_send_history(app: tauri::AppHandle) -> String {
+    let app_state = app.state::<AppState>();
+
+    let problems = app_state.send_history.lock().verify();
+
+    html! {
+        @if problems.is_empty() {
+            p.send-summary-row{("historie je v pořádku, žádný záznam nebyl změněn")}
+        }
+        @for problem in problems {
+            p.send-summary-row.scheduled-send-problem{
+                (match problem {
+                    ChainProblem::Altered(id) => format!("záznam {id} byl změněn"),
+                    ChainProblem::Broken(id) => {
+                        format!("řetězec je přerušen u záznamu {id}, záznamy byly smazány nebo přeházeny")
+                    }
+                    ChainProblem::FileChanged(id, name) => {
+                        format!("příloha {name} u záznamu {id} se liší od odeslané")
+                    }
+                })
+            }
+        }
+    }
+    .into_string()
+}
+
+#[tauri::command]
+pub fn export_history_proof(app: tauri::AppHandle, id: String) {
+    let id: usize = id
+        .parse()
+        .unwrap_or_else(|_| error_id_parse(app.clone(), id));
+
+    let Some(proof) = app.state::<AppState>().send_history.lock().proof(id) else {
+        return;
+    };
+
+    app.dialog()
+        .file()
+        .add_filter("Text", &["txt"])
+        .set_file_name(format!("doklad-{id}.txt"))
+        .save_file(move |file_path| {
+            let Some(path) = file_path.and_then(|file_path| file_path.into_path().ok()) else {
+                return;
+            };
+
+            if fs::write(path, proof).is_err() {
+                error_exporting_history_proof(app);
+            }
+        });
+}
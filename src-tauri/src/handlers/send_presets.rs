@@ -0,0 +1,457 @@
+use maud::{html, Markup};
+use tauri::Manager;
+
+use crate::backend::config::{Config, ProfileChoice};
+use crate::backend::error_handling::error_id_parse;
+use crate::backend::mail_list_utils::MailList;
+use crate::backend::mail_sender::MessageTemplate;
+use crate::backend::send_presets::{SendPreset, SendPresets};
+use crate::handlers::mail_send::reload_selection;
+use crate::AppState;
+
+//---------------------------main screen
+
+fn preset_buttons(send_presets: &SendPresets) -> Markup {
+    html! {
+        div.preset-buttons #preset-buttons{
+            @for preset in send_presets.presets() {
+                button.middle-button.preset-button
+                hx-post="command:apply_preset"
+                hx-trigger="click"
+                hx-target="#preset-buttons"
+                hx-swap="outerHTML"
+                hx-vals={(format!(r#""id": {}"#, preset.id))}
+                {(preset.name)}
+            }
+        }
+    }
+}
+
+#[tauri::command]
+pub fn load_preset_buttons(app: tauri::AppHandle) -> String {
+    let app_state = app.state::<AppState>();
+
+    let markup = preset_buttons(&app_state.send_presets.lock());
+
+    markup.into_string()
+}
+
+//selection is replaced, picked files stay
+#[tauri::command]
+pub fn apply_preset(app: tauri::AppHandle, id: String) -> String {
+    let id: usize = id
+        .parse()
+        .unwrap_or_else(|_| error_id_parse(app.clone(), id));
+
+    let app_state = app.state::<AppState>();
+
+    let Some(preset) = app_state.send_presets.lock().preset(id).cloned() else {
+        return load_preset_buttons(app);
+    };
+
+    {
+        let mail_list = app_state.mail_list.lock();
+        let mut mail = app_state.mail.lock();
+
+        mail.clear_recipients();
+
+        //removed, deactivated and invalid contacts are skipped, the integrity check shows them
+        for contact in preset
+            .contacts
+            .iter()
+            .filter_map(|contact_id| mail_list.load_contact(*contact_id))
+            .filter(|contact| contact.active && contact.has_valid_mail())
+        {
+            mail.add_person(contact.person(), app.clone());
+        }
+
+        for list_id in &preset.distribution_lists {
+            if mail_list.load_distribution_list(*list_id).is_some() {
+                mail.select_distribution_list(
+                    *list_id,
+                    mail_list.distribution_list_people(*list_id),
+                    app.clone(),
+                );
+            }
+        }
+
+        mail.set_template(Some(MessageTemplate {
+            subject: preset.subject.clone(),
+            body: preset.body.clone(),
+        }));
+    }
+
+    let mut other_mail_list = app_state.other_mail_list.lock();
+    other_mail_list.clear();
+    for mail in &preset.other_addresses {
+        other_mail_list.add_mail(mail.clone());
+    }
+    drop(other_mail_list);
+
+    *app_state.profile_choice.lock() = preset.profile;
+
+    let markup: Markup = html! {
+        (preset_buttons(&app_state.send_presets.lock()))
+        (reload_selection(&app))
+        div
+        hx-trigger="load delay:1ms"
+        hx-swap="outerHTML"
+        hx-target="#profile-select"
+        hx-post="command:load_profile_select"
+        {}
+    };
+
+    markup.into_string()
+}
+
+//---------------------------settings
+
+fn render_preset_list(send_presets: &SendPresets) -> Markup {
+    html! {
+        @for preset in send_presets.presets() {
+            button.middle-button.preset-button
+            hx-post="command:open_send_preset"
+            hx-trigger="click"
+            hx-target="#send-preset-editor"
+            hx-swap="innerHTML"
+            hx-vals={(format!(r#""id": {}"#, preset.id))}
+            {(preset.name)}
+        }
+        button.middle-button.add-category-button
+        hx-post="command:add_send_preset"
+        hx-trigger="click"
+        hx-target="#send-preset-editor"
+        hx-swap="innerHTML"
+        {("+ přidat předvolbu")}
+    }
+}
+
+fn reload_preset_list() -> Markup {
+    html! {
+        div
+        hx-trigger="load delay:1ms"
+        hx-swap="innerHTML"
+        hx-target="#send-preset-list"
+        hx-post="command:load_send_preset_list"
+        {}
+    }
+}
+
+fn toggle_button(command: &str, id: usize, item: usize, name: &str, picked: bool) -> Markup {
+    html! {
+        button.middle-button.clicked[picked]
+        hx-post=(command)
+        hx-trigger="click"
+        hx-swap="outerHTML"
+        hx-vals={(format!(r#""id": {id}, "item": {item}"#))}
+        {(name)}
+    }
+}
+
+fn preset_row(id: usize, field: &str, title: &str, value: &str) -> Markup {
+    html! {
+        div.config-row{
+            h1.config-row-title
+            {(title)}
+            input.config-row-input-field
+            type="text"
+            hx-post="command:edit_send_preset"
+            hx-trigger="change"
+            hx-target="#send-preset-list"
+            hx-swap="innerHTML"
+            hx-vals={(format!(r#""id": {id}, "field": "{field}""#))}
+            name="text"
+            value=(value)
+            {}
+        }
+    }
+}
+
+fn render_preset_editor(preset: &SendPreset, mail_list: &MailList, config: &Config) -> Markup {
+    let id = preset.id;
+
+    html! {
+        (preset_row(id, "name", "název předvolby:", &preset.name))
+        div.distribution-list-members{
+            @for category in mail_list.categories() {
+                h2.category-title{(category.name)}
+                div.category-buttons{
+                    @for contact in category.contacts.iter().filter(|c| !c.name.trim().is_empty()) {
+                        (toggle_button(
+                            "command:toggle_send_preset_contact",
+                            id,
+                            contact.id,
+                            &contact.name,
+                            preset.contacts.contains(&contact.id),
+                        ))
+                    }
+                }
+            }
+            @if !mail_list.distribution_lists().is_empty() {
+                h2.category-title{("distribuční seznamy")}
+                div.category-buttons{
+                    @for list in mail_list.distribution_lists() {
+                        (toggle_button(
+                            "command:toggle_send_preset_list",
+                            id,
+                            list.id,
+                            &list.name,
+                            preset.distribution_lists.contains(&list.id),
+                        ))
+                    }
+                }
+            }
+        }
+        h2.category-title{("ostatní E-maily (oddělené čárkou)")}
+        textarea.distribution-list-addresses
+        name="text"
+        hx-post="command:edit_send_preset"
+        hx-trigger="change"
+        hx-target="#send-preset-list"
+        hx-swap="innerHTML"
+        hx-vals={(format!(r#""id": {id}, "field": "other_addresses""#))}
+        {(preset.other_addresses.join(", "))}
+        div.config-row{
+            h1.config-row-title
+            {("odesílatel:")}
+            select.config-row-input-field
+            name="text"
+            hx-post="command:edit_send_preset"
+            hx-trigger="change"
+            hx-target="#send-preset-list"
+            hx-swap="innerHTML"
+            hx-vals={(format!(r#""id": {id}, "field": "profile""#))}
+            {
+                option value="auto" selected[preset.profile == ProfileChoice::Auto] {("podle příjemců")}
+                option value="main" selected[preset.profile == ProfileChoice::Main] {("hlavní profil")}
+                @for profile in config.profiles() {
+                    option value=(profile.id) selected[preset.profile == ProfileChoice::Profile(profile.id)] {(profile.name)}
+                }
+            }
+        }
+        (preset_row(id, "subject", "předmět (prázdný - podle profilu):", &preset.subject))
+        h2.category-title{("text E-mailu (prázdný - výchozí pozdrav)")}
+        textarea.distribution-list-addresses
+        name="text"
+        hx-post="command:edit_send_preset"
+        hx-trigger="change"
+        hx-target="#send-preset-list"
+        hx-swap="innerHTML"
+        hx-vals={(format!(r#""id": {id}, "field": "body""#))}
+        {(preset.body)}
+        div.bottom-button-row{
+            button.settings-bottom-button.close
+            hx-post="command:remove_send_preset"
+            hx-trigger="click"
+            hx-target="#send-preset-editor"
+            hx-swap="innerHTML"
+            hx-vals={(format!(r#""id": {id}"#))}
+            {("smazat předvolbu")}
+        }
+    }
+}
+
+#[tauri::command]
+pub fn open_send_presets(app: tauri::AppHandle) -> String {
+    let app_state = app.state::<AppState>();
+
+    let markup: Markup = html! {
+        div #overlay-send-presets .overlay .most-top
+        {
+            div.overlay-window
+            {
+                button.close-button
+                hx-post="command:close_send_presets"
+                hx-trigger="click"
+                hx-target="#overlay-send-presets"
+                hx-swap="outerHTML"
+                {("X")}
+                h1.overlay-title{("předvolby odeslání")}
+                div.preset-buttons #send-preset-list{
+                    (render_preset_list(&app_state.send_presets.lock()))
+                }
+                div #send-preset-editor{}
+            }
+        }
+    };
+
+    markup.into_string()
+}
+
+#[tauri::command]
+pub fn close_send_presets() -> String {
+    let markup: Markup = html! {
+        div #send-presets-placeholder {}
+    };
+
+    markup.into_string()
+}
+
+#[tauri::command]
+pub fn load_send_preset_list(app: tauri::AppHandle) -> String {
+    let app_state = app.state::<AppState>();
+
+    let markup = render_preset_list(&app_state.send_presets.lock());
+
+    markup.into_string()
+}
+
+#[tauri::command]
+pub fn add_send_preset(app: tauri::AppHandle) -> String {
+    let app_state = app.state::<AppState>();
+
+    let mut send_presets = app_state.send_presets.lock();
+    let id = send_presets.add();
+    send_presets.save(app.clone());
+    drop(send_presets);
+
+    open_send_preset(app, id.to_string())
+}
+
+#[tauri::command]
+pub fn open_send_preset(app: tauri::AppHandle, id: String) -> String {
+    let id: usize = id
+        .parse()
+        .unwrap_or_else(|_| error_id_parse(app.clone(), id));
+
+    let app_state = app.state::<AppState>();
+
+    let Some(preset) = app_state.send_presets.lock().preset(id).cloned() else {
+        return reload_preset_list().into_string();
+    };
+
+    let markup: Markup = html! {
+        (render_preset_editor(&preset, &app_state.mail_list.lock(), &app_state.config.lock()))
+        (reload_preset_list())
+    };
+
+    markup.into_string()
+}
+
+#[tauri::command]
+pub fn edit_send_preset(app: tauri::AppHandle, id: String, field: String, text: String) -> String {
+    let id: usize = id
+        .parse()
+        .unwrap_or_else(|_| error_id_parse(app.clone(), id));
+
+    let app_state = app.state::<AppState>();
+
+    let mut send_presets = app_state.send_presets.lock();
+
+    send_presets.update(id, |preset| match field.as_str() {
+        "name" => preset.name = text.trim().to_string(),
+        "other_addresses" => {
+            preset.other_addresses = text
+                .split(|c: char| c == ',' || c == ';' || c.is_whitespace())
+                .filter(|mail| !mail.is_empty())
+                .map(|mail| mail.to_string())
+                .collect()
+        }
+        "profile" => preset.profile = ProfileChoice::parse(&text),
+        "subject" => preset.subject = text.trim().to_string(),
+        "body" => preset.body = text.trim_end().to_string(),
+        _ => {}
+    });
+    send_presets.save(app.clone());
+
+    render_preset_list(&send_presets).into_string()
+}
+
+#[tauri::command]
+pub fn toggle_send_preset_contact(app: tauri::AppHandle, id: String, item: String) -> String {
+    let id: usize = id
+        .parse()
+        .unwrap_or_else(|_| error_id_parse(app.clone(), id));
+    let contact_id: usize = item
+        .parse()
+        .unwrap_or_else(|_| error_id_parse(app.clone(), item));
+
+    let app_state = app.state::<AppState>();
+
+    let mut send_presets = app_state.send_presets.lock();
+
+    send_presets.update(id, |preset| {
+        if preset.contacts.contains(&contact_id) {
+            preset.contacts.retain(|contact| *contact != contact_id);
+        } else {
+            preset.contacts.push(contact_id);
+        }
+    });
+    send_presets.save(app.clone());
+
+    let picked = send_presets
+        .preset(id)
+        .is_some_and(|preset| preset.contacts.contains(&contact_id));
+    let name = app_state
+        .mail_list
+        .lock()
+        .load_contact(contact_id)
+        .map(|contact| contact.name)
+        .unwrap_or_default();
+
+    toggle_button(
+        "command:toggle_send_preset_contact",
+        id,
+        contact_id,
+        &name,
+        picked,
+    )
+    .into_string()
+}
+
+#[tauri::command]
+pub fn toggle_send_preset_list(app: tauri::AppHandle, id: String, item: String) -> String {
+    let id: usize = id
+        .parse()
+        .unwrap_or_else(|_| error_id_parse(app.clone(), id));
+    let list_id: usize = item
+        .parse()
+        .unwrap_or_else(|_| error_id_parse(app.clone(), item));
+
+    let app_state = app.state::<AppState>();
+
+    let mut send_presets = app_state.send_presets.lock();
+
+    send_presets.update(id, |preset| {
+        if preset.distribution_lists.contains(&list_id) {
+            preset.distribution_lists.retain(|list| *list != list_id);
+        } else {
+            preset.distribution_lists.push(list_id);
+        }
+    });
+    send_presets.save(app.clone());
+
+    let picked = send_presets
+        .preset(id)
+        .is_some_and(|preset| preset.distribution_lists.contains(&list_id));
+    let name = app_state
+        .mail_list
+        .lock()
+        .load_distribution_list(list_id)
+        .map(|list| list.name.clone())
+        .unwrap_or_default();
+
+    toggle_button(
+        "command:toggle_send_preset_list",
+        id,
+        list_id,
+        &name,
+        picked,
+    )
+    .into_string()
+}
+
+#[tauri::command]
+pub fn remove_send_preset(app: tauri::AppHandle, id: String) -> String {
+    let id: usize = id
+        .parse()
+        .unwrap_or_else(|_| error_id_parse(app.clone(), id));
+
+    let app_state = app.state::<AppState>();
+
+    let mut send_presets = app_state.send_presets.lock();
+    send_presets.remove(id);
+    send_presets.save(app.clone());
+
+    reload_preset_list().into_string()
+}
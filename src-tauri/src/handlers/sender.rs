@@ -0,0 +1,154 @@
+use maud::{html, Markup};
+use tauri::Manager;
+
+use crate::backend::error_handling::error_id_parse;
+use crate::backend::mail_list_utils::Contact;
+use crate::backend::tutorial::TutorialAction;
+use crate::handlers::tutorial::tutorial_act;
+use crate::AppState;
+
+//---------------------------
+
+//mechanic picked before sending, gone when the contact was removed or deactivated
+pub fn current_sender(app_state: &AppState) -> Option<Contact> {
+    let id = (*app_state.current_sender.lock())?;
+
+    app_state
+        .mail_list
+        .lock()
+        .load_contact(id)
+        .filter(|contact| contact.active)
+}
+
+fn sender_button(sender: Option<&Contact>) -> Markup {
+    html! {
+        button.schedule-button #sender-button
+        .sender-missing[sender.is_none()]
+        hx-post="command:open_sender_picker"
+        hx-trigger="click"
+        hx-target="#send-error-placeholder"
+        hx-swap="outerHTML"
+        {
+            @match sender {
+                Some(sender) => { (format!("odesílá: {}", sender.name)) }
+                None => { ("kdo odesílá?") }
+            }
+        }
+    }
+}
+
+//shared workshop PC - whoever sends next has to identify again
+pub fn reset_sender(app_state: &AppState) {
+    *app_state.current_sender.lock() = None;
+}
+
+pub fn sender_button_reload() -> Markup {
+    html! {
+        div
+        hx-trigger="load delay:1ms"
+        hx-swap="outerHTML"
+        hx-target="#sender-button"
+        hx-post="command:load_sender_button"
+        {}
+    }
+}
+
+#[tauri::command]
+pub fn load_sender_button(app: tauri::AppHandle) -> String {
+    let app_state = app.state::<AppState>();
+
+    sender_button(current_sender(&app_state).as_ref()).into_string()
+}
+
+fn render_sender_picker(app_state: &AppState, wrong_pin: bool) -> Markup {
+    let current_id = *app_state.current_sender.lock();
+    let mail_list = app_state.mail_list.lock();
+
+    html! {
+        div .overlay .most-top #send-error-overlay{
+            div .overlay-error-window{
+                button.close-button
+                hx-post="command:close_send_error"
+                hx-trigger="click"
+                hx-target="#send-error-overlay"
+                hx-swap="outerHTML"
+                {("X")}
+                h1.overlay-error-subtitle{("kdo odesílá?")}
+                input.schedule-input
+                type="password"
+                inputmode="numeric"
+                autocomplete="off"
+                name="text"
+                placeholder="PIN"
+                hx-post="command:identify_by_pin"
+                hx-trigger="change"
+                hx-target="#send-error-overlay"
+                hx-swap="outerHTML"
+                {}
+                @if wrong_pin {
+                    p.other-mail-error{("Neznámý PIN, zkuste to prosím znovu, nebo vyberte své jméno.")}
+                }
+                div.sender-picker{
+                    @for contact in mail_list.contacts().filter(|contact| contact.active && contact.has_valid_mail()) {
+                        button.middle-button.clicked[current_id == Some(contact.id)]
+                        hx-post="command:pick_sender"
+                        hx-trigger="click"
+                        hx-target="#send-error-overlay"
+                        hx-swap="outerHTML"
+                        hx-vals={(format!(r#""id": {}"#, contact.id))}
+                        {(contact.name)}
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[tauri::command]
+pub fn open_sender_picker(app: tauri::AppHandle) -> String {
+    render_sender_picker(&app.state::<AppState>(), false).into_string()
+}
+
+//overlay is closed and the button shows the new name
+fn sender_picked() -> String {
+    html! {
+        div #send-error-placeholder {}
+        (sender_button_reload())
+    }
+    .into_string()
+}
+
+#[tauri::command]
+pub fn pick_sender(app: tauri::AppHandle, id: String) -> String {
+    let id: usize = id
+        .parse()
+        .unwrap_or_else(|_| error_id_parse(app.clone(), id));
+
+    let app_state = app.state::<AppState>();
+
+    *app_state.current_sender.lock() = Some(id);
+
+    tutorial_act(&app, TutorialAction::PickSender);
+
+    sender_picked()
+}
+
+#[tauri::command]
+pub fn identify_by_pin(app: tauri::AppHandle, text: String) -> String {
+    let app_state = app.state::<AppState>();
+
+    let contact_id = app_state
+        .mail_list
+        .lock()
+        .contact_by_pin(&text)
+        .map(|contact| contact.id);
+
+    match contact_id {
+        Some(id) => {
+            *app_state.current_sender.lock() = Some(id);
+            tutorial_act(&app, TutorialAction::PickSender);
+            sender_picked()
+        }
+        None => render_sender_picker(&app_state, true).into_string(),
+    }
+}
@@ -0,0 +1,100 @@
+use maud::{html, Markup};
+use tauri::Manager;
+
+use crate::backend::config::{Config, ProfileChoice};
+use crate::handlers::sender::current_sender;
+use crate::AppState;
+
+//---------------------------
+
+//first category with a default profile that has a picked recipient wins
+fn group_profile(app_state: &AppState, config: &Config) -> Option<usize> {
+    let mail_list = app_state.mail_list.lock();
+    let mail = app_state.mail.lock();
+
+    mail_list
+        .categories()
+        .iter()
+        .filter(|category| {
+            category.contacts.iter().any(|contact| {
+                contact
+                    .people()
+                    .iter()
+                    .any(|person| mail.is_picked(person) || mail.is_in_selected_list(person))
+            })
+        })
+        .find_map(|category| config.group_profile(category.id))
+}
+
+//None is the main profile, must be called before mail is locked
+fn send_profile(app_state: &AppState) -> Option<usize> {
+    let config = app_state.config.lock().clone();
+    let choice = *app_state.profile_choice.lock();
+
+    match choice {
+        ProfileChoice::Auto => group_profile(app_state, &config),
+        ProfileChoice::Main => None,
+        ProfileChoice::Profile(id) => config.profile(id).map(|profile| profile.id),
+    }
+}
+
+//picked profile and the config with it and the mechanic filled in, as the message will be sent
+//must be called before mail is locked
+pub fn send_config(app_state: &AppState) -> (Option<usize>, Config) {
+    let profile = send_profile(app_state);
+    let sender = current_sender(app_state);
+    let mechanic = sender.as_ref().map(|sender| sender.name.as_str());
+
+    let mut config = app_state.config.lock().for_send(profile, mechanic);
+
+    if let Some(template) = app_state.mail.lock().template() {
+        config.override_title(&template.subject, mechanic);
+    }
+
+    (profile, config)
+}
+
+fn profile_select(config: &Config, choice: ProfileChoice) -> Markup {
+    let main_name = if config.sender_name().is_empty() {
+        "hlavní"
+    } else {
+        config.sender_name()
+    };
+
+    html! {
+        @if config.profiles().is_empty() {
+            div #profile-select {}
+        } @else {
+            select.schedule-button #profile-select
+            name="text"
+            hx-post="command:save_profile_choice"
+            hx-trigger="change"
+            hx-swap="none"
+            {
+                option value="auto" selected[choice == ProfileChoice::Auto] {("odesílatel: podle příjemců")}
+                option value="main" selected[choice == ProfileChoice::Main] {(format!("odesílatel: {main_name}"))}
+                @for profile in config.profiles() {
+                    option value=(profile.id) selected[choice == ProfileChoice::Profile(profile.id)] {(format!("odesílatel: {}", profile.name))}
+                }
+            }
+        }
+    }
+}
+
+#[tauri::command]
+pub fn load_profile_select(app: tauri::AppHandle) -> String {
+    let app_state = app.state::<AppState>();
+
+    let choice = *app_state.profile_choice.lock();
+
+    let markup = profile_select(&app_state.config.lock(), choice);
+
+    markup.into_string()
+}
+
+#[tauri::command]
+pub fn save_profile_choice(app: tauri::AppHandle, text: String) {
+    let app_state = app.state::<AppState>();
+
+    *app_state.profile_choice.lock() = ProfileChoice::parse(&text);
+}
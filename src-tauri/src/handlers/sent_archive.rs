@@ -0,0 +1,263 @@
+use std::fs;
+
+use maud::{html, Markup};
+use tauri::Manager;
+use tauri_plugin_dialog::DialogExt;
+use tauri_plugin_opener::OpenerExt;
+
+use crate::backend::config::RecipientDomain;
+use crate::backend::error_handling::{
+    error_exporting_archived_message, error_id_parse, error_resending_archived_mail,
+};
+use crate::backend::mail_sender::{MailSender, MailSenderError};
+use crate::backend::send_history::{record_history, sha256_hex, HistoryEntry, HistoryOutcome};
+use crate::backend::sent_archive::{
+    archived_message_path, record_archive, ArchivedMessage, SentArchive,
+};
+use crate::handlers::tutorial::tutorial_running;
+use crate::AppState;
+
+//---------------------------
+
+//how many found messages are listed, the search narrows it down
+static ARCHIVE_SHOWN: usize = 50;
+
+//sending_allowed - false while the tutorial runs, its sends go only to the sandbox
+fn render_archive_results(
+    sent_archive: &SentArchive,
+    query: &str,
+    sending_allowed: bool,
+) -> Markup {
+    let found = sent_archive.search(query);
+
+    html! {
+        @if !sending_allowed {
+            p.send-summary-row{("během průvodce se nic neodesílá, ukončete ho pro odeslání")}
+        }
+        p.send-summary-row{(format!("nalezeno: {}", found.len()))}
+        @for message in found.iter().take(ARCHIVE_SHOWN) {
+            div.scheduled-send{
+                div.other-mail-button-row{
+                    p.scheduled-send-state{
+                        (format!("{} - {}", message.time, message.subject))
+                        @if let Some(sent_by) = &message.sent_by {
+                            (format!(" - odeslal(a) {sent_by}"))
+                        }
+                    }
+                    button.add-button
+                    hx-post="command:open_archived_message"
+                    hx-trigger="click"
+                    hx-swap="none"
+                    hx-vals={(format!(r#""id": {}"#, message.id))}
+                    {("otevřít")}
+                    button.add-button
+                    hx-post="command:export_archived_message"
+                    hx-trigger="click"
+                    hx-swap="none"
+                    hx-vals={(format!(r#""id": {}"#, message.id))}
+                    {("exportovat")}
+                    button.add-button
+                    hx-post="command:resend_archived_message"
+                    hx-trigger="click"
+                    hx-target="#sent-archive-results"
+                    hx-swap="innerHTML"
+                    hx-include="#sent-archive-search"
+                    hx-vals={(format!(r#""id": {}"#, message.id))}
+                    disabled[!sending_allowed]
+                    {("odeslat znovu")}
+                }
+                p.send-summary-row{(format!("od: {}", message.from))}
+                p.send-summary-row{(format!("příjemci: {}", message.recipients.join(", ")))}
+                p.send-summary-row{(format!("přílohy: {}", message.files.join(", ")))}
+            }
+        }
+    }
+}
+
+#[tauri::command]
+pub fn open_sent_archive(app: tauri::AppHandle) -> String {
+    let app_state = app.state::<AppState>();
+
+    let markup: Markup = html! {
+        div #overlay-sent-archive .overlay .most-top
+        {
+            div.overlay-window
+            {
+                button.close-button
+                hx-post="command:close_sent_archive"
+                hx-trigger="click"
+                hx-target="#overlay-sent-archive"
+                hx-swap="outerHTML"
+                {("X")}
+                h1.overlay-title{("archiv odeslaných E-mailů")}
+                input.recipient-search #sent-archive-search
+                type="search"
+                name="text"
+                placeholder="hledat předmět, adresu, přílohu nebo text v PDF..."
+                autocomplete="off"
+                hx-post="command:search_sent_archive"
+                hx-trigger="input changed delay:300ms, search"
+                hx-target="#sent-archive-results"
+                hx-swap="innerHTML"
+                {}
+                div.send-summary #sent-archive-results{
+                    (render_archive_results(&app_state.sent_archive.lock(), "", !tutorial_running(&app)))
+                }
+            }
+        }
+    };
+
+    markup.into_string()
+}
+
+#[tauri::command]
+pub fn close_sent_archive() -> String {
+    let markup: Markup = html! {
+        div #sent-archive-placeholder {}
+    };
+
+    markup.into_string()
+}
+
+#[tauri::command]
+pub fn search_sent_archive(app: tauri::AppHandle, text: String) -> String {
+    let app_state = app.state::<AppState>();
+
+    let markup = render_archive_results(
+        &app_state.sent_archive.lock(),
+        &text,
+        !tutorial_running(&app),
+    );
+
+    markup.into_string()
+}
+
+fn archived_message(app: &tauri::AppHandle, id: String) -> Option<ArchivedMessage> {
+    let id: usize = id
+        .parse()
+        .unwrap_or_else(|_| error_id_parse(app.clone(), id));
+
+    app.state::<AppState>()
+        .sent_archive
+        .lock()
+        .message(id)
+        .cloned()
+}
+
+//.eml is opened in the default mail client
+#[tauri::command]
+pub fn open_archived_message(app: tauri::AppHandle, id: String) {
+    if let Some(message) = archived_message(&app, id) {
+        let path = archived_message_path(&app, &message);
+        let _ = app.opener().open_path(path.to_string_lossy(), None::<&str>);
+    }
+}
+
+#[tauri::command]
+pub fn export_archived_message(app: tauri::AppHandle, id: String) {
+    let Some(message) = archived_message(&app, id) else {
+        return;
+    };
+
+    let source = archived_message_path(&app, &message);
+
+    app.dialog()
+        .file()
+        .add_filter("E-mail", &["eml"])
+        .set_file_name(format!("email-{}.eml", message.id))
+        .save_file(move |file_path| {
+            let Some(path) = file_path.and_then(|file_path| file_path.into_path().ok()) else {
+                return;
+            };
+
+            if fs::copy(source, path).is_err() {
+                error_exporting_archived_message(app);
+            }
+        });
+}
+
+//the exact message is sent again through the mailbox it left from
+//recipients are checked against the current blocked domains, the policy may have changed since
+#[tauri::command]
+pub fn resend_archived_message(app: tauri::AppHandle, id: String, text: String) -> String {
+    let app_state = app.state::<AppState>();
+
+    if let Some(message) = archived_message(&app, id).filter(|_| !tutorial_running(&app)) {
+        let config = {
+            let config = app_state.config.lock();
+            config.for_send(config.profile_by_mail(&message.from), None)
+        };
+
+        let blocked: Vec<String> = message
+            .recipients
+            .iter()
+            .filter(|mail| config.recipient_domain(mail) == RecipientDomain::Blocked)
+            .cloned()
+            .collect();
+        if !blocked.is_empty() {
+            return render_resend_result(&app, &text, &blocked).into_string();
+        }
+
+        let result = message.envelope().and_then(|envelope| {
+            let bytes = fs::read(archived_message_path(&app, &message))
+                .map_err(|_| MailSenderError::InvalidFilePath)?;
+
+            MailSender::send_raw(&config, &envelope, &bytes)?;
+
+            Ok((envelope, bytes))
+        });
+
+        match result {
+            Ok((envelope, bytes)) => {
+                record_archive(
+                    &app,
+                    ArchivedMessage {
+                        text: message.text.clone(),
+                        ..ArchivedMessage::new(
+                            &envelope,
+                            message.subject.clone(),
+                            message.files.clone(),
+                            message.sent_by.clone(),
+                        )
+                    },
+                    &bytes,
+                    &[],
+                );
+
+                record_history(
+                    &app,
+                    HistoryEntry {
+                        note: format!("znovu odesláno z archivu ({})", message.time),
+                        message_hash: Some(sha256_hex(&bytes)),
+                        ..HistoryEntry::new(
+                            HistoryOutcome::Sent,
+                            message.recipients,
+                            message.subject,
+                            message.files,
+                        )
+                    },
+                    &[],
+                );
+            }
+            Err(error) => error_resending_archived_mail(app.clone(), error),
+        }
+    }
+
+    render_resend_result(&app, &text, &[]).into_string()
+}
+
+//blocked recipients are listed above the results, the same text as on the main screen
+fn render_resend_result(app: &tauri::AppHandle, query: &str, blocked: &[String]) -> Markup {
+    html! {
+        @if !blocked.is_empty() {
+            p.send-summary-row{
+                (format!("Na tyto E-maily není dovoleno posílat: {}", blocked.join(", ")))
+            }
+        }
+        (render_archive_results(
+            &app.state::<AppState>().sent_archive.lock(),
+            query,
+            !tutorial_running(app),
+        ))
+    }
+}
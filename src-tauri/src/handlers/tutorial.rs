@@ -0,0 +1,139 @@
+use maud::{html, Markup, PreEscaped};
+use tauri::{Emitter, Manager};
+
+use crate::backend::config::Config;
+use crate::backend::mail_list_utils::Person;
+use crate::backend::mail_sender::{MailSender, MailSenderError};
+use crate::backend::tutorial::{sandbox_send, Tour, Tutorial, TutorialAction};
+use crate::AppState;
+
+//---------------------------
+
+//panel is in the corner of both screens, the step highlights its element by a style rule
+fn render_tutorial(tutorial: Option<&Tutorial>) -> Markup {
+    let Some(tutorial) = tutorial else {
+        return html! {
+            div.non-display {}
+        };
+    };
+
+    let step = tutorial.step();
+    let (number, count) = tutorial.position();
+
+    html! {
+        @if let Some(target) = step.target {
+            style{(PreEscaped(format!("{target} {{ outline: 5px solid #4fc3f7; outline-offset: 4px; }}")))}
+        }
+        div.tutorial-panel{
+            p.tutorial-progress{(format!("průvodce - krok {number} / {count}"))}
+            p.tutorial-text{(step.text)}
+            @if step.waits_for.is_some() {
+                p.tutorial-hint{("Jakmile to uděláte, průvodce pokračuje sám.")}
+            }
+            @if let Some(path) = tutorial.sandbox_file().filter(|_| tutorial.is_last()) {
+                p.tutorial-hint{(format!("zkušební E-mail je uložen v {}", path.display()))}
+            }
+            div.tutorial-buttons{
+                @if tutorial.is_last() {
+                    button.add-button
+                    hx-post="command:stop_tutorial"
+                    hx-trigger="click"
+                    hx-target="#tutorial"
+                    hx-swap="innerHTML"
+                    {("dokončit")}
+                } @else {
+                    button.add-button
+                    hx-post="command:next_tutorial_step"
+                    hx-trigger="click"
+                    hx-target="#tutorial"
+                    hx-swap="innerHTML"
+                    {@if step.waits_for.is_some() {("přeskočit")} @else {("další")}}
+                    button.remove-button
+                    hx-post="command:stop_tutorial"
+                    hx-trigger="click"
+                    hx-target="#tutorial"
+                    hx-swap="innerHTML"
+                    {("ukončit průvodce")}
+                }
+            }
+        }
+    }
+}
+
+//both screens load the panel, so a running tour survives opening and closing the settings
+#[tauri::command]
+pub fn load_tutorial(app: tauri::AppHandle) -> String {
+    let app_state = app.state::<AppState>();
+
+    let markup = render_tutorial(app_state.tutorial.lock().as_ref());
+
+    markup.into_string()
+}
+
+#[tauri::command]
+pub fn start_tutorial(app: tauri::AppHandle, tour: String) -> String {
+    let app_state = app.state::<AppState>();
+
+    let mut tutorial = app_state.tutorial.lock();
+    *tutorial = Tour::parse(&tour).map(Tutorial::new);
+
+    render_tutorial(tutorial.as_ref()).into_string()
+}
+
+#[tauri::command]
+pub fn next_tutorial_step(app: tauri::AppHandle) -> String {
+    let app_state = app.state::<AppState>();
+
+    let mut tutorial = app_state.tutorial.lock();
+    if let Some(tutorial) = tutorial.as_mut() {
+        tutorial.next();
+    }
+
+    render_tutorial(tutorial.as_ref()).into_string()
+}
+
+#[tauri::command]
+pub fn stop_tutorial(app: tauri::AppHandle) -> String {
+    *app.state::<AppState>().tutorial.lock() = None;
+
+    render_tutorial(None).into_string()
+}
+
+pub fn tutorial_running(app: &tauri::AppHandle) -> bool {
+    app.state::<AppState>().tutorial.lock().is_some()
+}
+
+//called by the handlers the steps wait for, the panel is updated through an event
+pub fn tutorial_act(app: &tauri::AppHandle, action: TutorialAction) {
+    let app_state = app.state::<AppState>();
+
+    let mut tutorial = app_state.tutorial.lock();
+    let Some(running) = tutorial.as_mut() else {
+        return;
+    };
+
+    if running.act(action) {
+        let _ = app.emit(
+            "tutorial_step",
+            render_tutorial(Some(running)).into_string(),
+        );
+    }
+}
+
+//nothing leaves during the tutorial, the file is shown on the last step
+pub fn send_to_sandbox(
+    app: &tauri::AppHandle,
+    mail: &MailSender,
+    other_people: Vec<Person>,
+    config: &Config,
+) -> Result<(), MailSenderError> {
+    let path = sandbox_send(app, mail, other_people, config)?;
+
+    if let Some(tutorial) = app.state::<AppState>().tutorial.lock().as_mut() {
+        tutorial.set_sandbox_file(path);
+    }
+
+    tutorial_act(app, TutorialAction::Send);
+
+    Ok(())
+}
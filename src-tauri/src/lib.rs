@@ -2,6 +2,7 @@
 
 //external imports
 
+use chrono::NaiveDateTime;
 use parking_lot::Mutex;
 use tauri::Manager;
 
@@ -10,17 +11,39 @@ use tauri::Manager;
 //import backend for AppState
 
 mod backend {
+    pub mod approval_queue;
     pub mod config;
+    pub mod contact_exchange;
+    pub mod domain_suggestions;
     pub mod error_handling;
+    pub mod integrity;
     pub mod mail_list_utils;
     pub mod mail_sender;
     pub mod other_mail_utils;
+    pub mod scheduled_sends;
+    pub mod schema;
+    pub mod send_history;
+    pub mod send_presets;
+    pub mod send_stats;
+    pub mod sent_archive;
+    pub mod storage;
+    pub mod tutorial;
 }
 
-use crate::backend::config::Config;
+use crate::backend::approval_queue::ApprovalQueue;
+use crate::backend::config::{Config, ProfileChoice};
+use crate::backend::contact_exchange::ContactImport;
+use crate::backend::integrity::{check_all, IntegrityIssue};
 use crate::backend::mail_list_utils::MailList;
-use crate::backend::mail_sender::MailSender;
+use crate::backend::mail_sender::{MailSender, PendingSend};
 use crate::backend::other_mail_utils::OtherMailList;
+use crate::backend::scheduled_sends::{start_scheduler, ScheduledSends};
+use crate::backend::send_history::SendHistory;
+use crate::backend::send_presets::SendPresets;
+use crate::backend::send_stats::SendStats;
+use crate::backend::sent_archive::{purge_archive, SentArchive};
+use crate::backend::storage::{release_all_saving, DataPaths};
+use crate::backend::tutorial::Tutorial;
 
 struct AppState {
     mail: Mutex<MailSender>,
@@ -28,6 +51,23 @@ struct AppState {
     other_mail_list: Mutex<OtherMailList>,
     settings_current_person_id: Mutex<Option<usize>>,
     config: Mutex<Config>,
+    integrity_issues: Mutex<Vec<IntegrityIssue>>,
+    contact_import: Mutex<Option<ContactImport>>,
+    send_stats: Mutex<SendStats>,
+    recipient_search: Mutex<String>,
+    confirmed_external: Mutex<Vec<String>>, //external recipients confirmed for the next sending
+    send_summary_confirmed: Mutex<bool>,
+    pending_send: Mutex<Option<PendingSend>>, //message waiting for the undo delay
+    schedule_at: Mutex<Option<NaiveDateTime>>, //set while "odeslat později" is in progress
+    scheduled_sends: Mutex<ScheduledSends>,
+    send_history: Mutex<SendHistory>,
+    approval_queue: Mutex<ApprovalQueue>,
+    approver_name: Mutex<String>, //who approves in settings, asked once per session
+    current_sender: Mutex<Option<usize>>, //contact id of the mechanic who sends
+    profile_choice: Mutex<ProfileChoice>,
+    send_presets: Mutex<SendPresets>,
+    sent_archive: Mutex<SentArchive>,
+    tutorial: Mutex<Option<Tutorial>>, //running guided tour, its sends go to the sandbox
 }
 
 //---------------------------
@@ -36,11 +76,23 @@ struct AppState {
 
 mod handlers {
     pub mod app_settings;
+    pub mod approvals;
     pub mod config_edit;
+    pub mod contact_editor;
+    pub mod contact_exchange;
+    pub mod distribution_lists;
     pub mod feedback;
+    pub mod integrity;
     pub mod mail_send;
     pub mod manuals;
     pub mod other_mail;
+    pub mod scheduled_sends;
+    pub mod send_history;
+    pub mod send_presets;
+    pub mod sender;
+    pub mod sender_profiles;
+    pub mod sent_archive;
+    pub mod tutorial;
 }
 
 /*
@@ -58,8 +110,15 @@ app_settings:
     - wrong_mail_warning
     - close_wrong_mail_warning
     - close_settings
-    - load_settings_mechanics
-    - load_settings_technics
+    - load_settings_categories
+    - reset_settings_bottom_bar
+    - add_category
+    - edit_category
+    - edit_category_name
+    - move_category_up
+    - move_category_down
+    - remove_category
+    - add_contact
     - edit_person
     - mark_person
     - unmark_person
@@ -84,11 +143,75 @@ config edit
     - save_feedback_recepient
     - save_feedback_subject
     - save_settings_password
+    - save_internal_domains
+    - save_allowed_domains
+    - save_blocked_domains
+    - save_send_confirmation
+    - save_undo_delay
+    - save_archive_retention_days
+    - save_approval
+    - save_approval_roles
+    - save_signature
+    - add_profile
+    - remove_profile
+    - edit_profile
+    - save_group_profile
 */
 use crate::handlers::config_edit::*;
 
 //---------------------------
 
+/*
+contact_editor
+    - open_contact_editor
+    - close_contact_editor
+    - edit_contact_role
+    - edit_contact_phone
+    - edit_contact_pin
+    - edit_contact_notes
+    - toggle_contact_active
+    - add_contact_address
+    - remove_contact_address
+    - set_contact_primary_address
+    - edit_contact_address_label
+    - edit_contact_address_mail
+    - apply_contact_address_suggestion
+*/
+use crate::handlers::contact_editor::*;
+
+//---------------------------
+
+/*
+contact_exchange
+    - open_contact_exchange
+    - close_contact_exchange
+    - import_contacts
+    - map_import_column
+    - apply_contact_import
+    - export_contacts_csv
+    - export_contacts_vcard
+*/
+use crate::handlers::contact_exchange::*;
+
+//---------------------------
+
+/*
+distribution_lists
+    - select_distribution_list
+    - deselect_distribution_list
+    - load_settings_distribution_lists
+    - add_distribution_list
+    - open_distribution_list
+    - close_distribution_list
+    - edit_distribution_list_name
+    - toggle_distribution_list_member
+    - edit_distribution_list_addresses
+    - remove_distribution_list
+*/
+use crate::handlers::distribution_lists::*;
+
+//---------------------------
+
 /*
 feedback
     - open_feedback
@@ -100,15 +223,35 @@ use crate::handlers::feedback::*;
 
 //---------------------------
 
+/*
+integrity
+    - load_integrity_banner
+    - close_integrity_banner
+*/
+use crate::handlers::integrity::*;
+
+//---------------------------
+
 /*
 mail_send
     - send
     - open_send_error
+    - open_external_confirmation
+    - confirm_external_recipients
+    - open_send_summary
+    - confirm_send_summary
+    - open_send_countdown
+    - send_countdown
+    - undo_send
     - close_send_error
-    - load_mechanics
-    - load_technics
+    - load_categories
+    - search_recipients
+    - load_other_buttons
     - add_person
     - remove_person
+    - open_address_picker
+    - close_address_picker
+    - pick_person_address
     - pick_file
 */
 use crate::handlers::mail_send::*;
@@ -133,39 +276,174 @@ other_mail
     - add_other_mail_row
     - remove_other_row
     - edit_mail
+    - apply_mail_suggestion
+    - keep_mail
     - mark_other
     - unmark_other
+    - promote_address
+    - drop_invalid_and_close_other
 */
 use crate::handlers::other_mail::*;
 
 //---------------------------
 
+/*
+scheduled_sends
+    - open_schedule_send
+    - save_schedule_time
+    - schedule_send
+    - open_scheduled_sends
+    - reschedule_send
+    - cancel_scheduled_send
+    - send_scheduled_now
+*/
+use crate::handlers::scheduled_sends::*;
+
+//---------------------------
+
+/*
+approvals
+    - open_approvals
+    - close_approvals
+    - save_approver_name
+    - save_approval_note
+    - open_approval_attachment
+    - approve_request
+    - reject_request
+    - open_approval_notice
+*/
+use crate::handlers::approvals::*;
+
+//---------------------------
+
+/*
+sender
+    - load_sender_button
+    - open_sender_picker
+    - pick_sender
+    - identify_by_pin
+*/
+use crate::handlers::sender::*;
+
+//---------------------------
+
+/*
+sender_profiles
+    - load_profile_select
+    - save_profile_choice
+*/
+use crate::handlers::sender_profiles::*;
+
+//---------------------------
+
+/*
+send_presets
+    - load_preset_buttons
+    - apply_preset
+    - open_send_presets
+    - close_send_presets
+    - load_send_preset_list
+    - add_send_preset
+    - open_send_preset
+    - edit_send_preset
+    - toggle_send_preset_contact
+    - toggle_send_preset_list
+    - remove_send_preset
+*/
+use crate::handlers::send_presets::*;
+
+//---------------------------
+
+/*
+send_history
+    - open_send_history
+    - resend_from_history
+    - forward_from_history
+    - verify_send_history
+    - export_history_proof
+*/
+use crate::handlers::send_history::*;
+
+//---------------------------
+
+/*
+sent_archive
+    - open_sent_archive
+    - close_sent_archive
+    - search_sent_archive
+    - open_archived_message
+    - export_archived_message
+    - resend_archived_message
+*/
+use crate::handlers::sent_archive::*;
+
+//---------------------------
+
+/*
+tutorial
+    - load_tutorial
+    - start_tutorial
+    - next_tutorial_step
+    - stop_tutorial
+*/
+use crate::handlers::tutorial::*;
+
+//---------------------------
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     tauri::Builder::default()
         .setup(|app| {
+            //must be managed first, config and mail list are loaded from these paths
+            app.manage(DataPaths::resolve(app.app_handle().clone()));
+            let mail_list = MailList::load_list(app.app_handle().clone());
+            let config = Config::load_config(app.app_handle().clone());
+            let integrity_issues = check_all(&mail_list, &config);
             app.manage(AppState {
                 mail: MailSender::default().into(),
-                mail_list: MailList::load_list(app.app_handle().clone()).into(),
+                mail_list: mail_list.into(),
                 other_mail_list: OtherMailList::default().into(),
                 settings_current_person_id: None.into(),
-                config: Config::load_config(app.app_handle().clone()).into(),
+                config: config.into(),
+                integrity_issues: integrity_issues.into(),
+                contact_import: None.into(),
+                send_stats: SendStats::load(app.app_handle().clone()).into(),
+                recipient_search: String::new().into(),
+                confirmed_external: Vec::new().into(),
+                send_summary_confirmed: false.into(),
+                pending_send: None.into(),
+                schedule_at: None.into(),
+                scheduled_sends: ScheduledSends::load(app.app_handle().clone()).into(),
+                send_history: SendHistory::load(app.app_handle().clone()).into(),
+                approval_queue: ApprovalQueue::load(app.app_handle().clone()).into(),
+                approver_name: String::new().into(),
+                current_sender: None.into(),
+                profile_choice: ProfileChoice::Auto.into(),
+                send_presets: SendPresets::load(app.app_handle().clone()).into(),
+                sent_archive: SentArchive::load(app.app_handle().clone()).into(),
+                tutorial: None.into(),
             });
+            purge_archive(app.app_handle());
+            start_scheduler(app.app_handle().clone());
             Ok(())
         })
         .plugin(tauri_plugin_dialog::init())
+        .plugin(tauri_plugin_opener::init())
         .invoke_handler(tauri::generate_handler![
             //seřadit
             pick_file,
             send,
-            load_mechanics,
-            load_technics,
+            load_categories,
+            search_recipients,
+            load_other_buttons,
             open_other,
             add_other_mail_row,
             close_other,
             add_person,
             remove_person,
             edit_mail,
+            apply_mail_suggestion,
+            keep_mail,
             remove_other_row,
             open_manual,
             close_manual,
@@ -181,8 +459,15 @@ pub fn run() {
             open_settings_manual,
             close_settings_manual,
             save_and_close_settings,
-            load_settings_mechanics,
-            load_settings_technics,
+            load_settings_categories,
+            reset_settings_bottom_bar,
+            add_category,
+            edit_category,
+            edit_category_name,
+            move_category_up,
+            move_category_down,
+            remove_category,
+            add_contact,
             edit_person,
             mark_person,
             unmark_person,
@@ -198,6 +483,14 @@ pub fn run() {
             save_feedback_recepient,
             save_feedback_subject,
             save_settings_password,
+            save_internal_domains,
+            save_allowed_domains,
+            save_blocked_domains,
+            save_send_confirmation,
+            save_undo_delay,
+            save_archive_retention_days,
+            save_approval,
+            save_approval_roles,
             close_wrong_mail_warning,
             close_settings,
             open_discard_overlay,
@@ -208,8 +501,110 @@ pub fn run() {
             mark_other,
             unmark_other,
             open_send_error,
-            close_send_error
+            open_external_confirmation,
+            confirm_external_recipients,
+            open_send_summary,
+            confirm_send_summary,
+            open_send_countdown,
+            send_countdown,
+            undo_send,
+            close_send_error,
+            load_integrity_banner,
+            close_integrity_banner,
+            select_distribution_list,
+            deselect_distribution_list,
+            load_settings_distribution_lists,
+            add_distribution_list,
+            open_distribution_list,
+            close_distribution_list,
+            edit_distribution_list_name,
+            toggle_distribution_list_member,
+            edit_distribution_list_addresses,
+            remove_distribution_list,
+            open_contact_exchange,
+            close_contact_exchange,
+            import_contacts,
+            map_import_column,
+            apply_contact_import,
+            export_contacts_csv,
+            export_contacts_vcard,
+            open_address_picker,
+            close_address_picker,
+            pick_person_address,
+            promote_address,
+            drop_invalid_and_close_other,
+            open_contact_editor,
+            close_contact_editor,
+            edit_contact_role,
+            edit_contact_phone,
+            edit_contact_pin,
+            edit_contact_notes,
+            toggle_contact_active,
+            add_contact_address,
+            remove_contact_address,
+            set_contact_primary_address,
+            edit_contact_address_label,
+            edit_contact_address_mail,
+            apply_contact_address_suggestion,
+            open_schedule_send,
+            save_schedule_time,
+            schedule_send,
+            open_scheduled_sends,
+            reschedule_send,
+            cancel_scheduled_send,
+            send_scheduled_now,
+            open_approvals,
+            close_approvals,
+            save_approver_name,
+            save_approval_note,
+            open_approval_attachment,
+            approve_request,
+            reject_request,
+            open_approval_notice,
+            load_sender_button,
+            open_sender_picker,
+            pick_sender,
+            identify_by_pin,
+            load_profile_select,
+            save_profile_choice,
+            load_preset_buttons,
+            apply_preset,
+            open_send_presets,
+            close_send_presets,
+            load_send_preset_list,
+            add_send_preset,
+            open_send_preset,
+            edit_send_preset,
+            toggle_send_preset_contact,
+            toggle_send_preset_list,
+            remove_send_preset,
+            open_send_history,
+            resend_from_history,
+            forward_from_history,
+            verify_send_history,
+            export_history_proof,
+            open_sent_archive,
+            close_sent_archive,
+            search_sent_archive,
+            open_archived_message,
+            export_archived_message,
+            resend_archived_message,
+            load_tutorial,
+            start_tutorial,
+            next_tutorial_step,
+            stop_tutorial,
+            add_profile,
+            remove_profile,
+            edit_profile,
+            save_group_profile,
+            save_signature
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while running tauri application")
+        .run(|_, event| {
+            //saves held for an unanswered backup dialog aren't lost
+            if let tauri::RunEvent::Exit = event {
+                let _ = release_all_saving();
+            }
+        });
 }
@@ -0,0 +1,77 @@
+// keyboard navigation for the recipient search on the main screen
+// arrows move between found people, enter / space toggles the focused one
+
+function resultButtons() {
+  return Array.from(
+    document.querySelectorAll("#category-sections button[data-person-id]:not([disabled])")
+  );
+}
+
+document.addEventListener("keydown", (event) => {
+  const search = document.getElementById("recipient-search");
+  if (!search) {
+    return;
+  }
+
+  const buttons = resultButtons();
+  const index = buttons.indexOf(document.activeElement);
+
+  if (document.activeElement === search) {
+    if (event.key === "ArrowDown" && buttons.length > 0) {
+      event.preventDefault();
+      buttons[0].focus();
+    } else if (event.key === "Enter" && buttons.length > 0) {
+      event.preventDefault();
+      buttons[0].click();
+    }
+    return;
+  }
+
+  if (index === -1) {
+    return;
+  }
+
+  switch (event.key) {
+    case "ArrowDown":
+    case "ArrowRight":
+      event.preventDefault();
+      buttons[Math.min(index + 1, buttons.length - 1)].focus();
+      break;
+    case "ArrowUp":
+    case "ArrowLeft":
+      event.preventDefault();
+      if (index === 0) {
+        search.focus();
+      } else {
+        buttons[index - 1].focus();
+      }
+      break;
+    case "Escape":
+      event.preventDefault();
+      search.focus();
+      break;
+  }
+});
+
+// toggled button is swapped by htmx, so focus is moved to the new one
+let focusedPersonId = null;
+
+document.addEventListener("htmx:beforeRequest", (event) => {
+  const element = event.detail.elt;
+  focusedPersonId =
+    element && element === document.activeElement ? element.dataset.personId : null;
+});
+
+document.addEventListener("htmx:afterSettle", () => {
+  if (!focusedPersonId) {
+    return;
+  }
+
+  const button = document.querySelector(
+    `#category-sections button[data-person-id="${focusedPersonId}"]`
+  );
+  if (button) {
+    button.focus();
+  }
+  focusedPersonId = null;
+});